@@ -0,0 +1,392 @@
+//! A task executor that doesn't require allocation.
+//!
+//! An [`Executor`] owns a fixed number of statically allocated task slots, each of which can hold a
+//! future of up to a fixed size.  Tasks are added through a [`Spawner`], and are polled whenever
+//! their waker has been woken.  Wakers only set a flag, so it is fine to wake them from interrupt
+//! handlers (like the ones that drive timers or SPI transfers on a device).
+//!
+//! The executor is meant to be put in a `static`, so that tasks can live for the rest of the
+//! program:
+//!
+//! ```ignore
+//! static EXECUTOR: executor::Executor<8, 1024> = executor::Executor::new();
+//!
+//! EXECUTOR.block_on(|spawner| async move { spawner.spawn(blink()) }, wait_for_interrupt)
+//! ```
+use core::alloc;
+use core::cell;
+use core::fmt;
+use core::future;
+use core::marker;
+use core::mem;
+use core::pin;
+use core::ptr;
+use core::sync::atomic;
+use core::task;
+
+const FREE: u8 = 0;
+const RESERVED: u8 = 1;
+const OCCUPIED: u8 = 2;
+
+/// The alignment of the storage of every task slot.
+///
+/// Futures that require a larger alignment than this can't be spawned.
+pub const TASK_ALIGN: usize = 8;
+
+/// An error that can occur when spawning a task.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SpawnError {
+    /// All task slots are currently in use.
+    NoFreeSlot,
+    /// The future does not fit in a task slot.
+    TooLarge,
+}
+
+/// An executor with `TASKS` statically allocated task slots of `SIZE` bytes each.
+///
+/// In addition to the spawned tasks, the executor can run one "main" future through
+/// [`Executor::block_on`], which is not stored in a slot.
+pub struct Executor<const TASKS: usize, const SIZE: usize> {
+    running: atomic::AtomicBool,
+    main_ready: atomic::AtomicBool,
+    slots: [Slot<SIZE>; TASKS],
+}
+
+#[repr(C, align(8))]
+struct Storage<const SIZE: usize>([mem::MaybeUninit<u8>; SIZE]);
+
+struct Slot<const SIZE: usize> {
+    state: atomic::AtomicU8,
+    ready: atomic::AtomicBool,
+    vtable: cell::Cell<Option<TaskVTable>>,
+    storage: cell::UnsafeCell<Storage<SIZE>>,
+}
+
+#[derive(Clone, Copy)]
+struct TaskVTable {
+    poll: unsafe fn(*mut u8, &mut task::Context<'_>) -> task::Poll<()>,
+    drop: unsafe fn(*mut u8),
+}
+
+/// A handle that can be used to spawn tasks onto a running [`Executor`].
+///
+/// Spawners are handed out by [`Executor::block_on`].  The handle can be freely copied into tasks,
+/// but can't leave the thread that runs the executor.
+#[derive(Clone, Copy)]
+pub struct Spawner {
+    slots: &'static dyn Slots,
+    phantom: marker::PhantomData<*const ()>,
+}
+
+trait Slots: Sync {
+    fn reserve(&self, size: usize, align: usize) -> Result<(usize, *mut u8), SpawnError>;
+
+    fn commit(&self, index: usize, vtable: TaskVTable);
+}
+
+// Safety: the storage and vtable of a slot are owned by whoever moved its state away from `FREE`.
+// A spawner claims a free slot with a compare-and-swap, and only writes to it until it publishes
+// the task by storing `OCCUPIED` with release ordering.  From then on only the thread that runs the
+// executor (of which there is one at a time, guaranteed by the `running` flag) touches it, until it
+// frees the slot again with release ordering.  Tasks are `Send`, so it doesn't matter if a task is
+// polled or dropped by a different thread than the one that spawned it, e.g. when it is left over
+// from one call to [`Executor::block_on`] and keeps running on the next one.  Spawners are only
+// handed out by [`Executor::block_on`] and can't leave the thread that they were handed out on,
+// while other threads and interrupt handlers only touch the atomic ready flags through wakers.
+unsafe impl<const TASKS: usize, const SIZE: usize> Sync for Executor<TASKS, SIZE> {}
+
+impl<const SIZE: usize> Slot<SIZE> {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NEW: Self = Slot {
+        state: atomic::AtomicU8::new(FREE),
+        ready: atomic::AtomicBool::new(false),
+        vtable: cell::Cell::new(None),
+        storage: cell::UnsafeCell::new(Storage([mem::MaybeUninit::uninit(); SIZE])),
+    };
+
+    fn poll(&'static self) {
+        if self.state.load(atomic::Ordering::Acquire) != OCCUPIED
+            || !self.ready.load(atomic::Ordering::Acquire)
+        {
+            return;
+        }
+        // Clear the flag before polling, so that wakeups that happen during the poll are not lost.
+        self.ready.store(false, atomic::Ordering::Release);
+
+        let vtable = self.vtable.get().expect("occupied task slot has no vtable");
+        let data = self.storage.get() as *mut u8;
+        let waker = waker(&self.ready);
+        let mut cx = task::Context::from_waker(&waker);
+
+        // Safety: the slot is occupied, so the storage holds a future of the type that the vtable
+        // was created for.  It is never moved out of the slot, so it stays pinned.
+        if unsafe { (vtable.poll)(data, &mut cx) }.is_ready() {
+            unsafe { (vtable.drop)(data) };
+            self.vtable.set(None);
+            self.state.store(FREE, atomic::Ordering::Release);
+        }
+    }
+}
+
+impl<const TASKS: usize, const SIZE: usize> Executor<TASKS, SIZE> {
+    /// Creates a new executor with all of its task slots free.
+    pub const fn new() -> Self {
+        Executor {
+            running: atomic::AtomicBool::new(false),
+            main_ready: atomic::AtomicBool::new(false),
+            slots: [Slot::NEW; TASKS],
+        }
+    }
+
+    /// Whether there is a task that has been woken and is waiting to be polled.
+    ///
+    /// This can be used by the `wait` function passed to [`Executor::block_on`] and
+    /// [`Executor::run`] to check whether it is safe to sleep, e.g. inside of a critical section.
+    pub fn has_ready_tasks(&self) -> bool {
+        self.main_ready.load(atomic::Ordering::Acquire)
+            || self.slots.iter().any(|slot| {
+                slot.state.load(atomic::Ordering::Acquire) == OCCUPIED
+                    && slot.ready.load(atomic::Ordering::Acquire)
+            })
+    }
+
+    /// Whether any spawned task is still running, i.e. hasn't completed yet.
+    pub fn has_tasks(&self) -> bool {
+        self.slots
+            .iter()
+            .any(|slot| slot.state.load(atomic::Ordering::Acquire) != FREE)
+    }
+
+    /// Runs the future created by `main` to completion, while also running any spawned tasks.
+    ///
+    /// Whenever no task is ready to make progress, `wait` is called.  On a device, it would
+    /// typically wait for an interrupt to happen.  Spawned tasks that are still running when the
+    /// main future completes stay in their slots, and will continue to run on the next call to
+    /// [`Executor::block_on`] or [`Executor::run`].
+    ///
+    /// Panics if the executor is already running.
+    pub fn block_on<M, F, W>(&'static self, main: M, mut wait: W) -> F::Output
+    where
+        M: FnOnce(Spawner) -> F,
+        F: future::Future,
+        W: FnMut(),
+    {
+        let _running = self.enter();
+
+        let mut future = main(self.spawner());
+        // Safety: the future is shadowed and can never be moved again.
+        let mut future = unsafe { pin::Pin::new_unchecked(&mut future) };
+        let waker = waker(&self.main_ready);
+        let mut cx = task::Context::from_waker(&waker);
+
+        self.main_ready.store(true, atomic::Ordering::Release);
+        loop {
+            if self.main_ready.load(atomic::Ordering::Acquire) {
+                self.main_ready.store(false, atomic::Ordering::Release);
+                if let task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                    return output;
+                }
+            }
+            self.poll_slots();
+            if !self.has_ready_tasks() {
+                wait();
+            }
+        }
+    }
+
+    /// Runs spawned tasks forever.
+    ///
+    /// Whenever no task is ready to make progress, `wait` is called.
+    ///
+    /// Panics if the executor is already running.
+    pub fn run<W>(&'static self, mut wait: W) -> !
+    where
+        W: FnMut(),
+    {
+        let _running = self.enter();
+
+        loop {
+            // There is no main future any more, so ignore any stale wakeups of it.
+            self.main_ready.store(false, atomic::Ordering::Release);
+            self.poll_slots();
+            if !self.has_ready_tasks() {
+                wait();
+            }
+        }
+    }
+
+    /// Polls spawned tasks until none of them are ready to make progress, and then returns.
+    ///
+    /// This is mostly useful for driving tasks step by step in tests.
+    ///
+    /// Panics if the executor is already running.
+    pub fn run_until_stalled(&'static self) {
+        let _running = self.enter();
+
+        self.main_ready.store(false, atomic::Ordering::Release);
+        while self.has_ready_tasks() {
+            self.poll_slots();
+        }
+    }
+
+    /// Creates a handle that can spawn tasks onto this executor.
+    fn spawner(&'static self) -> Spawner {
+        let slots = self;
+        let phantom = marker::PhantomData;
+        Spawner { slots, phantom }
+    }
+
+    fn enter(&self) -> Running<'_> {
+        if self.running.swap(true, atomic::Ordering::AcqRel) {
+            panic!("the executor is already running");
+        }
+        Running(&self.running)
+    }
+
+    fn poll_slots(&'static self) {
+        for slot in self.slots.iter() {
+            slot.poll();
+        }
+    }
+}
+
+impl<const TASKS: usize, const SIZE: usize> Slots for Executor<TASKS, SIZE> {
+    fn reserve(&self, size: usize, align: usize) -> Result<(usize, *mut u8), SpawnError> {
+        if size > SIZE || align > TASK_ALIGN {
+            return Err(SpawnError::TooLarge);
+        }
+
+        for (index, slot) in self.slots.iter().enumerate() {
+            let reserved = slot.state.compare_exchange(
+                FREE,
+                RESERVED,
+                atomic::Ordering::AcqRel,
+                atomic::Ordering::Acquire,
+            );
+            if reserved.is_ok() {
+                return Ok((index, slot.storage.get() as *mut u8));
+            }
+        }
+
+        Err(SpawnError::NoFreeSlot)
+    }
+
+    fn commit(&self, index: usize, vtable: TaskVTable) {
+        let slot = &self.slots[index];
+        slot.vtable.set(Some(vtable));
+        slot.ready.store(true, atomic::Ordering::Release);
+        slot.state.store(OCCUPIED, atomic::Ordering::Release);
+    }
+}
+
+impl<const TASKS: usize, const SIZE: usize> Default for Executor<TASKS, SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const TASKS: usize, const SIZE: usize> fmt::Debug for Executor<TASKS, SIZE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let occupied = self
+            .slots
+            .iter()
+            .filter(|slot| slot.state.load(atomic::Ordering::Acquire) != FREE)
+            .count();
+        f.debug_struct("Executor")
+            .field("tasks", &TASKS)
+            .field("size", &SIZE)
+            .field("occupied", &occupied)
+            .finish()
+    }
+}
+
+impl Spawner {
+    /// Spawns a new task that will run the specified future to completion.
+    ///
+    /// The future has to be `Send`, since a task that is still running when
+    /// [`Executor::block_on`] returns continues to run on the next call, which may happen on a
+    /// different thread.
+    ///
+    /// ```compile_fail
+    /// # use embedded_platform_core::executor::Executor;
+    /// # use std::rc::Rc;
+    /// static EXECUTOR: Executor<1, 64> = Executor::new();
+    ///
+    /// let shared = Rc::new(());
+    /// EXECUTOR.block_on(|spawner| async move { spawner.spawn(async move { drop(shared) }) }, || {});
+    /// ```
+    ///
+    /// Fails if all task slots are in use, or if the future is too large to fit in a task slot.
+    pub fn spawn<F>(&self, future: F) -> Result<(), SpawnError>
+    where
+        F: future::Future<Output = ()> + Send + 'static,
+    {
+        let layout = alloc::Layout::new::<F>();
+        let (index, data) = self.slots.reserve(layout.size(), layout.align())?;
+        // Safety: the reserved storage is large enough and sufficiently aligned for `F`, and no
+        // one else is accessing it until it has been committed.
+        unsafe { ptr::write(data as *mut F, future) };
+        self.slots.commit(
+            index,
+            TaskVTable {
+                poll: poll_task::<F>,
+                drop: drop_task::<F>,
+            },
+        );
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Spawner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Spawner").finish()
+    }
+}
+
+impl fmt::Display for SpawnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpawnError::NoFreeSlot => f.write_str("all task slots are in use"),
+            SpawnError::TooLarge => f.write_str("the task does not fit in a task slot"),
+        }
+    }
+}
+
+struct Running<'a>(&'a atomic::AtomicBool);
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, atomic::Ordering::Release);
+    }
+}
+
+unsafe fn poll_task<F>(data: *mut u8, cx: &mut task::Context<'_>) -> task::Poll<()>
+where
+    F: future::Future<Output = ()>,
+{
+    pin::Pin::new_unchecked(&mut *(data as *mut F)).poll(cx)
+}
+
+unsafe fn drop_task<F>(data: *mut u8) {
+    ptr::drop_in_place(data as *mut F)
+}
+
+static WAKER_VTABLE: task::RawWakerVTable =
+    task::RawWakerVTable::new(clone_waker, wake_waker, wake_waker, drop_waker);
+
+fn waker(ready: &'static atomic::AtomicBool) -> task::Waker {
+    let data: *const atomic::AtomicBool = ready;
+    let data = data.cast::<()>();
+    // Safety: the ready flag lives in a static executor, so it outlives every waker.
+    unsafe { task::Waker::from_raw(task::RawWaker::new(data, &WAKER_VTABLE)) }
+}
+
+unsafe fn clone_waker(data: *const ()) -> task::RawWaker {
+    task::RawWaker::new(data, &WAKER_VTABLE)
+}
+
+unsafe fn wake_waker(data: *const ()) {
+    (*(data as *const atomic::AtomicBool)).store(true, atomic::Ordering::Release);
+}
+
+unsafe fn drop_waker(_data: *const ()) {}
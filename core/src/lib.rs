@@ -5,12 +5,12 @@
     missing_copy_implementations,
     trivial_casts,
     trivial_numeric_casts,
+    unstable_features,
     unused_import_braces,
     unused_qualifications,
     clippy::all
 )]
-#![feature(thread_local, generator_trait, optin_builtin_traits)]
 
-pub mod future;
+pub mod executor;
 
 pub use core::*;
@@ -0,0 +1,128 @@
+use embedded_platform_core::executor::{Executor, SpawnError};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::task::{Context, Poll, Waker};
+
+/// A gate that tasks can wait for until it is opened.
+struct Gate {
+    open: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+struct Wait(&'static Gate);
+
+impl Gate {
+    const fn new() -> Self {
+        Gate {
+            open: AtomicBool::new(false),
+            waker: Mutex::new(None),
+        }
+    }
+
+    fn wait(&'static self) -> Wait {
+        Wait(self)
+    }
+
+    fn open(&self) {
+        self.open.store(true, Ordering::Release);
+        if let Some(waker) = self.waker.lock().unwrap().take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Future for Wait {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.0.open.load(Ordering::Acquire) {
+            Poll::Ready(())
+        } else {
+            *self.0.waker.lock().unwrap() = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+#[test]
+fn tasks_are_polled_in_spawn_order_and_again_when_woken() {
+    static EXECUTOR: Executor<4, 256> = Executor::new();
+    static GATE: Gate = Gate::new();
+    static LOG: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+
+    EXECUTOR.block_on(
+        |spawner| async move {
+            spawner
+                .spawn(async { LOG.lock().unwrap().push("a") })
+                .unwrap();
+            spawner
+                .spawn(async {
+                    GATE.wait().await;
+                    LOG.lock().unwrap().push("b");
+                })
+                .unwrap();
+            spawner
+                .spawn(async { LOG.lock().unwrap().push("c") })
+                .unwrap();
+            LOG.lock().unwrap().push("main");
+        },
+        || {},
+    );
+    assert_eq!(*LOG.lock().unwrap(), vec!["main"]);
+
+    EXECUTOR.run_until_stalled();
+    assert_eq!(*LOG.lock().unwrap(), vec!["main", "a", "c"]);
+    assert!(!EXECUTOR.has_ready_tasks());
+
+    GATE.open();
+    assert!(EXECUTOR.has_ready_tasks());
+    EXECUTOR.run_until_stalled();
+    assert_eq!(*LOG.lock().unwrap(), vec!["main", "a", "c", "b"]);
+}
+
+#[test]
+fn spawning_fails_when_slots_run_out() {
+    static EXECUTOR: Executor<2, 64> = Executor::new();
+    static GATE: Gate = Gate::new();
+
+    let results = EXECUTOR.block_on(
+        |spawner| async move {
+            let large = [0u8; 128];
+            [
+                spawner.spawn(GATE.wait()),
+                spawner.spawn(async move { assert_eq!(large.len(), 128) }),
+                spawner.spawn(GATE.wait()),
+                spawner.spawn(GATE.wait()),
+            ]
+        },
+        || {},
+    );
+    assert_eq!(
+        results,
+        [
+            Ok(()),
+            Err(SpawnError::TooLarge),
+            Ok(()),
+            Err(SpawnError::NoFreeSlot)
+        ]
+    );
+}
+
+#[test]
+fn slots_are_reused_after_tasks_complete() {
+    static EXECUTOR: Executor<1, 64> = Executor::new();
+    static GATE: Gate = Gate::new();
+
+    let spawn = || EXECUTOR.block_on(|spawner| async move { spawner.spawn(GATE.wait()) }, || {});
+    assert_eq!(spawn(), Ok(()));
+    EXECUTOR.run_until_stalled();
+    assert_eq!(spawn(), Err(SpawnError::NoFreeSlot));
+
+    GATE.open();
+    EXECUTOR.run_until_stalled();
+    assert_eq!(spawn(), Ok(()));
+    EXECUTOR.run_until_stalled();
+    assert_eq!(spawn(), Ok(()));
+}
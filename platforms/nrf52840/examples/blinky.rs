@@ -24,8 +24,9 @@ use panic_halt as _;
 
 #[cortex_m_rt::entry]
 fn main() -> ! {
-    nrf52840_platform::ParticleArgon::main(|mut platform| async {
-        let timer = platform.take_timer0().into_periodic_timer(1.0.hz())?;
+    let policy = embedded_platform::platform::policy::Policy::new();
+    nrf52840_platform::ParticleArgon::main(policy, |mut platform, _spawner| async move {
+        let timer = platform.take_timer0().into_periodic_timer(1.hz())?;
         feather_blink(platform, timer).await?;
         Ok(())
     })
@@ -37,16 +38,16 @@ async fn feather_blink<P>(
 ) -> Result<(), P::Error>
 where
     P: embedded_platform::specs::feather::Feather,
+    <P::MainLed as embedded_platform::gpio::IntoPushPullOutputPin>::PushPullOutputPin:
+        embedded_platform::gpio::StatefulOutputPin,
 {
     let mut main_led = feather.take_main_led().into_push_pull_output_pin(false)?;
-    let mut on = false;
 
     timer.start().await?;
     let mut ticks = timer.ticks();
 
     while let Some(_) = ticks.try_next().await? {
-        on = !on;
-        main_led.set(on).await?;
+        main_led.toggle().await?;
     }
 
     Ok(())
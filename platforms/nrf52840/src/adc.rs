@@ -0,0 +1,486 @@
+//! Analog inputs, using the SAADC peripheral.
+//!
+//! The pins that are connected to one of the analog inputs `AIN0-AIN7` of the SAADC can be turned
+//! into analog inputs.  Every measurement re-configures channel 0 of the SAADC for the pin that is
+//! measured, and takes a single sample.  A sample only takes around 12 µs, so measuring blocks
+//! until the sample is ready instead of waiting for an interrupt.
+//!
+//! Any of the timers can also sample an analog input continuously.  The timer triggers the samples
+//! through PPI, and the SAADC writes them into three buffers in turn using EasyDMA, so the CPU is
+//! only involved once per buffer.  A buffer that isn't taken before the next one is filled is
+//! skipped and counted as an overrun.  Since the SAADC has a single set of buffers, only one input
+//! can be sampled continuously at a time, and single measurements fail while it is.
+use crate::error;
+use crate::gpio;
+use crate::timer;
+use core::cell;
+use core::fmt;
+use core::pin;
+use core::sync::atomic;
+use core::task;
+use nrf52840_hal::gpio as hal_gpio;
+use nrf52840_hal::gpio::p0;
+use nrf52840_hal::target::interrupt;
+
+/// The resolution of the samples, in bits.
+const RESOLUTION: u32 = 12;
+/// The voltage of the internal reference, in millivolts.
+const INTERNAL_REFERENCE_MILLIVOLTS: u32 = 600;
+/// The supply voltage of the board, a quarter of which is used as the supply reference.
+const SUPPLY_MILLIVOLTS: u32 = 3300;
+/// The PPI channel that connects the timer of a sampler to the `SAMPLE` task.
+const PPI_SAMPLE: u32 = 18;
+/// The PPI channel that restarts the SAADC with the next buffer when a buffer has been filled.
+const PPI_RESTART: u32 = 19;
+
+/// The buffer that the SAADC writes single samples to.
+static RESULT: atomic::AtomicU16 = atomic::AtomicU16::new(0);
+
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY_SAMPLE: atomic::AtomicU16 = atomic::AtomicU16::new(0);
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY_BUFFER: [atomic::AtomicU16; embedded_platform::adc::BUFFER_LEN] =
+    [EMPTY_SAMPLE; embedded_platform::adc::BUFFER_LEN];
+
+/// The buffers that the SAADC writes continuous samples to, in turn.
+///
+/// There is one buffer that is being filled, one that is queued up to be filled next, and one that
+/// holds the samples that are waiting to be taken, so that the SAADC never writes to a buffer while
+/// it is being copied.
+static BUFFERS: [[atomic::AtomicU16; embedded_platform::adc::BUFFER_LEN]; 3] =
+    [EMPTY_BUFFER, EMPTY_BUFFER, EMPTY_BUFFER];
+
+/// Whether an analog input is being sampled continuously.
+static SAMPLING: atomic::AtomicBool = atomic::AtomicBool::new(false);
+
+static SAMPLER_STATE: bare_metal::Mutex<cell::RefCell<SamplerState>> =
+    bare_metal::Mutex::new(cell::RefCell::new(SamplerState {
+        filling: 0,
+        queued: 1,
+        ready: None,
+        overruns: 0,
+        waker: None,
+    }));
+
+/// A pin in analog input mode.
+#[derive(Debug)]
+pub struct AnalogPin<P> {
+    pin: P,
+    /// The index of the analog input that the pin is connected to.
+    input: u8,
+    config: embedded_platform::adc::Config,
+}
+
+/// An analog input that is sampled continuously, triggered by a timer.
+///
+/// Dropping a sampler stops sampling, so that the SAADC can be used again, but leaves its timer
+/// running; use [`Sampler::into_inner`] to get the stopped timer back.
+pub struct Sampler<P, T>
+where
+    T: Unpin,
+{
+    /// The analog input and the timer that triggers its samples, until they are handed back by
+    /// [`Sampler::into_inner`].
+    parts: Option<(AnalogPin<P>, timer::Timer<T, nrf52840_hal::timer::Periodic>)>,
+}
+
+/// The state of continuous sampling that is shared with the interrupt handler.
+struct SamplerState {
+    /// The index of the buffer that the SAADC is currently filling.
+    filling: usize,
+    /// The index of the buffer that the SAADC fills when it restarts.
+    queued: usize,
+    /// The index of the filled buffer that is waiting to be taken, if any.
+    ready: Option<usize>,
+    /// The number of buffers that were discarded since a buffer was last taken.
+    overruns: u32,
+    waker: Option<task::Waker>,
+}
+
+impl SamplerState {
+    /// The index of the buffer that is neither being filled nor waiting to be taken.
+    fn spare(&self) -> usize {
+        (0..BUFFERS.len())
+            .find(|&index| index != self.filling && Some(index) != self.ready)
+            .expect("there are more buffers than are in use")
+    }
+}
+
+impl<P> AnalogPin<P> {
+    /// Returns the underlying pin, which is left in floating input mode.
+    pub fn into_inner(self) -> P {
+        self.pin
+    }
+}
+
+impl<P> embedded_platform::adc::AnalogInputPin for AnalogPin<P>
+where
+    P: Unpin,
+{
+    type Error = error::Error;
+
+    fn poll_read(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<embedded_platform::adc::Reading, Self::Error>> {
+        task::Poll::Ready(measure(self.input, &self.config))
+    }
+}
+
+impl<P, T> fmt::Debug for Sampler<P, T>
+where
+    T: Unpin,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sampler").finish()
+    }
+}
+
+impl<P, T> Drop for Sampler<P, T>
+where
+    T: Unpin,
+{
+    fn drop(&mut self) {
+        if self.parts.is_some() {
+            stop();
+        }
+    }
+}
+
+impl<P, T> embedded_platform::adc::Sampler for Sampler<P, T>
+where
+    P: Unpin,
+    T: Unpin,
+{
+    type Error = error::Error;
+
+    fn poll_buffer(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<embedded_platform::adc::Buffer, Self::Error>> {
+        let (pin, _) = self.parts.as_ref().expect("sampler is sampling");
+        let full_scale = full_scale(&pin.config);
+        cortex_m::interrupt::free(|cs| {
+            let mut state = SAMPLER_STATE.borrow(cs).borrow_mut();
+            match state.ready.take() {
+                Some(index) => {
+                    // The SAADC wrote the samples using DMA, which the compiler doesn't know about.
+                    atomic::compiler_fence(atomic::Ordering::SeqCst);
+                    let mut samples = [0; embedded_platform::adc::BUFFER_LEN];
+                    for (sample, raw) in samples.iter_mut().zip(BUFFERS[index].iter()) {
+                        *sample = raw.load(atomic::Ordering::Relaxed) as i16;
+                    }
+                    let overruns = state.overruns;
+                    state.overruns = 0;
+                    task::Poll::Ready(Ok(embedded_platform::adc::Buffer::new(
+                        samples, RESOLUTION, full_scale, overruns,
+                    )))
+                }
+                None => {
+                    state.waker = Some(cx.waker().clone());
+                    task::Poll::Pending
+                }
+            }
+        })
+    }
+}
+
+macro_rules! analog {
+    ($($typ:ident => $input:expr,)*) => {
+        $(
+            impl<M> embedded_platform::adc::IntoAnalogInputPin for gpio::Pin<p0::$typ<M>> where M: Unpin {
+                type AnalogInputPin = AnalogPin<gpio::Pin<p0::$typ<hal_gpio::Input<hal_gpio::Floating>>>>;
+
+                fn into_analog_input_pin(
+                    self,
+                    config: embedded_platform::adc::Config,
+                ) -> Result<Self::AnalogInputPin, Self::Error> {
+                    let pin = embedded_platform::gpio::IntoFloatingInputPin::into_floating_input_pin(self)?;
+                    let input = $input;
+                    Ok(AnalogPin { pin, input, config })
+                }
+            }
+        )*
+    };
+}
+
+analog! {
+    P0_02 => 0,
+    P0_03 => 1,
+    P0_04 => 2,
+    P0_05 => 3,
+    P0_28 => 4,
+    P0_29 => 5,
+    P0_30 => 6,
+    P0_31 => 7,
+}
+
+macro_rules! sampler {
+    ($($ty:ty,)*) => {
+        $(
+            impl<P> Sampler<P, $ty> {
+                /// Stops sampling, returning the analog input and the timer, which is stopped as
+                /// well.
+                pub fn into_inner(
+                    mut self,
+                ) -> (AnalogPin<P>, timer::Timer<$ty, nrf52840_hal::timer::OneShot>) {
+                    let (pin, timer) = self.parts.take().expect("sampler is sampling");
+                    stop();
+                    (pin, timer.into_released())
+                }
+            }
+
+            impl<P, M> embedded_platform::adc::IntoSampler<AnalogPin<P>> for timer::Timer<$ty, M>
+            where
+                P: Unpin,
+                M: Unpin,
+            {
+                type Error = error::Error;
+                type Sampler = Sampler<P, $ty>;
+
+                fn into_sampler(
+                    self,
+                    pin: AnalogPin<P>,
+                    rate: embedded_platform::time::Rate,
+                ) -> Result<Self::Sampler, Self::Error> {
+                    if SAMPLING.swap(true, atomic::Ordering::AcqRel) {
+                        return Err(error::Error::Busy);
+                    }
+                    start(pin.input, &pin.config);
+                    match self.into_trigger(rate) {
+                        Ok((timer, event)) => {
+                            connect(PPI_SAMPLE, event, &saadc().tasks_sample as *const _ as u32);
+                            let parts = Some((pin, timer));
+                            Ok(Sampler { parts })
+                        }
+                        Err(err) => {
+                            stop();
+                            Err(err)
+                        }
+                    }
+                }
+            }
+        )*
+    };
+}
+
+sampler! {
+    nrf52840_hal::target::TIMER0,
+    nrf52840_hal::target::TIMER1,
+    nrf52840_hal::target::TIMER2,
+    nrf52840_hal::target::TIMER3,
+    nrf52840_hal::target::TIMER4,
+}
+
+/// Configures the parts of the SAADC that are shared by all measurements.
+pub(crate) fn enable(saadc: &nrf52840_hal::target::SAADC) {
+    saadc.resolution.write(|w| w.val()._12bit());
+    saadc.oversample.write(|w| w.oversample().bypass());
+    saadc.samplerate.write(|w| w.mode().task());
+    saadc.enable.write(|w| w.enable().enabled());
+    unsafe { cortex_m::peripheral::NVIC::unmask(nrf52840_hal::target::Interrupt::SAADC) };
+}
+
+fn saadc() -> &'static nrf52840_hal::target::saadc::RegisterBlock {
+    // Safety: the platform doesn't hand out the SAADC, and after it has been enabled, it is only
+    // accessed by measurements and samplers, which take turns using the `SAMPLING` flag, and by
+    // the interrupt handler while sampling.
+    unsafe { &*nrf52840_hal::target::SAADC::ptr() }
+}
+
+fn ppi() -> &'static nrf52840_hal::target::ppi::RegisterBlock {
+    // Safety: the platform doesn't hand out the PPI, and only the channels that are reserved for
+    // samplers are modified.
+    unsafe { &*nrf52840_hal::target::PPI::ptr() }
+}
+
+/// The input voltage in millivolts that corresponds to the full range of the SAADC.
+fn full_scale(config: &embedded_platform::adc::Config) -> u32 {
+    let reference = match config.reference {
+        embedded_platform::adc::Reference::Internal => INTERNAL_REFERENCE_MILLIVOLTS,
+        embedded_platform::adc::Reference::Supply => SUPPLY_MILLIVOLTS / 4,
+    };
+    config.full_scale(reference)
+}
+
+/// Configures channel 0 of the SAADC to measure the specified analog input.
+fn configure(input: u8, config: &embedded_platform::adc::Config) {
+    use embedded_platform::adc::Gain;
+    use embedded_platform::adc::Reference;
+
+    let saadc = saadc();
+    saadc.ch[0].config.write(|w| {
+        match config.reference {
+            Reference::Internal => w.refsel().internal(),
+            Reference::Supply => w.refsel().vdd1_4(),
+        };
+        match config.gain {
+            Gain::OneSixth => w.gain().gain1_6(),
+            Gain::OneFifth => w.gain().gain1_5(),
+            Gain::OneQuarter => w.gain().gain1_4(),
+            Gain::OneThird => w.gain().gain1_3(),
+            Gain::OneHalf => w.gain().gain1_2(),
+            Gain::One => w.gain().gain1(),
+            Gain::Two => w.gain().gain2(),
+            Gain::Four => w.gain().gain4(),
+        };
+        w.tacq()._10us();
+        w.mode().se();
+        w.resp().bypass();
+        w.resn().bypass();
+        w.burst().disabled()
+    });
+    // `PSELP` values start at 1 for `AIN0`, since 0 means that the channel is not connected.
+    saadc.ch[0]
+        .pselp
+        .write(|w| unsafe { w.bits(u32::from(input) + 1) });
+}
+
+/// Takes a single sample of the specified analog input.
+fn measure(
+    input: u8,
+    config: &embedded_platform::adc::Config,
+) -> Result<embedded_platform::adc::Reading, error::Error> {
+    if SAMPLING.swap(true, atomic::Ordering::AcqRel) {
+        return Err(error::Error::Busy);
+    }
+
+    let raw = cortex_m::interrupt::free(|_| {
+        let saadc = saadc();
+        configure(input, config);
+        saadc
+            .result
+            .ptr
+            .write(|w| unsafe { w.ptr().bits(RESULT.as_ptr() as u32) });
+        saadc.result.maxcnt.write(|w| unsafe { w.maxcnt().bits(1) });
+
+        saadc.tasks_start.write(|w| unsafe { w.bits(1) });
+        while saadc.events_started.read().bits() == 0 {}
+        saadc.events_started.reset();
+        saadc.tasks_sample.write(|w| unsafe { w.bits(1) });
+        while saadc.events_end.read().bits() == 0 {}
+        saadc.events_end.reset();
+        saadc.ch[0].pselp.reset();
+
+        // The SAADC wrote the sample using DMA, which the compiler doesn't know about.
+        atomic::compiler_fence(atomic::Ordering::SeqCst);
+        // Samples are signed, since noise can make inputs at ground read slightly negative.
+        RESULT.load(atomic::Ordering::Relaxed) as i16
+    });
+    SAMPLING.store(false, atomic::Ordering::Release);
+
+    let full_scale = full_scale(config);
+    Ok(embedded_platform::adc::Reading::new(
+        i32::from(raw),
+        RESOLUTION,
+        full_scale,
+    ))
+}
+
+/// Starts filling the first buffer with samples of the specified analog input, restarting with a
+/// spare buffer every time a buffer has been filled.
+///
+/// The SAADC only takes a sample when its `SAMPLE` task is triggered.
+fn start(input: u8, config: &embedded_platform::adc::Config) {
+    let saadc = saadc();
+    cortex_m::interrupt::free(|cs| {
+        let mut state = SAMPLER_STATE.borrow(cs).borrow_mut();
+        state.filling = 0;
+        state.queued = 1;
+        state.ready = None;
+        state.overruns = 0;
+        state.waker = None;
+
+        configure(input, config);
+        saadc
+            .result
+            .ptr
+            .write(|w| unsafe { w.ptr().bits(BUFFERS[0].as_ptr() as u32) });
+        saadc
+            .result
+            .maxcnt
+            .write(|w| unsafe { w.maxcnt().bits(embedded_platform::adc::BUFFER_LEN as u16) });
+        saadc.events_started.reset();
+        saadc.events_end.reset();
+        saadc.intenset.write(|w| {
+            w.started().set();
+            w.end().set()
+        });
+    });
+    connect(
+        PPI_RESTART,
+        &saadc.events_end as *const _ as u32,
+        &saadc.tasks_start as *const _ as u32,
+    );
+    saadc.tasks_start.write(|w| unsafe { w.bits(1) });
+}
+
+/// Stops continuous sampling, so that the SAADC can be used by others.
+fn stop() {
+    let saadc = saadc();
+    ppi()
+        .chenclr
+        .write(|w| unsafe { w.bits(1 << PPI_SAMPLE | 1 << PPI_RESTART) });
+    saadc.intenclr.write(|w| {
+        w.started().clear();
+        w.end().clear()
+    });
+    saadc.tasks_stop.write(|w| unsafe { w.bits(1) });
+    while saadc.events_stopped.read().bits() == 0 {}
+    saadc.events_stopped.reset();
+    saadc.events_started.reset();
+    saadc.events_end.reset();
+    saadc.ch[0].pselp.reset();
+
+    cortex_m::interrupt::free(|cs| {
+        let mut state = SAMPLER_STATE.borrow(cs).borrow_mut();
+        state.ready = None;
+        state.waker = None;
+    });
+    SAMPLING.store(false, atomic::Ordering::Release);
+}
+
+/// Has a PPI channel trigger the task at the specified address whenever the event at the specified
+/// address is generated.
+fn connect(channel: u32, event: u32, task: u32) {
+    let ppi = ppi();
+    let ch = &ppi.ch[channel as usize];
+    ch.eep.write(|w| unsafe { w.bits(event) });
+    ch.tep.write(|w| unsafe { w.bits(task) });
+    ppi.chenset.write(|w| unsafe { w.bits(1 << channel) });
+}
+
+#[cfg(feature = "rt")]
+#[interrupt]
+fn SAADC() {
+    let saadc = saadc();
+    cortex_m::interrupt::free(|cs| {
+        let mut state = SAMPLER_STATE.borrow(cs).borrow_mut();
+        // A filled buffer has to be handled before the start of the next one, since both events
+        // may be pending when the buffers are swapped.
+        if saadc.events_end.read().bits() != 0 {
+            saadc.events_end.reset();
+            // The buffer that was waiting to be taken, if any, is skipped in favor of the newer one.
+            let filled = state.filling;
+            if state.ready.replace(filled).is_some() {
+                state.overruns = state.overruns.saturating_add(1);
+            }
+            state.filling = state.queued;
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        }
+        if saadc.events_started.read().bits() != 0 {
+            saadc.events_started.reset();
+            // The SAADC has latched the pointer of the buffer that it is filling, so the spare
+            // buffer can be queued up for when it restarts.  The buffer that is waiting to be
+            // taken is never queued, since it might still be copied when the SAADC restarts.
+            let next = state.spare();
+            state.queued = next;
+            saadc
+                .result
+                .ptr
+                .write(|w| unsafe { w.ptr().bits(BUFFERS[next].as_ptr() as u32) });
+        }
+    });
+}
@@ -0,0 +1,77 @@
+//! A monotonic clock backed by the RTC1 real-time counter.
+//!
+//! The RTC runs from the 32.768 kHz low-frequency clock, and keeps counting while the CPU sleeps.
+//! Its counter is only 24 bits wide and wraps around every 512 seconds, so overflows are counted in
+//! an interrupt handler to extend it to 56 bits.
+use core::sync::atomic;
+use nrf52840_hal::target::interrupt;
+
+const TICKS_PER_SECOND: u64 = 32_768;
+const COUNTER_BITS: u32 = 24;
+
+static OVERFLOWS: atomic::AtomicU32 = atomic::AtomicU32::new(0);
+
+/// A handle to the clock of the platform, which can be copied freely.
+#[derive(Clone, Copy, Debug)]
+pub struct Clock {
+    _private: (),
+}
+
+impl Clock {
+    pub(crate) fn new(
+        clock: &nrf52840_hal::target::CLOCK,
+        rtc: nrf52840_hal::target::RTC1,
+    ) -> Self {
+        clock.tasks_lfclkstart.write(|w| unsafe { w.bits(1) });
+        while clock.events_lfclkstarted.read().bits() == 0 {}
+        clock.events_lfclkstarted.write(|w| unsafe { w.bits(0) });
+
+        rtc.tasks_stop.write(|w| unsafe { w.bits(1) });
+        rtc.tasks_clear.write(|w| unsafe { w.bits(1) });
+        rtc.prescaler.write(|w| unsafe { w.prescaler().bits(0) });
+        rtc.events_ovrflw.write(|w| unsafe { w.bits(0) });
+        rtc.intenset.write(|w| w.ovrflw().set());
+        OVERFLOWS.store(0, atomic::Ordering::Release);
+
+        unsafe { cortex_m::peripheral::NVIC::unmask(nrf52840_hal::target::Interrupt::RTC1) };
+        rtc.tasks_start.write(|w| unsafe { w.bits(1) });
+
+        Self { _private: () }
+    }
+}
+
+impl embedded_platform::clock::Clock for Clock {
+    fn now(&self) -> embedded_platform::time::Instant {
+        // Safety: the RTC was configured when the clock was created, and from then on it's only read
+        // here and in the overflow interrupt handler.
+        let rtc = unsafe { &*nrf52840_hal::target::RTC1::ptr() };
+
+        let ticks = cortex_m::interrupt::free(|_| {
+            let mut overflows = OVERFLOWS.load(atomic::Ordering::Acquire);
+            let mut counter = rtc.counter.read().bits();
+            if rtc.events_ovrflw.read().bits() != 0 {
+                // The counter has wrapped around, but the interrupt hasn't been handled yet.
+                overflows += 1;
+                counter = rtc.counter.read().bits();
+            }
+            u64::from(overflows) << COUNTER_BITS | u64::from(counter)
+        });
+
+        let second = embedded_platform::time::Duration::from_seconds(1).as_nanos();
+        let whole = ticks / TICKS_PER_SECOND * second;
+        let fraction = ticks % TICKS_PER_SECOND * second / TICKS_PER_SECOND;
+        embedded_platform::time::Instant::from_nanos(whole + fraction)
+    }
+}
+
+#[cfg(feature = "rt")]
+#[interrupt]
+fn RTC1() {
+    let rtc = unsafe { &*nrf52840_hal::target::RTC1::ptr() };
+    cortex_m::interrupt::free(|_| {
+        if rtc.events_ovrflw.read().bits() != 0 {
+            rtc.events_ovrflw.write(|w| unsafe { w.bits(0) });
+            OVERFLOWS.fetch_add(1, atomic::Ordering::AcqRel);
+        }
+    });
+}
@@ -1,7 +1,18 @@
+use crate::gpio;
+use embedded_platform::specs::feather;
+
 #[derive(Debug)]
 pub enum Error {
+    AlreadyInitialized,
+    Busy,
+    Elapsed,
     Eof,
+    MixedPorts,
+    OutOfRange,
+    PinInUse(feather::PinInUse<gpio::PinId>),
+    TasksRunning,
     WriteZero,
+    WrongMode,
     Uarte(nrf52840_hal::uarte::Error),
     Spim(nrf52840_hal::spim::Error),
 }
@@ -18,6 +29,12 @@ impl embedded_platform::io::WriteError for Error {
     }
 }
 
+impl From<embedded_platform::timer::timeout::Elapsed> for Error {
+    fn from(_: embedded_platform::timer::timeout::Elapsed) -> Self {
+        Error::Elapsed
+    }
+}
+
 impl From<nrf52840_hal::uarte::Error> for Error {
     fn from(err: nrf52840_hal::uarte::Error) -> Self {
         Error::Uarte(err)
@@ -29,3 +46,9 @@ impl From<nrf52840_hal::spim::Error> for Error {
         Error::Spim(err)
     }
 }
+
+impl From<feather::PinInUse<gpio::PinId>> for Error {
+    fn from(err: feather::PinInUse<gpio::PinId>) -> Self {
+        Error::PinInUse(err)
+    }
+}
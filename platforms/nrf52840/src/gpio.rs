@@ -1,18 +1,119 @@
 use crate::error;
+use core::cell;
 use core::fmt;
 use core::pin;
 use core::task;
+use embedded_platform::specs::feather;
 use nrf52840_hal::gpio;
 use nrf52840_hal::gpio::p0;
 use nrf52840_hal::gpio::p1;
+use nrf52840_hal::target::interrupt;
+
+mod drive;
+
+/// Identifies a physical pin of the nRF52840 by its port and its index within the port.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PinId {
+    pub port: u8,
+    pub index: u8,
+}
+
+impl fmt::Display for PinId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{:02}", self.port, self.index)
+    }
+}
+
+/// A pin whose physical location is known from its type, e.g. so that peripherals can be connected
+/// to it.
+pub trait PhysicalPin {
+    /// The physical pin that this pin is bound to.
+    fn id(&self) -> PinId;
+}
+
+/// The roles that the pins of both ports were taken as.
+#[derive(Debug)]
+pub(crate) struct Claims([[Option<feather::Role>; 32]; 2]);
+
+impl Claims {
+    pub(crate) fn new() -> Self {
+        Claims([[None; 32]; 2])
+    }
+
+    /// Takes a pin, and records the role that it was taken as.
+    pub(crate) fn take<P>(
+        &mut self,
+        pin: &mut Option<P>,
+        id: PinId,
+        role: feather::Role,
+    ) -> Result<P, feather::PinInUse<PinId>> {
+        let claim = &mut self.0[usize::from(id.port)][usize::from(id.index)];
+        match pin.take() {
+            Some(pin) => {
+                *claim = Some(role);
+                Ok(pin)
+            }
+            None => {
+                let role = claim.expect("taken pins are claimed");
+                Err(feather::PinInUse { pin: id, role })
+            }
+        }
+    }
+
+    /// Puts a pin back, and forgets the role that it was taken as.
+    pub(crate) fn release<P>(&mut self, slot: &mut Option<P>, id: PinId, pin: P) {
+        self.0[usize::from(id.port)][usize::from(id.index)] = None;
+        *slot = Some(pin);
+    }
+
+    /// The pins that have been taken so far, and the roles that they were taken as.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (PinId, feather::Role)> + '_ {
+        self.0.iter().enumerate().flat_map(|(port, claims)| {
+            claims.iter().enumerate().filter_map(move |(index, role)| {
+                let id = PinId {
+                    port: port as u8,
+                    index: index as u8,
+                };
+                role.map(|role| (id, role))
+            })
+        })
+    }
+}
 
 #[repr(transparent)]
 pub struct Pin<P>(pub(crate) P)
 where
     P: Unpin + ?Sized;
 
+macro_rules! degrade {
+    ($m:ident::$typ:ident @ $port:expr, $index:expr => [$($mode:ty: $variant:ident,)*]) => {
+    $(
+        impl embedded_platform::gpio::Degrade for Pin<$m::$typ<$mode>> {
+            type AnyPin = AnyPin;
+
+            fn degrade(self) -> Self::AnyPin {
+                AnyPin { port: $port, index: $index, mode: AnyMode::$variant(self.0.degrade()) }
+            }
+        }
+    )*
+    };
+}
+
+/// Applies the same expression to the pin of a type-erased pin, whatever its mode.
+macro_rules! with_any_mode {
+    ($mode:expr, $pin:ident => $e:expr) => {
+        match $mode {
+            AnyMode::FloatingInput($pin) => $e,
+            AnyMode::PullUpInput($pin) => $e,
+            AnyMode::PullDownInput($pin) => $e,
+            AnyMode::PushPullOutput($pin) => $e,
+            AnyMode::OpenDrainOutput($pin) => $e,
+        }
+    };
+}
+
 macro_rules! gpio {
-    ($($m:ident: $mtyp:ident => [$($name:ident: $typ:ident,)*],)*) => {
+    ($($m:ident: $mtyp:ident @ $port:expr => [$($name:ident: $typ:ident @ $index:expr,)*],)*) => {
     $(
         #[derive(Debug)]
         pub(crate) struct $mtyp {
@@ -44,6 +145,23 @@ macro_rules! gpio {
             }
         }
 
+        impl<S> embedded_platform::gpio::WaitInputPin for Pin<$m::$typ<gpio::Input<S>>> where S: Unpin {
+            fn poll_listen(
+                self: pin::Pin<&mut Self>,
+                _cx: &mut task::Context<'_>,
+            ) -> task::Poll<Result<(), Self::Error>> {
+                listen($port, $index);
+                task::Poll::Ready(Ok(()))
+            }
+
+            fn poll_change(
+                self: pin::Pin<&mut Self>,
+                cx: &mut task::Context<'_>,
+            ) -> task::Poll<Result<bool, Self::Error>> {
+                poll_change($port, $index, cx).map(Ok)
+            }
+        }
+
         impl<S> embedded_platform::gpio::OutputPin for Pin<$m::$typ<gpio::Output<S>>> where S: Unpin {
             fn poll_set(
                 mut self: pin::Pin<&mut Self>,
@@ -57,6 +175,52 @@ macro_rules! gpio {
             }
         }
 
+        impl<S> embedded_platform::gpio::StatefulOutputPin for Pin<$m::$typ<gpio::Output<S>>> where S: Unpin {
+            fn poll_is_set_high(
+                self: pin::Pin<&mut Self>,
+                _cx: &mut task::Context<'_>,
+            ) -> task::Poll<Result<bool, Self::Error>> {
+                use embedded_hal::digital::v2::StatefulOutputPin;
+                task::Poll::Ready(Ok(self.0.is_set_high().unwrap()))
+            }
+        }
+
+        degrade! {
+            $m::$typ @ $port, $index => [
+                gpio::Input<gpio::Floating>: FloatingInput,
+                gpio::Input<gpio::PullUp>: PullUpInput,
+                gpio::Input<gpio::PullDown>: PullDownInput,
+                gpio::Output<gpio::PushPull>: PushPullOutput,
+                gpio::Output<gpio::OpenDrain>: OpenDrainOutput,
+            ]
+        }
+
+        impl<M> PhysicalPin for Pin<$m::$typ<M>> where M: Unpin {
+            fn id(&self) -> PinId {
+                PinId { port: $port, index: $index }
+            }
+        }
+
+        impl<M> From<Pin<$m::$typ<M>>> for GroupPin<M> where M: Unpin {
+            fn from(pin: Pin<$m::$typ<M>>) -> Self {
+                GroupPin { port: $port, index: $index, pin: pin.0.degrade() }
+            }
+        }
+
+        impl<S> embedded_platform::gpio::ConfigureDriveStrength for Pin<$m::$typ<gpio::Output<S>>> where S: Unpin {
+            fn set_drive_strength(&mut self, strength: embedded_platform::gpio::DriveStrength) -> Result<(), Self::Error> {
+                set_drive_strength($port, $index, strength);
+                Ok(())
+            }
+        }
+
+        impl embedded_platform::gpio::ConfigureOpenDrain for Pin<$m::$typ<gpio::Output<gpio::OpenDrain>>> {
+            fn set_open_drain_flavor(&mut self, flavor: embedded_platform::gpio::OpenDrainFlavor) -> Result<(), Self::Error> {
+                set_open_drain_flavor($port, $index, flavor);
+                Ok(())
+            }
+        }
+
         impl<S> embedded_platform::gpio::IntoFloatingInputPin for Pin<$m::$typ<S>> where S: Unpin {
             type FloatingInputPin = Pin<$m::$typ<gpio::Input<gpio::Floating>>>;
 
@@ -93,21 +257,21 @@ macro_rules! gpio {
             type OpenDrainOutputPin = Pin<$m::$typ<gpio::Output<gpio::OpenDrain>>>;
 
             fn into_open_drain_output_pin(self, initial_high: bool) -> Result<Self::OpenDrainOutputPin, Self::Error> {
-                Ok(Pin(self.0.into_open_drain_output(gpio::OpenDrainConfig::Disconnect0Standard1, if initial_high { gpio::Level::High } else { gpio::Level::Low })))
+                Ok(Pin(self.0.into_open_drain_output(gpio::OpenDrainConfig::Standard0Disconnect1, if initial_high { gpio::Level::High } else { gpio::Level::Low })))
             }
         }
 
         impl<S> fmt::Debug for Pin<$m::$typ<gpio::Input<S>>> where S: Unpin {
             fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                // TODO: display current GPIO state
-                f.debug_struct(stringify!($typ)).finish()
+                use embedded_hal::digital::v2::InputPin;
+                f.debug_struct(stringify!($typ)).field("high", &self.0.is_high().unwrap()).finish()
             }
         }
 
         impl<S> fmt::Debug for Pin<$m::$typ<gpio::Output<S>>> where S: Unpin {
             fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                // TODO: display current GPIO state
-                f.debug_struct(stringify!($typ)).finish()
+                use embedded_hal::digital::v2::StatefulOutputPin;
+                f.debug_struct(stringify!($typ)).field("set_high", &self.0.is_set_high().unwrap()).finish()
             }
         }
     )*)*
@@ -115,56 +279,565 @@ macro_rules! gpio {
 }
 
 gpio! {
-    p0: P0 => [
-        p0_00: P0_00,
-        p0_01: P0_01,
-        p0_02: P0_02,
-        p0_03: P0_03,
-        p0_04: P0_04,
-        p0_05: P0_05,
-        p0_06: P0_06,
-        p0_07: P0_07,
-        p0_08: P0_08,
-        p0_09: P0_09,
-        p0_10: P0_10,
-        p0_11: P0_11,
-        p0_12: P0_12,
-        p0_13: P0_13,
-        p0_14: P0_14,
-        p0_15: P0_15,
-        p0_16: P0_16,
-        p0_17: P0_17,
-        p0_18: P0_18,
-        p0_19: P0_19,
-        p0_20: P0_20,
-        p0_21: P0_21,
-        p0_22: P0_22,
-        p0_23: P0_23,
-        p0_24: P0_24,
-        p0_25: P0_25,
-        p0_26: P0_26,
-        p0_27: P0_27,
-        p0_28: P0_28,
-        p0_29: P0_29,
-        p0_30: P0_30,
-        p0_31: P0_31,
+    p0: P0 @ 0 => [
+        p0_00: P0_00 @ 0,
+        p0_01: P0_01 @ 1,
+        p0_02: P0_02 @ 2,
+        p0_03: P0_03 @ 3,
+        p0_04: P0_04 @ 4,
+        p0_05: P0_05 @ 5,
+        p0_06: P0_06 @ 6,
+        p0_07: P0_07 @ 7,
+        p0_08: P0_08 @ 8,
+        p0_09: P0_09 @ 9,
+        p0_10: P0_10 @ 10,
+        p0_11: P0_11 @ 11,
+        p0_12: P0_12 @ 12,
+        p0_13: P0_13 @ 13,
+        p0_14: P0_14 @ 14,
+        p0_15: P0_15 @ 15,
+        p0_16: P0_16 @ 16,
+        p0_17: P0_17 @ 17,
+        p0_18: P0_18 @ 18,
+        p0_19: P0_19 @ 19,
+        p0_20: P0_20 @ 20,
+        p0_21: P0_21 @ 21,
+        p0_22: P0_22 @ 22,
+        p0_23: P0_23 @ 23,
+        p0_24: P0_24 @ 24,
+        p0_25: P0_25 @ 25,
+        p0_26: P0_26 @ 26,
+        p0_27: P0_27 @ 27,
+        p0_28: P0_28 @ 28,
+        p0_29: P0_29 @ 29,
+        p0_30: P0_30 @ 30,
+        p0_31: P0_31 @ 31,
     ],
-    p1: P1 => [
-        p1_00: P1_00,
-        p1_01: P1_01,
-        p1_02: P1_02,
-        p1_03: P1_03,
-        p1_04: P1_04,
-        p1_05: P1_05,
-        p1_06: P1_06,
-        p1_07: P1_07,
-        p1_08: P1_08,
-        p1_09: P1_09,
-        p1_10: P1_10,
-        p1_11: P1_11,
-        p1_12: P1_12,
-        p1_13: P1_13,
-        p1_14: P1_14,
-        p1_15: P1_15,
+    p1: P1 @ 1 => [
+        p1_00: P1_00 @ 0,
+        p1_01: P1_01 @ 1,
+        p1_02: P1_02 @ 2,
+        p1_03: P1_03 @ 3,
+        p1_04: P1_04 @ 4,
+        p1_05: P1_05 @ 5,
+        p1_06: P1_06 @ 6,
+        p1_07: P1_07 @ 7,
+        p1_08: P1_08 @ 8,
+        p1_09: P1_09 @ 9,
+        p1_10: P1_10 @ 10,
+        p1_11: P1_11 @ 11,
+        p1_12: P1_12 @ 12,
+        p1_13: P1_13 @ 13,
+        p1_14: P1_14 @ 14,
+        p1_15: P1_15 @ 15,
     ],
 }
+
+/// A pin whose mode is only known at runtime, so that pins can be stored together.
+///
+/// Reading the pin while it is an output, or writing it while it is an input, fails with
+/// [`Error::WrongMode`](error::Error::WrongMode).
+pub struct AnyPin {
+    port: usize,
+    index: usize,
+    mode: AnyMode,
+}
+
+enum AnyMode {
+    FloatingInput(gpio::Pin<gpio::Input<gpio::Floating>>),
+    PullUpInput(gpio::Pin<gpio::Input<gpio::PullUp>>),
+    PullDownInput(gpio::Pin<gpio::Input<gpio::PullDown>>),
+    PushPullOutput(gpio::Pin<gpio::Output<gpio::PushPull>>),
+    OpenDrainOutput(gpio::Pin<gpio::Output<gpio::OpenDrain>>),
+}
+
+impl AnyPin {
+    fn level(&self) -> Result<bool, error::Error> {
+        use embedded_hal::digital::v2::InputPin;
+        match &self.mode {
+            AnyMode::FloatingInput(pin) => Ok(pin.is_high().unwrap()),
+            AnyMode::PullUpInput(pin) => Ok(pin.is_high().unwrap()),
+            AnyMode::PullDownInput(pin) => Ok(pin.is_high().unwrap()),
+            AnyMode::PushPullOutput(_) | AnyMode::OpenDrainOutput(_) => {
+                Err(error::Error::WrongMode)
+            }
+        }
+    }
+
+    fn is_set_high(&self) -> Result<bool, error::Error> {
+        use embedded_hal::digital::v2::StatefulOutputPin;
+        match &self.mode {
+            AnyMode::PushPullOutput(pin) => Ok(pin.is_set_high().unwrap()),
+            AnyMode::OpenDrainOutput(pin) => Ok(pin.is_set_high().unwrap()),
+            AnyMode::FloatingInput(_) | AnyMode::PullUpInput(_) | AnyMode::PullDownInput(_) => {
+                Err(error::Error::WrongMode)
+            }
+        }
+    }
+
+    fn is_input(&self) -> bool {
+        match self.mode {
+            AnyMode::FloatingInput(_) | AnyMode::PullUpInput(_) | AnyMode::PullDownInput(_) => true,
+            AnyMode::PushPullOutput(_) | AnyMode::OpenDrainOutput(_) => false,
+        }
+    }
+
+    fn into_mode<F>(self, f: F) -> Self
+    where
+        F: FnOnce(AnyMode) -> AnyMode,
+    {
+        let mode = f(self.mode);
+        AnyPin { mode, ..self }
+    }
+}
+
+impl embedded_platform::gpio::Pin for AnyPin {
+    type Error = error::Error;
+}
+
+impl embedded_platform::gpio::InputPin for AnyPin {
+    fn poll_get(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>> {
+        task::Poll::Ready(self.level())
+    }
+}
+
+impl embedded_platform::gpio::WaitInputPin for AnyPin {
+    fn poll_listen(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        if !self.is_input() {
+            return task::Poll::Ready(Err(error::Error::WrongMode));
+        }
+        listen(self.port, self.index);
+        task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_change(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>> {
+        if !self.is_input() {
+            return task::Poll::Ready(Err(error::Error::WrongMode));
+        }
+        poll_change(self.port, self.index, cx).map(Ok)
+    }
+}
+
+impl embedded_platform::gpio::OutputPin for AnyPin {
+    fn poll_set(
+        mut self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+        high: bool,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        use embedded_hal::digital::v2::OutputPin;
+
+        let result = match &mut self.mode {
+            AnyMode::PushPullOutput(pin) if high => Ok(pin.set_high().unwrap()),
+            AnyMode::PushPullOutput(pin) => Ok(pin.set_low().unwrap()),
+            AnyMode::OpenDrainOutput(pin) if high => Ok(pin.set_high().unwrap()),
+            AnyMode::OpenDrainOutput(pin) => Ok(pin.set_low().unwrap()),
+            AnyMode::FloatingInput(_) | AnyMode::PullUpInput(_) | AnyMode::PullDownInput(_) => {
+                Err(error::Error::WrongMode)
+            }
+        };
+        task::Poll::Ready(result)
+    }
+}
+
+impl embedded_platform::gpio::StatefulOutputPin for AnyPin {
+    fn poll_is_set_high(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>> {
+        task::Poll::Ready(self.is_set_high())
+    }
+}
+
+impl embedded_platform::gpio::ConfigureDriveStrength for AnyPin {
+    fn set_drive_strength(
+        &mut self,
+        strength: embedded_platform::gpio::DriveStrength,
+    ) -> Result<(), Self::Error> {
+        if self.is_input() {
+            return Err(error::Error::WrongMode);
+        }
+        set_drive_strength(self.port, self.index, strength);
+        Ok(())
+    }
+}
+
+impl embedded_platform::gpio::ConfigureOpenDrain for AnyPin {
+    fn set_open_drain_flavor(
+        &mut self,
+        flavor: embedded_platform::gpio::OpenDrainFlavor,
+    ) -> Result<(), Self::Error> {
+        if let AnyMode::OpenDrainOutput(_) = self.mode {
+            set_open_drain_flavor(self.port, self.index, flavor);
+            Ok(())
+        } else {
+            Err(error::Error::WrongMode)
+        }
+    }
+}
+
+impl embedded_platform::gpio::IntoFloatingInputPin for AnyPin {
+    type FloatingInputPin = Self;
+
+    fn into_floating_input_pin(self) -> Result<Self::FloatingInputPin, Self::Error> {
+        Ok(self.into_mode(
+            |mode| with_any_mode!(mode, pin => AnyMode::FloatingInput(pin.into_floating_input())),
+        ))
+    }
+}
+
+impl embedded_platform::gpio::IntoPullUpInputPin for AnyPin {
+    type PullUpInputPin = Self;
+
+    fn into_pull_up_input_pin(self) -> Result<Self::PullUpInputPin, Self::Error> {
+        Ok(self.into_mode(
+            |mode| with_any_mode!(mode, pin => AnyMode::PullUpInput(pin.into_pullup_input())),
+        ))
+    }
+}
+
+impl embedded_platform::gpio::IntoPullDownInputPin for AnyPin {
+    type PullDownInputPin = Self;
+
+    fn into_pull_down_input_pin(self) -> Result<Self::PullDownInputPin, Self::Error> {
+        Ok(self.into_mode(
+            |mode| with_any_mode!(mode, pin => AnyMode::PullDownInput(pin.into_pulldown_input())),
+        ))
+    }
+}
+
+impl embedded_platform::gpio::IntoPushPullOutputPin for AnyPin {
+    type PushPullOutputPin = Self;
+
+    fn into_push_pull_output_pin(
+        self,
+        initial_high: bool,
+    ) -> Result<Self::PushPullOutputPin, Self::Error> {
+        let level = if initial_high {
+            gpio::Level::High
+        } else {
+            gpio::Level::Low
+        };
+        Ok(self.into_mode(|mode| {
+            with_any_mode!(mode, pin => AnyMode::PushPullOutput(pin.into_push_pull_output(level)))
+        }))
+    }
+}
+
+impl embedded_platform::gpio::IntoOpenDrainOutputPin for AnyPin {
+    type OpenDrainOutputPin = Self;
+
+    fn into_open_drain_output_pin(
+        self,
+        initial_high: bool,
+    ) -> Result<Self::OpenDrainOutputPin, Self::Error> {
+        let level = if initial_high {
+            gpio::Level::High
+        } else {
+            gpio::Level::Low
+        };
+        Ok(self.into_mode(|mode| {
+            with_any_mode!(mode, pin => AnyMode::OpenDrainOutput(
+                pin.into_open_drain_output(gpio::OpenDrainConfig::Standard0Disconnect1, level)
+            ))
+        }))
+    }
+}
+
+impl embedded_platform::gpio::Degrade for AnyPin {
+    type AnyPin = Self;
+
+    fn degrade(self) -> Self::AnyPin {
+        self
+    }
+}
+
+impl fmt::Debug for AnyPin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("AnyPin");
+        debug.field("port", &self.port).field("index", &self.index);
+        match self.level() {
+            Ok(high) => debug.field("high", &high),
+            Err(_) => debug.field("set_high", &self.is_set_high().unwrap()),
+        };
+        debug.finish()
+    }
+}
+
+/// Updates the drives of the low and high levels of a pin.
+fn configure_drive<F>(index: usize, pin: usize, f: F)
+where
+    F: FnOnce((drive::Drive, drive::Drive)) -> (drive::Drive, drive::Drive),
+{
+    // The interrupt handler modifies the same register to update the `SENSE` field.
+    cortex_m::interrupt::free(|_| {
+        let pin_cnf = &port(index).pin_cnf[pin];
+        let drives = f(drive::DRIVES[usize::from(pin_cnf.read().drive().bits())]);
+        pin_cnf.modify(|_, w| match drives {
+            (drive::Drive::Standard, drive::Drive::Standard) => w.drive().s0s1(),
+            (drive::Drive::High, drive::Drive::Standard) => w.drive().h0s1(),
+            (drive::Drive::Standard, drive::Drive::High) => w.drive().s0h1(),
+            (drive::Drive::High, drive::Drive::High) => w.drive().h0h1(),
+            (drive::Drive::Disconnect, drive::Drive::Standard) => w.drive().d0s1(),
+            (drive::Drive::Disconnect, drive::Drive::High) => w.drive().d0h1(),
+            (drive::Drive::Standard, drive::Drive::Disconnect) => w.drive().s0d1(),
+            (drive::Drive::High, drive::Drive::Disconnect) => w.drive().h0d1(),
+            (drive::Drive::Disconnect, drive::Drive::Disconnect) => {
+                unreachable!("pin drives neither level")
+            }
+        });
+    })
+}
+
+fn set_drive_strength(index: usize, pin: usize, strength: embedded_platform::gpio::DriveStrength) {
+    configure_drive(index, pin, |drives| drive::with_strength(drives, strength));
+}
+
+fn set_open_drain_flavor(
+    index: usize,
+    pin: usize,
+    flavor: embedded_platform::gpio::OpenDrainFlavor,
+) {
+    configure_drive(index, pin, |drives| drive::with_flavor(drives, flavor));
+}
+
+/// A pin that has been handed over to a [`PortGroup`].
+///
+/// Any pin in the specified mode can be converted into a group pin using [`From`], and back into
+/// a type-erased pin using [`Degrade`](embedded_platform::gpio::Degrade).
+pub struct GroupPin<M> {
+    port: usize,
+    index: usize,
+    pin: gpio::Pin<M>,
+}
+
+impl<M> fmt::Debug for GroupPin<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GroupPin")
+            .field("port", &self.port)
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+macro_rules! degrade_group_pin {
+    ($($mode:ty: $variant:ident,)*) => {
+    $(
+        impl embedded_platform::gpio::Degrade for GroupPin<$mode> {
+            type AnyPin = AnyPin;
+
+            fn degrade(self) -> Self::AnyPin {
+                AnyPin { port: self.port, index: self.index, mode: AnyMode::$variant(self.pin) }
+            }
+        }
+    )*
+    };
+}
+
+degrade_group_pin! {
+    gpio::Input<gpio::Floating>: FloatingInput,
+    gpio::Input<gpio::PullUp>: PullUpInput,
+    gpio::Input<gpio::PullDown>: PullDownInput,
+    gpio::Output<gpio::PushPull>: PushPullOutput,
+    gpio::Output<gpio::OpenDrain>: OpenDrainOutput,
+}
+
+/// A group of pins on the same port, which are read or written together through the registers of
+/// the port.
+///
+/// Writes first set the pins that go high using the `OUTSET` register, and then clear the pins
+/// that go low using the `OUTCLR` register, so each of the two sets of pins changes at once.
+#[derive(Debug)]
+pub struct PortGroup<M, const N: usize> {
+    port: usize,
+    pins: [GroupPin<M>; N],
+}
+
+impl<M, const N: usize> PortGroup<M, N> {
+    /// Creates a new group of the specified pins, where the first pin corresponds to the lowest
+    /// bit of words.
+    ///
+    /// Fails with [`Error::MixedPorts`](error::Error::MixedPorts) if the pins aren't all on the
+    /// same port, handing the pins back along with the error;
+    /// [`Sequential`](embedded_platform::gpio::sequential::Sequential) can group such pins
+    /// instead.
+    pub fn new(pins: [GroupPin<M>; N]) -> Result<Self, (error::Error, [GroupPin<M>; N])> {
+        assert!(N <= 32, "a group can contain at most 32 pins");
+        let port = pins.first().map_or(0, |pin| pin.port);
+        if pins.iter().any(|pin| pin.port != port) {
+            return Err((error::Error::MixedPorts, pins));
+        }
+
+        Ok(Self { port, pins })
+    }
+
+    /// Breaks up this group, and returns its pins in the order that they were grouped in.
+    pub fn release(self) -> [GroupPin<M>; N] {
+        self.pins
+    }
+
+    /// Maps the bits of a word to the bits of the corresponding pins in the port registers.
+    fn port_bits(&self, word: u32) -> u32 {
+        self.pins
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| word & 1 << bit != 0)
+            .fold(0, |bits, (_, pin)| bits | 1 << pin.index)
+    }
+}
+
+impl<M, const N: usize> embedded_platform::gpio::Pin for PortGroup<M, N> {
+    type Error = error::Error;
+}
+
+impl<S, const N: usize> embedded_platform::gpio::InputPort for PortGroup<gpio::Input<S>, N> {
+    fn poll_read_word(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<u32, Self::Error>> {
+        let levels = port(self.port).in_.read().bits();
+        let word = self
+            .pins
+            .iter()
+            .enumerate()
+            .filter(|(_, pin)| levels & 1 << pin.index != 0)
+            .fold(0, |word, (bit, _)| word | 1 << bit);
+        task::Poll::Ready(Ok(word))
+    }
+}
+
+impl<S, const N: usize> embedded_platform::gpio::OutputPort for PortGroup<gpio::Output<S>, N> {
+    fn poll_write_word(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+        mask: u32,
+        word: u32,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        let set = self.port_bits(mask & word);
+        let clear = self.port_bits(mask & !word);
+        let port = port(self.port);
+        port.outset.write(|w| unsafe { w.bits(set) });
+        port.outclr.write(|w| unsafe { w.bits(clear) });
+        task::Poll::Ready(Ok(()))
+    }
+}
+
+/// The level changes detected by the GPIOTE `PORT` event, for every pin of both ports.
+struct SenseState {
+    /// For each port, the levels that the pins had when they last reported a change.
+    levels: [u32; 2],
+    /// For each pin, the number of level changes that haven't been reported yet.
+    changes: [[u32; 32]; 2],
+    wakers: [[Option<task::Waker>; 32]; 2],
+}
+
+const NO_WAKER: Option<task::Waker> = None;
+const NO_WAKERS: [Option<task::Waker>; 32] = [NO_WAKER; 32];
+
+static SENSE: bare_metal::Mutex<cell::RefCell<SenseState>> =
+    bare_metal::Mutex::new(cell::RefCell::new(SenseState {
+        levels: [0; 2],
+        changes: [[0; 32]; 2],
+        wakers: [NO_WAKERS; 2],
+    }));
+
+/// Configures both ports to latch pin changes, and enables the `PORT` event interrupt.
+pub(crate) fn enable_sense(gpiote: &nrf52840_hal::target::GPIOTE) {
+    for index in 0..2 {
+        port(index).detectmode.write(|w| w.detectmode().ldetect());
+    }
+    gpiote.events_port.write(|w| unsafe { w.bits(0) });
+    gpiote.intenset.write(|w| w.port().set());
+    unsafe { cortex_m::peripheral::NVIC::unmask(nrf52840_hal::target::Interrupt::GPIOTE) };
+}
+
+fn port(index: usize) -> &'static nrf52840_hal::target::p0::RegisterBlock {
+    // Safety: only the `SENSE` and `DRIVE` fields of owned pins and the `LATCH` register are
+    // modified after the ports have been split into pins, and only in critical sections.  Pins that
+    // are part of a `PortGroup` are additionally written through the `OUTSET` and `OUTCLR`
+    // registers, which only affect the bits that are written as ones.
+    if index == 0 {
+        unsafe { &*nrf52840_hal::target::P0::ptr() }
+    } else {
+        unsafe { &*nrf52840_hal::target::P1::ptr() }
+    }
+}
+
+/// Makes the pin sense the opposite of the specified level.
+fn sense(index: usize, pin: usize, high: bool) {
+    port(index).pin_cnf[pin].modify(|_, w| {
+        if high {
+            w.sense().low()
+        } else {
+            w.sense().high()
+        }
+    });
+}
+
+fn listen(index: usize, pin: usize) {
+    cortex_m::interrupt::free(|cs| {
+        let mut state = SENSE.borrow(cs).borrow_mut();
+        let high = port(index).in_.read().bits() & 1 << pin != 0;
+
+        sense(index, pin, high);
+        port(index).latch.write(|w| unsafe { w.bits(1 << pin) });
+
+        state.changes[index][pin] = 0;
+        if high {
+            state.levels[index] |= 1 << pin;
+        } else {
+            state.levels[index] &= !(1 << pin);
+        }
+    })
+}
+
+fn poll_change(index: usize, pin: usize, cx: &mut task::Context<'_>) -> task::Poll<bool> {
+    if port(index).pin_cnf[pin].read().sense().is_disabled() {
+        listen(index, pin);
+    }
+
+    cortex_m::interrupt::free(|cs| {
+        let mut state = SENSE.borrow(cs).borrow_mut();
+        if state.changes[index][pin] > 0 {
+            // Levels alternate, so every detected change can be reported in order.
+            state.changes[index][pin] -= 1;
+            state.levels[index] ^= 1 << pin;
+            task::Poll::Ready(state.levels[index] & 1 << pin != 0)
+        } else {
+            state.wakers[index][pin] = Some(cx.waker().clone());
+            task::Poll::Pending
+        }
+    })
+}
+
+#[cfg(feature = "rt")]
+#[interrupt]
+fn GPIOTE() {
+    let gpiote = unsafe { &*nrf52840_hal::target::GPIOTE::ptr() };
+    cortex_m::interrupt::free(|cs| {
+        gpiote.events_port.write(|w| unsafe { w.bits(0) });
+
+        let mut state = SENSE.borrow(cs).borrow_mut();
+        for index in 0..2 {
+            let latch = port(index).latch.read().bits();
+            for pin in (0..32).filter(|pin| latch & 1 << pin != 0) {
+                // The pin has reached the level it was sensing, so start sensing the way back.
+                let high = port(index).pin_cnf[pin].read().sense().is_high();
+                sense(index, pin, high);
+                state.changes[index][pin] = state.changes[index][pin].saturating_add(1);
+                if let Some(waker) = state.wakers[index][pin].take() {
+                    waker.wake();
+                }
+            }
+            port(index).latch.write(|w| unsafe { w.bits(latch) });
+        }
+    });
+}
@@ -0,0 +1,53 @@
+//! The drive configurations of the pins, independently of the registers that hold them.
+use embedded_platform::gpio::{DriveStrength, OpenDrainFlavor};
+
+/// How a pin drives one of the two levels.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) enum Drive {
+    Standard,
+    High,
+    Disconnect,
+}
+
+/// The drives of the low and high levels, indexed by the `DRIVE` field of the `PIN_CNF` register.
+pub(super) const DRIVES: [(Drive, Drive); 8] = [
+    (Drive::Standard, Drive::Standard),
+    (Drive::High, Drive::Standard),
+    (Drive::Standard, Drive::High),
+    (Drive::High, Drive::High),
+    (Drive::Disconnect, Drive::Standard),
+    (Drive::Disconnect, Drive::High),
+    (Drive::Standard, Drive::Disconnect),
+    (Drive::High, Drive::Disconnect),
+];
+
+/// The drives of the low and high levels after changing the drive strength, where disconnected
+/// levels stay disconnected.
+pub(super) fn with_strength(
+    (low, high): (Drive, Drive),
+    strength: DriveStrength,
+) -> (Drive, Drive) {
+    let drive = match strength {
+        DriveStrength::Standard => Drive::Standard,
+        DriveStrength::High => Drive::High,
+    };
+    let apply = |level| match level {
+        Drive::Disconnect => Drive::Disconnect,
+        Drive::Standard | Drive::High => drive,
+    };
+    (apply(low), apply(high))
+}
+
+/// The drives of the low and high levels after changing the open drain flavor, where the level
+/// that is driven keeps the current drive strength.
+pub(super) fn with_flavor((low, high): (Drive, Drive), flavor: OpenDrainFlavor) -> (Drive, Drive) {
+    let drive = if low == Drive::High || high == Drive::High {
+        Drive::High
+    } else {
+        Drive::Standard
+    };
+    match flavor {
+        OpenDrainFlavor::DriveLow => (drive, Drive::Disconnect),
+        OpenDrainFlavor::DriveHigh => (Drive::Disconnect, drive),
+    }
+}
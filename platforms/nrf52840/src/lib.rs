@@ -13,14 +13,21 @@
 #![allow(clippy::missing_safety_doc)]
 // #![forbid(unsafe_code)]
 
+use core::executor;
 use core::future;
+use core::sync::atomic;
 use core::task;
 use embedded_platform::platform;
-use embedded_platform::specs;
+use embedded_platform::platform::policy;
+use embedded_platform::specs::feather;
+use embedded_platform::time;
 
+pub mod adc;
+pub mod clock;
 pub mod error;
 pub mod gpio;
 pub mod i2c;
+pub mod pwm;
 pub mod serial;
 pub mod spi;
 pub mod timer;
@@ -29,43 +36,75 @@ use nrf52840_hal::gpio as hal_gpio;
 use nrf52840_hal::gpio::p0;
 use nrf52840_hal::gpio::p1;
 
+/// The number of tasks that applications can spawn.
+pub const MAX_TASKS: usize = 8;
+/// The maximum size in bytes of a spawned task.
+pub const MAX_TASK_SIZE: usize = 1024;
+
+static EXECUTOR: executor::Executor<MAX_TASKS, MAX_TASK_SIZE> = executor::Executor::new();
+static INITIALIZED: atomic::AtomicBool = atomic::AtomicBool::new(false);
+
 #[derive(Debug)]
 pub struct ParticleArgon {
+    clock: clock::Clock,
     p0: gpio::P0,
     p1: gpio::P1,
+    claims: gpio::Claims,
     timers: timer::Timers,
+    pwms: pwm::Pwms,
 }
 
 impl platform::Platform for ParticleArgon {
     type Error = error::Error;
-
-    fn main<I, F>(run: I) -> !
+    type Clock = clock::Clock;
+
+    /// Runs the application on the device.
+    ///
+    /// A reset outcome resets the chip, and a halt outcome disables interrupts and blinks the main
+    /// LED forever: twice per period for initialization errors, and three times for application
+    /// errors.  The backoff delay before a retry busy-waits, so spawned tasks don't run during it.
+    fn main<I, F>(policy: policy::Policy<Self::Error>, run: I) -> !
     where
-        I: FnOnce(Self) -> F,
+        I: FnMut(Self, executor::Spawner) -> F,
         F: future::Future<Output = Result<(), Self::Error>>,
     {
-        let future = async {
-            use embedded_platform::platform::PlatformExt;
-            let platform = ParticleArgon::initialize().await.unwrap();
-            run(platform).await.unwrap();
-
-            loop {
-                cortex_m::asm::wfi();
-            }
+        let delay = |backoff| {
+            spin(backoff);
+            future::ready(())
         };
-        let wait = cortex_m::asm::wfi;
-        direct_executor::run(future, wait)
+        let main = |spawner| policy::supervise(policy, spawner, run, delay);
+        match EXECUTOR.block_on(main, wait_for_interrupt) {
+            // Keep running any tasks that were spawned by the application.
+            policy::Outcome::Completed => EXECUTOR.run(wait_for_interrupt),
+            policy::Outcome::Reset => cortex_m::peripheral::SCB::sys_reset(),
+            policy::Outcome::Halt(stage) => halt(stage),
+        }
     }
 
+    /// Initializes the platform.
+    ///
+    /// The platform can be initialized again after the previous instance has been dropped, which
+    /// is what happens when a policy retries a failed application.  Since tasks spawned by a
+    /// previous attempt may still own its peripherals, initializing fails with
+    /// [`error::Error::TasksRunning`] until all of them have completed.
     fn poll_initialize(_cx: &mut task::Context<'_>) -> task::Poll<Result<Self, Self::Error>> {
-        let mut core =
-            cortex_m::Peripherals::take().expect("A cortex_m platform was already initialized");
-
-        let peripherals = nrf52840_hal::nrf52840_pac::Peripherals::take()
-            .expect("A nrf52840 platform was already initialized");
-
+        if EXECUTOR.has_tasks() {
+            return task::Poll::Ready(Err(error::Error::TasksRunning));
+        }
+        if INITIALIZED.swap(true, atomic::Ordering::AcqRel) {
+            return task::Poll::Ready(Err(error::Error::AlreadyInitialized));
+        }
+
+        // Safety: the `INITIALIZED` flag ensures that there is only one live platform instance
+        // owning the peripherals at a time.
+        let mut core = unsafe { cortex_m::Peripherals::steal() };
+        let peripherals = unsafe { nrf52840_hal::nrf52840_pac::Peripherals::steal() };
+
+        let clock = clock::Clock::new(&peripherals.CLOCK, peripherals.RTC1);
         let p0 = gpio::P0::new(peripherals.P0);
         let p1 = gpio::P1::new(peripherals.P1);
+        gpio::enable_sense(&peripherals.GPIOTE);
+        adc::enable(&peripherals.SAADC);
 
         let timers = timer::Timers::new(
             peripherals.TIMER0,
@@ -76,43 +115,202 @@ impl platform::Platform for ParticleArgon {
             &mut core.NVIC,
         );
 
-        task::Poll::Ready(Ok(Self { p0, p1, timers }))
+        let pwms = pwm::Pwms::new(
+            peripherals.PWM0,
+            peripherals.PWM1,
+            peripherals.PWM2,
+            peripherals.PWM3,
+        );
+
+        let claims = gpio::Claims::new();
+
+        task::Poll::Ready(Ok(Self {
+            clock,
+            p0,
+            p1,
+            claims,
+            timers,
+            pwms,
+        }))
+    }
+
+    fn clock(&self) -> Self::Clock {
+        self.clock
+    }
+}
+
+impl Drop for ParticleArgon {
+    fn drop(&mut self) {
+        INITIALIZED.store(false, atomic::Ordering::Release);
+    }
+}
+
+/// Sleeps until the next interrupt, unless a task was woken since the executor last checked.
+fn wait_for_interrupt() {
+    // Interrupts that happen inside of the critical section still wake the core from `wfi`, and
+    // are handled right after it.
+    cortex_m::interrupt::free(|_| {
+        if !EXECUTOR.has_ready_tasks() {
+            cortex_m::asm::wfi();
+        }
+    })
+}
+
+/// Busy-waits for at least the specified duration.
+fn spin(duration: time::Duration) {
+    // The CPU runs at 64 MHz, i.e. 64 cycles per microsecond.
+    let cycles = duration.as_micros().saturating_mul(64);
+    for _ in 0..cycles / u64::from(u32::MAX) {
+        cortex_m::asm::delay(u32::MAX);
+    }
+    cortex_m::asm::delay((cycles % u64::from(u32::MAX)) as u32);
+}
+
+/// Blinks the main LED (D7, pin 1.12) forever in a pattern that identifies the failed stage.
+fn halt(stage: policy::Stage) -> ! {
+    // The CPU runs at 64 MHz.
+    const BLINK_CYCLES: u32 = 64_000_000 / 5;
+    const PAUSE_CYCLES: u32 = 64_000_000;
+    const MAIN_LED: u32 = 1 << 12;
+
+    cortex_m::interrupt::disable();
+
+    let blinks = match stage {
+        policy::Stage::Initialize => 2,
+        policy::Stage::Run => 3,
+    };
+
+    // Safety: interrupts are disabled and the application has stopped running, so nothing else is
+    // accessing the port anymore.
+    let p1 = unsafe { &*nrf52840_hal::nrf52840_pac::P1::ptr() };
+    p1.pin_cnf[12].write(|w| w.dir().output().input().disconnect());
+
+    loop {
+        for _ in 0..blinks {
+            p1.outset.write(|w| unsafe { w.bits(MAIN_LED) });
+            cortex_m::asm::delay(BLINK_CYCLES);
+            p1.outclr.write(|w| unsafe { w.bits(MAIN_LED) });
+            cortex_m::asm::delay(BLINK_CYCLES);
+        }
+        cortex_m::asm::delay(PAUSE_CYCLES);
     }
 }
 
 impl ParticleArgon {
+    /// The pins that the application has taken so far, and the roles that they were taken as.
+    pub fn claimed_pins(&self) -> impl Iterator<Item = (gpio::PinId, feather::Role)> + '_ {
+        self.claims.iter()
+    }
+
     pub fn take_timer0(
         &mut self,
     ) -> timer::Timer<nrf52840_hal::target::TIMER0, nrf52840_hal::timer::OneShot> {
         self.timers.timer0.take().expect("timer 0 is already taken")
     }
 
+    /// Puts timer 0 back, so that it can be taken again, stopping it if it is running.
+    pub fn release_timer0<M>(&mut self, timer: timer::Timer<nrf52840_hal::target::TIMER0, M>)
+    where
+        M: Unpin,
+    {
+        self.timers.timer0 = Some(timer.into_released());
+    }
+
     pub fn take_timer1(
         &mut self,
     ) -> timer::Timer<nrf52840_hal::target::TIMER1, nrf52840_hal::timer::OneShot> {
         self.timers.timer1.take().expect("timer 1 is already taken")
     }
 
+    /// Puts timer 1 back, so that it can be taken again, stopping it if it is running.
+    pub fn release_timer1<M>(&mut self, timer: timer::Timer<nrf52840_hal::target::TIMER1, M>)
+    where
+        M: Unpin,
+    {
+        self.timers.timer1 = Some(timer.into_released());
+    }
+
     pub fn take_timer2(
         &mut self,
     ) -> timer::Timer<nrf52840_hal::target::TIMER2, nrf52840_hal::timer::OneShot> {
         self.timers.timer2.take().expect("timer 2 is already taken")
     }
 
+    /// Puts timer 2 back, so that it can be taken again, stopping it if it is running.
+    pub fn release_timer2<M>(&mut self, timer: timer::Timer<nrf52840_hal::target::TIMER2, M>)
+    where
+        M: Unpin,
+    {
+        self.timers.timer2 = Some(timer.into_released());
+    }
+
     pub fn take_timer3(
         &mut self,
     ) -> timer::Timer<nrf52840_hal::target::TIMER3, nrf52840_hal::timer::OneShot> {
         self.timers.timer3.take().expect("timer 3 is already taken")
     }
 
+    /// Puts timer 3 back, so that it can be taken again, stopping it if it is running.
+    pub fn release_timer3<M>(&mut self, timer: timer::Timer<nrf52840_hal::target::TIMER3, M>)
+    where
+        M: Unpin,
+    {
+        self.timers.timer3 = Some(timer.into_released());
+    }
+
     pub fn take_timer4(
         &mut self,
     ) -> timer::Timer<nrf52840_hal::target::TIMER4, nrf52840_hal::timer::OneShot> {
         self.timers.timer4.take().expect("timer 4 is already taken")
     }
+
+    /// Puts timer 4 back, so that it can be taken again, stopping it if it is running.
+    pub fn release_timer4<M>(&mut self, timer: timer::Timer<nrf52840_hal::target::TIMER4, M>)
+    where
+        M: Unpin,
+    {
+        self.timers.timer4 = Some(timer.into_released());
+    }
+
+    pub fn take_pwm0(&mut self) -> pwm::Pwm<nrf52840_hal::target::PWM0> {
+        self.pwms.pwm0.take().expect("PWM 0 is already taken")
+    }
+
+    /// Puts PWM 0 back, so that it can be taken again.
+    pub fn release_pwm0(&mut self, pwm: pwm::Pwm<nrf52840_hal::target::PWM0>) {
+        self.pwms.pwm0 = Some(pwm);
+    }
+
+    pub fn take_pwm1(&mut self) -> pwm::Pwm<nrf52840_hal::target::PWM1> {
+        self.pwms.pwm1.take().expect("PWM 1 is already taken")
+    }
+
+    /// Puts PWM 1 back, so that it can be taken again.
+    pub fn release_pwm1(&mut self, pwm: pwm::Pwm<nrf52840_hal::target::PWM1>) {
+        self.pwms.pwm1 = Some(pwm);
+    }
+
+    pub fn take_pwm2(&mut self) -> pwm::Pwm<nrf52840_hal::target::PWM2> {
+        self.pwms.pwm2.take().expect("PWM 2 is already taken")
+    }
+
+    /// Puts PWM 2 back, so that it can be taken again.
+    pub fn release_pwm2(&mut self, pwm: pwm::Pwm<nrf52840_hal::target::PWM2>) {
+        self.pwms.pwm2 = Some(pwm);
+    }
+
+    pub fn take_pwm3(&mut self) -> pwm::Pwm<nrf52840_hal::target::PWM3> {
+        self.pwms.pwm3.take().expect("PWM 3 is already taken")
+    }
+
+    /// Puts PWM 3 back, so that it can be taken again.
+    pub fn release_pwm3(&mut self, pwm: pwm::Pwm<nrf52840_hal::target::PWM3>) {
+        self.pwms.pwm3 = Some(pwm);
+    }
 }
 
-impl specs::feather::Feather for ParticleArgon {
+impl feather::Feather for ParticleArgon {
+    type PinId = gpio::PinId;
     type MainLed = Self::D7;
     type MainI2cMapping = i2c::I2cMapping<Self::SDA, Self::SCL>;
 
@@ -138,98 +336,408 @@ impl specs::feather::Feather for ParticleArgon {
     type A1 = gpio::Pin<p0::P0_04<hal_gpio::Input<hal_gpio::Floating>>>;
     type A0 = gpio::Pin<p0::P0_03<hal_gpio::Input<hal_gpio::Floating>>>;
 
-    fn take_main_led(&mut self) -> Self::MainLed {
-        self.p1.p1_12.take().expect("pin 1.12 is already taken")
+    fn try_take_main_led(&mut self) -> Result<Self::MainLed, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 1, index: 12 };
+        self.claims
+            .take(&mut self.p1.p1_12, id, feather::Role::MainLed)
     }
 
-    fn take_main_i2c(
+    fn try_take_main_i2c(
         &mut self,
-    ) -> <Self::MainI2cMapping as embedded_platform::i2c::I2cBusMapping<Self::SDA, Self::SCL>>::Bus
-    {
+    ) -> Result<feather::MainI2cBus<Self>, feather::PinInUse<Self::PinId>> {
         unimplemented!()
     }
 
-    fn take_sda(&mut self) -> Self::SDA {
-        self.p0.p0_26.take().expect("pin 0.26 is already taken")
+    fn try_take_sda(&mut self) -> Result<Self::SDA, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 0, index: 26 };
+        self.claims.take(&mut self.p0.p0_26, id, feather::Role::Sda)
+    }
+
+    fn try_take_scl(&mut self) -> Result<Self::SCL, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 0, index: 27 };
+        self.claims.take(&mut self.p0.p0_27, id, feather::Role::Scl)
+    }
+
+    fn try_take_d2(&mut self) -> Result<Self::D2, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 1, index: 1 };
+        self.claims.take(&mut self.p1.p1_01, id, feather::Role::D2)
+    }
+
+    fn try_take_d3(&mut self) -> Result<Self::D3, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 1, index: 2 };
+        self.claims.take(&mut self.p1.p1_02, id, feather::Role::D3)
     }
 
-    fn take_scl(&mut self) -> Self::SCL {
-        self.p0.p0_27.take().expect("pin 0.27 is already taken")
+    fn try_take_d4(&mut self) -> Result<Self::D4, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 1, index: 8 };
+        self.claims.take(&mut self.p1.p1_08, id, feather::Role::D4)
     }
 
-    fn take_d2(&mut self) -> Self::D2 {
-        self.p1.p1_01.take().expect("pin 1.01 is already taken")
+    fn try_take_d5(&mut self) -> Result<Self::D5, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 1, index: 10 };
+        self.claims.take(&mut self.p1.p1_10, id, feather::Role::D5)
     }
 
-    fn take_d3(&mut self) -> Self::D3 {
-        self.p1.p1_02.take().expect("pin 1.02 is already taken")
+    fn try_take_d6(&mut self) -> Result<Self::D6, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 1, index: 11 };
+        self.claims.take(&mut self.p1.p1_11, id, feather::Role::D6)
     }
 
-    fn take_d4(&mut self) -> Self::D4 {
-        self.p1.p1_08.take().expect("pin 1.08 is already taken")
+    fn try_take_d7(&mut self) -> Result<Self::D7, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 1, index: 12 };
+        self.claims.take(&mut self.p1.p1_12, id, feather::Role::D7)
     }
 
-    fn take_d5(&mut self) -> Self::D5 {
-        self.p1.p1_10.take().expect("pin 1.10 is already taken")
+    fn try_take_d8(&mut self) -> Result<Self::D8, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 1, index: 3 };
+        self.claims.take(&mut self.p1.p1_03, id, feather::Role::D8)
     }
 
-    fn take_d6(&mut self) -> Self::D6 {
-        self.p1.p1_11.take().expect("pin 1.11 is already taken")
+    fn try_take_p0(&mut self) -> Result<Self::P0, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 0, index: 11 };
+        self.claims.take(&mut self.p0.p0_11, id, feather::Role::P0)
     }
 
-    fn take_d7(&mut self) -> Self::D7 {
-        self.p1.p1_12.take().expect("pin 1.12 is already taken")
+    fn try_take_tx(&mut self) -> Result<Self::TX, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 0, index: 6 };
+        self.claims.take(&mut self.p0.p0_06, id, feather::Role::Tx)
     }
 
-    fn take_d8(&mut self) -> Self::D8 {
-        self.p1.p1_03.take().expect("pin 1.03 is already taken")
+    fn try_take_rx(&mut self) -> Result<Self::RX, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 0, index: 8 };
+        self.claims.take(&mut self.p0.p0_08, id, feather::Role::Rx)
     }
 
-    fn take_p0(&mut self) -> Self::P0 {
-        self.p0.p0_11.take().expect("pin 0.11 is already taken")
+    fn try_take_miso(&mut self) -> Result<Self::MISO, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 1, index: 14 };
+        self.claims
+            .take(&mut self.p1.p1_14, id, feather::Role::Miso)
     }
 
-    fn take_tx(&mut self) -> Self::TX {
-        self.p0.p0_06.take().expect("pin 0.06 is already taken")
+    fn try_take_mosi(&mut self) -> Result<Self::MOSI, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 1, index: 13 };
+        self.claims
+            .take(&mut self.p1.p1_13, id, feather::Role::Mosi)
     }
 
-    fn take_rx(&mut self) -> Self::RX {
-        self.p0.p0_08.take().expect("pin 0.08 is already taken")
+    fn try_take_sck(&mut self) -> Result<Self::SCK, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 1, index: 15 };
+        self.claims.take(&mut self.p1.p1_15, id, feather::Role::Sck)
     }
 
-    fn take_miso(&mut self) -> Self::MISO {
-        self.p1.p1_14.take().expect("pin 1.14 is already taken")
+    fn try_take_a5(&mut self) -> Result<Self::A5, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 0, index: 31 };
+        self.claims.take(&mut self.p0.p0_31, id, feather::Role::A5)
     }
 
-    fn take_mosi(&mut self) -> Self::MOSI {
-        self.p1.p1_13.take().expect("pin 1.13 is already taken")
+    fn try_take_a4(&mut self) -> Result<Self::A4, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 0, index: 30 };
+        self.claims.take(&mut self.p0.p0_30, id, feather::Role::A4)
     }
 
-    fn take_sck(&mut self) -> Self::SCK {
-        self.p1.p1_15.take().expect("pin 1.15 is already taken")
+    fn try_take_a3(&mut self) -> Result<Self::A3, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 0, index: 29 };
+        self.claims.take(&mut self.p0.p0_29, id, feather::Role::A3)
     }
 
-    fn take_a5(&mut self) -> Self::A5 {
-        self.p0.p0_31.take().expect("pin 0.31 is already taken")
+    fn try_take_a2(&mut self) -> Result<Self::A2, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 0, index: 28 };
+        self.claims.take(&mut self.p0.p0_28, id, feather::Role::A2)
     }
 
-    fn take_a4(&mut self) -> Self::A4 {
-        self.p0.p0_30.take().expect("pin 0.30 is already taken")
+    fn try_take_a1(&mut self) -> Result<Self::A1, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 0, index: 4 };
+        self.claims.take(&mut self.p0.p0_04, id, feather::Role::A1)
     }
 
-    fn take_a3(&mut self) -> Self::A3 {
-        self.p0.p0_29.take().expect("pin 0.29 is already taken")
+    fn try_take_a0(&mut self) -> Result<Self::A0, feather::PinInUse<Self::PinId>> {
+        let id = gpio::PinId { port: 0, index: 3 };
+        self.claims.take(&mut self.p0.p0_03, id, feather::Role::A0)
     }
 
-    fn take_a2(&mut self) -> Self::A2 {
-        self.p0.p0_28.take().expect("pin 0.28 is already taken")
+    fn release_main_led<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::MainLed,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 1, index: 12 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p1.p1_12, id, pin);
+        Ok(())
     }
 
-    fn take_a1(&mut self) -> Self::A1 {
-        self.p0.p0_04.take().expect("pin 0.04 is already taken")
+    fn release_sda<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::SDA,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 0, index: 26 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p0.p0_26, id, pin);
+        Ok(())
+    }
+
+    fn release_scl<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::SCL,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 0, index: 27 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p0.p0_27, id, pin);
+        Ok(())
     }
 
-    fn take_a0(&mut self) -> Self::A0 {
-        self.p0.p0_03.take().expect("pin 0.03 is already taken")
+    fn release_d2<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::D2,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 1, index: 1 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p1.p1_01, id, pin);
+        Ok(())
+    }
+
+    fn release_d3<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::D3,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 1, index: 2 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p1.p1_02, id, pin);
+        Ok(())
+    }
+
+    fn release_d4<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::D4,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 1, index: 8 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p1.p1_08, id, pin);
+        Ok(())
+    }
+
+    fn release_d5<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::D5,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 1, index: 10 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p1.p1_10, id, pin);
+        Ok(())
+    }
+
+    fn release_d6<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::D6,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 1, index: 11 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p1.p1_11, id, pin);
+        Ok(())
+    }
+
+    fn release_d7<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::D7,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 1, index: 12 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p1.p1_12, id, pin);
+        Ok(())
+    }
+
+    fn release_d8<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::D8,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 1, index: 3 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p1.p1_03, id, pin);
+        Ok(())
+    }
+
+    fn release_p0<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::P0,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 0, index: 11 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p0.p0_11, id, pin);
+        Ok(())
+    }
+
+    fn release_tx<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::TX,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 0, index: 6 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p0.p0_06, id, pin);
+        Ok(())
+    }
+
+    fn release_rx<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::RX,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 0, index: 8 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p0.p0_08, id, pin);
+        Ok(())
+    }
+
+    fn release_miso<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::MISO,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 1, index: 14 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p1.p1_14, id, pin);
+        Ok(())
+    }
+
+    fn release_mosi<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::MOSI,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 1, index: 13 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p1.p1_13, id, pin);
+        Ok(())
+    }
+
+    fn release_sck<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::SCK,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 1, index: 15 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p1.p1_15, id, pin);
+        Ok(())
+    }
+
+    fn release_a5<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::A5,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 0, index: 31 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p0.p0_31, id, pin);
+        Ok(())
+    }
+
+    fn release_a4<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::A4,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 0, index: 30 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p0.p0_30, id, pin);
+        Ok(())
+    }
+
+    fn release_a3<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::A3,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 0, index: 29 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p0.p0_29, id, pin);
+        Ok(())
+    }
+
+    fn release_a2<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::A2,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 0, index: 28 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p0.p0_28, id, pin);
+        Ok(())
+    }
+
+    fn release_a1<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::A1,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 0, index: 4 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p0.p0_04, id, pin);
+        Ok(())
+    }
+
+    fn release_a0<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::A0,
+            Error = Self::Error,
+        >,
+    {
+        let id = gpio::PinId { port: 0, index: 3 };
+        let pin = pin.into_floating_input_pin()?;
+        self.claims.release(&mut self.p0.p0_03, id, pin);
+        Ok(())
     }
 }
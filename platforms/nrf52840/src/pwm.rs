@@ -0,0 +1,219 @@
+//! Hardware PWM, using the PWM peripherals.
+//!
+//! Each of the four PWM peripherals drives a single pin, so that every PWM pin can have its own
+//! rate.  The peripheral reads the compare value from a one-element sequence in RAM, and keeps
+//! repeating the last value of the sequence, so the CPU is only involved when the duty cycle
+//! changes.
+use crate::error;
+use crate::gpio;
+use core::fmt;
+use core::ops;
+use core::pin;
+use core::sync::atomic;
+use core::task;
+
+/// The frequency of the clock that PWM counters run at before prescaling.
+const BASE_CLOCK_HZ: u32 = 16_000_000;
+/// The largest prescaler, which divides the base clock by 128.
+const MAX_PRESCALER: u8 = 7;
+/// The range of values that the `COUNTERTOP` register accepts.
+const COUNTERTOP: ops::RangeInclusive<u64> = 3..=32_767;
+/// The bit of a sequence value that inverts the output.
+///
+/// With the bit cleared, the output starts each period high and falls once the counter reaches the
+/// compare value.
+const POLARITY_RISING_EDGE: u16 = 1 << 15;
+
+/// A PWM peripheral of the nRF52840.
+pub trait Instance: ops::Deref<Target = nrf52840_hal::target::pwm0::RegisterBlock> + Unpin {
+    /// The sequence that the peripheral reads its compare value from.
+    fn sequence() -> &'static atomic::AtomicU16;
+}
+
+pub struct Pwm<T> {
+    raw: T,
+}
+
+#[derive(Debug)]
+pub(crate) struct Pwms {
+    pub pwm0: Option<Pwm<nrf52840_hal::target::PWM0>>,
+    pub pwm1: Option<Pwm<nrf52840_hal::target::PWM1>>,
+    pub pwm2: Option<Pwm<nrf52840_hal::target::PWM2>>,
+    pub pwm3: Option<Pwm<nrf52840_hal::target::PWM3>>,
+}
+
+/// A pin that is driven by a PWM peripheral.
+#[derive(Debug)]
+pub struct PwmPin<T, P> {
+    pwm: Pwm<T>,
+    pin: P,
+    top: u16,
+    active_high: bool,
+}
+
+impl<T> Pwm<T>
+where
+    T: Instance,
+{
+    pub(crate) fn new(raw: T) -> Self {
+        Self { raw }
+    }
+}
+
+impl<T> fmt::Debug for Pwm<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pwm").finish()
+    }
+}
+
+impl Pwms {
+    pub(crate) fn new(
+        pwm0: nrf52840_hal::target::PWM0,
+        pwm1: nrf52840_hal::target::PWM1,
+        pwm2: nrf52840_hal::target::PWM2,
+        pwm3: nrf52840_hal::target::PWM3,
+    ) -> Self {
+        let pwm0 = Some(Pwm::new(pwm0));
+        let pwm1 = Some(Pwm::new(pwm1));
+        let pwm2 = Some(Pwm::new(pwm2));
+        let pwm3 = Some(Pwm::new(pwm3));
+
+        Pwms {
+            pwm0,
+            pwm1,
+            pwm2,
+            pwm3,
+        }
+    }
+}
+
+impl<T, P> embedded_platform::pwm::IntoPwmPin<P> for Pwm<T>
+where
+    T: Instance,
+    P: embedded_platform::gpio::IntoPushPullOutputPin<Error = error::Error>,
+    P::PushPullOutputPin: gpio::PhysicalPin,
+{
+    type Error = error::Error;
+    type PwmPin = PwmPin<T, P::PushPullOutputPin>;
+
+    fn into_pwm_pin(
+        self,
+        pin: P,
+        config: embedded_platform::pwm::Config,
+    ) -> Result<Self::PwmPin, Self::Error> {
+        let (prescaler, top) = countertop(config.rate())?;
+        let active_high = config.is_active_high();
+        // The pin keeps its inactive level whenever the peripheral is disconnected.
+        let pin = pin.into_push_pull_output_pin(!active_high)?;
+        let id = gpio::PhysicalPin::id(&pin);
+
+        let raw = &self.raw;
+        raw.psel.out[0].write(|w| unsafe {
+            w.pin().bits(id.index);
+            w.port().bit(id.port == 1);
+            w.connect().connected()
+        });
+        raw.enable.write(|w| w.enable().enabled());
+        raw.mode.write(|w| w.updown().up());
+        raw.prescaler
+            .write(|w| unsafe { w.prescaler().bits(prescaler) });
+        raw.countertop
+            .write(|w| unsafe { w.countertop().bits(top) });
+        raw.decoder.write(|w| {
+            w.load().common();
+            w.mode().refresh_count()
+        });
+        raw.loop_.write(|w| w.cnt().disabled());
+        raw.seq0
+            .ptr
+            .write(|w| unsafe { w.bits(T::sequence().as_ptr() as u32) });
+        raw.seq0.cnt.write(|w| unsafe { w.bits(1) });
+        raw.seq0.refresh.write(|w| unsafe { w.bits(0) });
+        raw.seq0.enddelay.write(|w| unsafe { w.bits(0) });
+
+        let mut pwm = PwmPin {
+            pwm: self,
+            pin,
+            top,
+            active_high,
+        };
+        pwm.load(config.initial_duty());
+        Ok(pwm)
+    }
+}
+
+impl<T, P> PwmPin<T, P>
+where
+    T: Instance,
+{
+    /// Stops the peripheral, returning it and the pin, which is left at its inactive level.
+    pub fn into_inner(self) -> (Pwm<T>, P) {
+        let raw = &self.pwm.raw;
+        raw.tasks_stop.write(|w| unsafe { w.bits(1) });
+        raw.enable.write(|w| w.enable().disabled());
+        raw.psel.out[0].write(|w| w.connect().disconnected());
+        (self.pwm, self.pin)
+    }
+
+    /// Stores the compare value for a duty cycle, and has the peripheral load it.
+    fn load(&mut self, duty: embedded_platform::pwm::Duty) {
+        let compare = duty.scale(u64::from(self.top)) as u16;
+        let value = if self.active_high {
+            compare
+        } else {
+            compare | POLARITY_RISING_EDGE
+        };
+        T::sequence().store(value, atomic::Ordering::Relaxed);
+        // The peripheral reads the sequence using DMA, which the compiler doesn't know about.
+        atomic::compiler_fence(atomic::Ordering::SeqCst);
+        self.pwm.raw.tasks_seqstart[0].write(|w| unsafe { w.bits(1) });
+    }
+}
+
+impl<T, P> embedded_platform::pwm::PwmPin for PwmPin<T, P>
+where
+    T: Instance,
+    P: Unpin,
+{
+    type Error = error::Error;
+
+    fn poll_set_duty(
+        mut self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+        duty: embedded_platform::pwm::Duty,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        self.load(duty);
+        task::Poll::Ready(Ok(()))
+    }
+}
+
+/// Finds the smallest prescaler that lets the counter count one period of the rate, for the best
+/// resolution, and returns it along with the number of ticks per period.
+fn countertop(rate: embedded_platform::time::Rate) -> Result<(u8, u16), error::Error> {
+    for prescaler in 0..=MAX_PRESCALER {
+        let tick_rate = embedded_platform::time::Rate::from_hz(BASE_CLOCK_HZ >> prescaler);
+        match rate.period_ticks(tick_rate) {
+            Some(ticks) if COUNTERTOP.contains(&ticks) => return Ok((prescaler, ticks as u16)),
+            Some(ticks) if ticks < *COUNTERTOP.start() => break,
+            _ => {}
+        }
+    }
+    Err(error::Error::OutOfRange)
+}
+
+macro_rules! pwm {
+    ($ty:ty, $sequence:ident) => {
+        static $sequence: atomic::AtomicU16 = atomic::AtomicU16::new(0);
+
+        impl Instance for $ty {
+            fn sequence() -> &'static atomic::AtomicU16 {
+                &$sequence
+            }
+        }
+    };
+}
+
+pwm!(nrf52840_hal::target::PWM0, PWM0_SEQUENCE);
+pwm!(nrf52840_hal::target::PWM1, PWM1_SEQUENCE);
+pwm!(nrf52840_hal::target::PWM2, PWM2_SEQUENCE);
+pwm!(nrf52840_hal::target::PWM3, PWM3_SEQUENCE);
@@ -2,6 +2,7 @@
 
 use crate::error;
 use core::cell;
+use core::convert;
 use core::fmt;
 use core::pin;
 use core::task;
@@ -12,7 +13,7 @@ where
     T: Unpin,
 {
     ticks: u32,
-    raw: Option<nrf52840_hal::timer::Timer<T, M>>,
+    raw: nrf52840_hal::timer::Timer<T, M>,
 }
 
 #[derive(Debug)]
@@ -30,7 +31,6 @@ where
 {
     fn new(raw: nrf52840_hal::timer::Timer<T, M>) -> Self {
         let ticks = 1;
-        let raw = Some(raw);
         Self { ticks, raw }
     }
 }
@@ -81,26 +81,103 @@ impl Timers {
     }
 }
 
-struct InterruptState<T> {
-    inner: Option<InnerInterruptState<T>>,
-}
-
-struct InnerInterruptState<T> {
-    waker: task::Waker,
-    timer: InterruptTimer<T>,
+/// The state shared with the interrupt handler of a timer, which counts every tick, whether or
+/// not anyone is waiting for it, so that overruns can be detected.
+struct InterruptState {
+    /// The number of ticks that haven't been observed yet.
+    ticks: u32,
+    waker: Option<task::Waker>,
 }
 
-enum InterruptTimer<T> {
-    Oneshot(nrf52840_hal::timer::Timer<T, nrf52840_hal::timer::OneShot>),
-    Periodic(nrf52840_hal::timer::Timer<T, nrf52840_hal::timer::Periodic>),
-}
+type InterruptStateCell = bare_metal::Mutex<cell::RefCell<InterruptState>>;
 
-type InterruptStateCell<T> = bare_metal::Mutex<cell::RefCell<InterruptState<T>>>;
+type Registers = nrf52840_hal::target::timer0::RegisterBlock;
 
 macro_rules! timer {
     ($ty:ty, $interrupt:ident, $state:ident) => {
-        static $state: InterruptStateCell<$ty> =
-            bare_metal::Mutex::new(cell::RefCell::new(InterruptState { inner: None }));
+        static $state: InterruptStateCell =
+            bare_metal::Mutex::new(cell::RefCell::new(InterruptState {
+                ticks: 0,
+                waker: None,
+            }));
+
+        impl<M> Timer<$ty, M>
+        where
+            M: Unpin,
+        {
+            /// Stops this timer and turns it back into the one-shot mode that it was taken in.
+            pub(crate) fn into_released(self) -> Timer<$ty, nrf52840_hal::timer::OneShot> {
+                Self::stop();
+                Self::reset();
+                let mut raw = self.raw.into_oneshot();
+                // The interrupt is disabled while the timer is used as a trigger.
+                raw.enable_interrupt(None);
+                Timer::new(raw)
+            }
+
+            /// Starts this timer in periodic mode to trigger another peripheral, and returns it
+            /// along with the address of the event that it generates once per period, for use
+            /// with PPI.
+            ///
+            /// The interrupt of the timer is disabled, so the timer doesn't tick, until it is
+            /// released.
+            pub(crate) fn into_trigger(
+                self,
+                rate: embedded_platform::time::Rate,
+            ) -> Result<(Timer<$ty, nrf52840_hal::timer::Periodic>, u32), error::Error> {
+                use embedded_hal::timer::CountDown;
+
+                let mut timer =
+                    embedded_platform::timer::IntoPeriodicTimer::into_periodic_timer(self, rate)?;
+                Self::stop();
+                timer.raw.disable_interrupt(None);
+                Self::reset();
+                timer.raw.start(timer.ticks);
+                let event = &Self::registers().events_compare[0] as *const _ as u32;
+                Ok((timer, event))
+            }
+
+            fn registers() -> &'static Registers {
+                // Safety: the registers are only accessed by the owner of the timer, and by the
+                // interrupt handler to acknowledge the compare event inside of critical sections.
+                unsafe { &*<$ty>::ptr().cast::<Registers>() }
+            }
+
+            /// Stops the timer, without touching the ticks that it has counted so far.
+            ///
+            /// Stopping a timer that isn't running has no effect.
+            fn stop() {
+                Self::registers().tasks_stop.write(|w| unsafe { w.bits(1) });
+            }
+
+            /// Reads how far the timer has counted since it was last cleared, by capturing its
+            /// counter into the `CC[1]` register, which isn't used for comparing.
+            fn counter() -> u32 {
+                let registers = Self::registers();
+                registers.tasks_capture[1].write(|w| unsafe { w.bits(1) });
+                registers.cc[1].read().bits()
+            }
+
+            /// Discards any ticks that haven't been observed yet, including a pending interrupt.
+            ///
+            /// The timer should be stopped before calling this, so that no new tick sneaks in.
+            fn reset() {
+                reset(
+                    Self::registers(),
+                    &$state,
+                    nrf52840_hal::target::Interrupt::$interrupt,
+                );
+            }
+
+            /// Stops the timer, discards any unobserved ticks, and starts counting down again.
+            fn rearm(&mut self) {
+                use embedded_hal::timer::CountDown;
+
+                Self::stop();
+                Self::reset();
+                self.raw.start(self.ticks);
+            }
+        }
 
         impl<M> embedded_platform::timer::Timer for Timer<$ty, M>
         where
@@ -112,11 +189,7 @@ macro_rules! timer {
                 mut self: pin::Pin<&mut Self>,
                 _cx: &mut task::Context<'_>,
             ) -> task::Poll<Result<(), Self::Error>> {
-                use embedded_hal::timer::CountDown;
-
-                let this = &mut *self;
-                this.raw.unwrap().start(this.ticks);
-
+                self.rearm();
                 task::Poll::Ready(Ok(()))
             }
 
@@ -124,7 +197,14 @@ macro_rules! timer {
                 self: pin::Pin<&mut Self>,
                 cx: &mut task::Context<'_>,
             ) -> task::Poll<Result<(), Self::Error>> {
-                impl_poll_tick(&mut *self, cx, &$state)
+                impl_poll_ticks(cx, &$state, false).map(|result| result.map(|_| ()))
+            }
+
+            fn poll_ticks(
+                self: pin::Pin<&mut Self>,
+                cx: &mut task::Context<'_>,
+            ) -> task::Poll<Result<u32, Self::Error>> {
+                impl_poll_ticks(cx, &$state, true)
             }
         }
 
@@ -138,9 +218,14 @@ macro_rules! timer {
                 self,
                 rate: embedded_platform::time::Rate,
             ) -> Result<Self::PeriodicTimer, Self::Error> {
-                let ticks = (nrf52840_hal::timer::Timer::<$ty, M>::TICKS_PER_SECOND as f32
-                    / rate.as_hz()) as u32;
-                let raw = Some(self.raw.unwrap().into_periodic());
+                let tick_rate = embedded_platform::time::Rate::from_hz(
+                    nrf52840_hal::timer::Timer::<$ty, M>::TICKS_PER_SECOND,
+                );
+                let ticks = rate
+                    .period_ticks(tick_rate)
+                    .and_then(|ticks| convert::TryFrom::try_from(ticks).ok())
+                    .ok_or(error::Error::OutOfRange)?;
+                let raw = self.raw.into_periodic();
                 Ok(Timer { ticks, raw })
             }
         }
@@ -155,47 +240,143 @@ macro_rules! timer {
                 self,
                 period: embedded_platform::time::Duration,
             ) -> Result<Self::OneshotTimer, Self::Error> {
-                let ticks = period.as_nanos()
-                    * nrf52840_hal::timer::Timer::<$ty, M>::TICKS_PER_SECOND
-                    / 1_000_000;
-                let raw = Some(self.raw.unwrap().into_oneshot());
+                let ticks = duration_ticks(
+                    period,
+                    nrf52840_hal::timer::Timer::<$ty, M>::TICKS_PER_SECOND,
+                )?;
+                let raw = self.raw.into_oneshot();
                 Ok(Timer { ticks, raw })
             }
         }
 
+        impl<M> embedded_platform::timer::CancellableTimer for Timer<$ty, M>
+        where
+            M: Unpin,
+        {
+            fn poll_cancel(
+                self: pin::Pin<&mut Self>,
+                _cx: &mut task::Context<'_>,
+            ) -> task::Poll<Result<(), Self::Error>> {
+                Self::stop();
+                Self::reset();
+
+                task::Poll::Ready(Ok(()))
+            }
+        }
+
+        impl<M> embedded_platform::timer::RestartableTimer for Timer<$ty, M>
+        where
+            M: Unpin,
+        {
+            fn poll_restart(
+                mut self: pin::Pin<&mut Self>,
+                _cx: &mut task::Context<'_>,
+                delay: embedded_platform::time::Duration,
+            ) -> task::Poll<Result<(), Self::Error>> {
+                self.ticks = duration_ticks(
+                    delay,
+                    nrf52840_hal::timer::Timer::<$ty, M>::TICKS_PER_SECOND,
+                )?;
+                self.rearm();
+
+                task::Poll::Ready(Ok(()))
+            }
+        }
+
+        impl<M> embedded_platform::timer::CountingTimer for Timer<$ty, M>
+        where
+            M: Unpin,
+        {
+            fn poll_elapsed(
+                self: pin::Pin<&mut Self>,
+                _cx: &mut task::Context<'_>,
+            ) -> task::Poll<Result<embedded_platform::time::Duration, Self::Error>> {
+                let elapsed = ticks_duration(
+                    Self::counter(),
+                    nrf52840_hal::timer::Timer::<$ty, M>::TICKS_PER_SECOND,
+                );
+
+                task::Poll::Ready(Ok(elapsed))
+            }
+        }
+
         #[cfg(feature = "rt")]
         #[interrupt]
         fn $interrupt() {
-            interrupt_impl(&$state, nrf52840_hal::target::Interrupt::$interrupt);
+            interrupt_impl(
+                Timer::<$ty, nrf52840_hal::timer::OneShot>::registers(),
+                &$state,
+                nrf52840_hal::target::Interrupt::$interrupt,
+            );
         }
     };
 }
 
-fn impl_poll_tick<T, M, E>(
-    this: &mut Timer<T, M>,
+/// Observes either one or all of the ticks counted by the interrupt handler.
+fn impl_poll_ticks<E>(
     cx: &mut task::Context,
-    state: &InterruptStateCell<T>,
-) -> task::Poll<Result<(), E>>
-where
-    T: Unpin,
-{
+    state: &InterruptStateCell,
+    all: bool,
+) -> task::Poll<Result<u32, E>> {
     cortex_m::interrupt::free(|cs| {
         let mut state = state.borrow(cs).borrow_mut();
-        if let Some(state) = state.inner.take() {
-            this.raw = Some(state.timer);
-            task::Poll::Ready(Ok(()))
+        if state.ticks > 0 {
+            let count = if all { state.ticks } else { 1 };
+            state.ticks -= count;
+            task::Poll::Ready(Ok(count))
         } else {
-            state.inner = Some((this.raw.take().unwrap(), cx.waker().clone()));
+            state.waker = Some(cx.waker().clone());
             task::Poll::Pending
         }
     })
 }
 
-fn interrupt_impl<T>(state: &InterruptStateCell<T>, interrupt: nrf52840_hal::target::Interrupt) {
-    cortex_m::peripheral::NVIC::unpend(interrupt);
+fn reset(
+    registers: &Registers,
+    state: &InterruptStateCell,
+    interrupt: nrf52840_hal::target::Interrupt,
+) {
+    cortex_m::interrupt::free(|cs| {
+        registers.events_compare[0].reset();
+        cortex_m::peripheral::NVIC::unpend(interrupt);
+        let mut state = state.borrow(cs).borrow_mut();
+        state.ticks = 0;
+        state.waker = None;
+    })
+}
+
+fn duration_ticks(
+    duration: embedded_platform::time::Duration,
+    ticks_per_second: u32,
+) -> Result<u32, error::Error> {
+    let ticks = u128::from(duration.as_nanos()) * u128::from(ticks_per_second)
+        / u128::from(embedded_platform::time::Duration::from_seconds(1).as_nanos());
+    convert::TryFrom::try_from(ticks).map_err(|_| error::Error::OutOfRange)
+}
+
+fn ticks_duration(ticks: u32, ticks_per_second: u32) -> embedded_platform::time::Duration {
+    let nanos = u64::from(ticks) * embedded_platform::time::Duration::from_seconds(1).as_nanos()
+        / u64::from(ticks_per_second);
+    embedded_platform::time::Duration::from_nanos(nanos)
+}
+
+/// Acknowledges the compare event of a timer and counts it as a tick, waking the task that waits
+/// for it, if any.
+fn interrupt_impl(
+    registers: &Registers,
+    state: &InterruptStateCell,
+    interrupt: nrf52840_hal::target::Interrupt,
+) {
     cortex_m::interrupt::free(|cs| {
-        if let Some((raw, waker)) = state.borrow(cs).borrow_mut().inner {
-            raw.wait().unwrap();
+        if registers.events_compare[0].read().bits() == 0 {
+            return;
+        }
+        registers.events_compare[0].reset();
+        cortex_m::peripheral::NVIC::unpend(interrupt);
+
+        let mut state = state.borrow(cs).borrow_mut();
+        state.ticks = state.ticks.saturating_add(1);
+        if let Some(waker) = state.waker.take() {
             waker.wake();
         }
     });
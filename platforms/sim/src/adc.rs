@@ -0,0 +1,250 @@
+//! Simulated analog inputs.
+//!
+//! Every simulated pin can be used as an analog input.  Inputs measure the voltage that is applied
+//! to the pin using [`gpio::Probe::apply_voltage`], or otherwise the supply or ground voltage
+//! depending on the level of the pin.  Like the SAADC of the nRF52 family, the simulated converter
+//! has a resolution of 12 bits, an internal reference of 600 mV, and a supply reference of a
+//! quarter of the supply voltage.
+//!
+//! A simulated [`timer::Timer`] can sample an analog input continuously, taking a sample every
+//! time the timer ticks in virtual time.  Synthetic [`Waveform`]s can be applied to pins using
+//! [`gpio::Probe::apply_waveform`], to see how applications handle changing signals.
+use crate::error;
+use crate::gpio;
+use crate::timer;
+use core::pin;
+use core::task;
+use std::f64;
+
+/// The resolution of the simulated converter, in bits.
+pub const RESOLUTION: u32 = 12;
+/// The voltage of the internal reference, in millivolts.
+pub const INTERNAL_REFERENCE_MILLIVOLTS: u32 = 600;
+
+/// A synthetic signal that can be applied to a simulated pin.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Waveform {
+    /// A sine wave that oscillates around `offset` by `amplitude` millivolts, starting at the
+    /// offset and rising.
+    Sine {
+        offset: u32,
+        amplitude: u32,
+        frequency: embedded_platform::time::Rate,
+    },
+    /// A square wave that is at `high` millivolts during the first half of each period, and at
+    /// `low` millivolts during the second half.
+    Square {
+        low: u32,
+        high: u32,
+        frequency: embedded_platform::time::Rate,
+    },
+    /// A sawtooth wave that rises linearly from `low` to `high` millivolts during each period.
+    Sawtooth {
+        low: u32,
+        high: u32,
+        frequency: embedded_platform::time::Rate,
+    },
+}
+
+/// A simulated pin in analog input mode.
+#[derive(Debug)]
+pub struct AnalogPin {
+    pin: gpio::Pin<gpio::Analog>,
+    config: embedded_platform::adc::Config,
+}
+
+impl AnalogPin {
+    /// The physical pin that this pin is bound to.
+    pub fn id(&self) -> gpio::PinId {
+        self.pin.id()
+    }
+
+    /// Returns the underlying pin, e.g. to re-configure it into a digital mode.
+    pub fn into_inner(self) -> gpio::Pin<gpio::Analog> {
+        self.pin
+    }
+
+    /// The input voltage in millivolts that corresponds to the full range of the converter.
+    fn full_scale(&self) -> u32 {
+        let reference = match self.config.reference {
+            embedded_platform::adc::Reference::Internal => INTERNAL_REFERENCE_MILLIVOLTS,
+            embedded_platform::adc::Reference::Supply => gpio::SUPPLY_MILLIVOLTS / 4,
+        };
+        self.config.full_scale(reference)
+    }
+
+    /// Converts a voltage into the value that the converter reports for it.
+    fn raw(&self, millivolts: u32) -> u16 {
+        let max = (1 << RESOLUTION) - 1;
+        let raw = (u64::from(millivolts) << RESOLUTION) / u64::from(self.full_scale());
+        raw.min(max) as u16
+    }
+}
+
+/// A simulated analog input that is sampled every time a periodic timer ticks.
+#[derive(Debug)]
+pub struct Sampler {
+    pin: AnalogPin,
+    timer: timer::Timer,
+    period: embedded_platform::time::Duration,
+    /// The time at which the next sample is taken.
+    next: embedded_platform::time::Instant,
+    samples: [i16; embedded_platform::adc::BUFFER_LEN],
+    filled: usize,
+    ready: Option<embedded_platform::adc::Buffer>,
+    overruns: u32,
+}
+
+impl Waveform {
+    /// The voltage of this waveform at the specified time, in millivolts.
+    pub fn millivolts_at(&self, time: embedded_platform::time::Instant) -> u32 {
+        match *self {
+            Waveform::Sine {
+                offset,
+                amplitude,
+                frequency,
+            } => {
+                let angle = 2.0 * f64::consts::PI * phase(time, frequency);
+                let millivolts = f64::from(offset) + f64::from(amplitude) * angle.sin();
+                millivolts.round().max(0.0) as u32
+            }
+            Waveform::Square {
+                low,
+                high,
+                frequency,
+            } => {
+                if phase(time, frequency) < 0.5 {
+                    high
+                } else {
+                    low
+                }
+            }
+            Waveform::Sawtooth {
+                low,
+                high,
+                frequency,
+            } => {
+                let rise = f64::from(high) - f64::from(low);
+                let millivolts = f64::from(low) + rise * phase(time, frequency);
+                millivolts.round().max(0.0) as u32
+            }
+        }
+    }
+}
+
+/// The fraction of the current period of a signal that has passed at the specified time.
+fn phase(time: embedded_platform::time::Instant, frequency: embedded_platform::time::Rate) -> f64 {
+    let period = frequency.period().as_nanos().max(1);
+    (time.as_nanos() % period) as f64 / period as f64
+}
+
+impl Sampler {
+    /// Stops sampling, returning the analog input and the timer, which is left running.
+    pub fn into_inner(self) -> (AnalogPin, timer::Timer) {
+        (self.pin, self.timer)
+    }
+
+    /// Takes the sample that is due at the next tick of the timer.
+    fn sample(&mut self) {
+        let millivolts = self.pin.pin.millivolts_at(self.next);
+        self.samples[self.filled] = self.pin.raw(millivolts) as i16;
+        self.filled += 1;
+        self.next += self.period;
+
+        if self.filled == self.samples.len() {
+            self.filled = 0;
+            if self.ready.take().is_some() {
+                self.overruns = self.overruns.saturating_add(1);
+            }
+            self.ready = Some(embedded_platform::adc::Buffer::new(
+                self.samples,
+                RESOLUTION,
+                self.pin.full_scale(),
+                self.overruns,
+            ));
+        }
+    }
+}
+
+impl<M> embedded_platform::adc::IntoAnalogInputPin for gpio::Pin<M> {
+    type AnalogInputPin = AnalogPin;
+
+    fn into_analog_input_pin(
+        self,
+        config: embedded_platform::adc::Config,
+    ) -> Result<Self::AnalogInputPin, Self::Error> {
+        let pin = self.into_analog();
+        Ok(AnalogPin { pin, config })
+    }
+}
+
+impl embedded_platform::adc::AnalogInputPin for AnalogPin {
+    type Error = error::Error;
+
+    fn poll_read(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<embedded_platform::adc::Reading, Self::Error>> {
+        let raw = self.raw(self.pin.millivolts());
+        let reading =
+            embedded_platform::adc::Reading::new(i32::from(raw), RESOLUTION, self.full_scale());
+        task::Poll::Ready(Ok(reading))
+    }
+}
+
+impl embedded_platform::adc::IntoSampler<AnalogPin> for timer::Timer {
+    type Error = error::Error;
+    type Sampler = Sampler;
+
+    fn into_sampler(
+        self,
+        pin: AnalogPin,
+        rate: embedded_platform::time::Rate,
+    ) -> Result<Self::Sampler, Self::Error> {
+        let timer = embedded_platform::timer::IntoPeriodicTimer::into_periodic_timer(self, rate)?;
+        let period = rate.period();
+        let next = timer.start_immediately() + period;
+        let samples = [0; embedded_platform::adc::BUFFER_LEN];
+        let filled = 0;
+        let ready = None;
+        let overruns = 0;
+        Ok(Sampler {
+            pin,
+            timer,
+            period,
+            next,
+            samples,
+            filled,
+            ready,
+            overruns,
+        })
+    }
+}
+
+impl embedded_platform::adc::Sampler for Sampler {
+    type Error = error::Error;
+
+    fn poll_buffer(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<embedded_platform::adc::Buffer, Self::Error>> {
+        let this = &mut *self;
+
+        while let task::Poll::Ready(ticks) =
+            embedded_platform::timer::Timer::poll_ticks(pin::Pin::new(&mut this.timer), cx)
+        {
+            match ticks {
+                Ok(ticks) => (0..ticks).for_each(|_| this.sample()),
+                Err(err) => return task::Poll::Ready(Err(err)),
+            }
+        }
+
+        match this.ready.take() {
+            Some(buffer) => {
+                this.overruns = 0;
+                task::Poll::Ready(Ok(buffer))
+            }
+            None => task::Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Deterministic virtual time.
+//!
+//! A [`VirtualClock`] only moves forward when [`VirtualClock::advance`] is called.  Timers created
+//! from the clock expire as virtual time passes their deadlines, which makes timing behaviour
+//! reproducible and lets tests cover long delays without actually sleeping.
+use crate::timer;
+use std::sync;
+
+#[derive(Debug, Default)]
+pub(crate) struct State {
+    pub(crate) now: u64,
+    pub(crate) timers: Vec<timer::State>,
+}
+
+/// A clock that is advanced manually.
+///
+/// Cloning a clock creates another handle to the same virtual time.
+#[derive(Clone, Debug, Default)]
+pub struct VirtualClock {
+    state: sync::Arc<sync::Mutex<State>>,
+}
+
+impl VirtualClock {
+    /// Creates a new clock, starting at time zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new timer that is driven by this clock.
+    pub fn timer(&self) -> timer::Timer {
+        let index = {
+            let mut state = self.lock();
+            state.timers.push(timer::State::default());
+            state.timers.len() - 1
+        };
+        timer::Timer::new(self.clone(), index)
+    }
+
+    /// Moves virtual time forward by the specified duration.
+    ///
+    /// Every timer deadline that is passed along the way is expired in deadline order, with ties
+    /// broken by the order in which the timers were created.  Periodic timers may expire several
+    /// times during a single call.  The wakers of the expired timers are woken in the same order
+    /// once all deadlines have been processed.
+    ///
+    /// Virtual time saturates at the largest representable instant, so advancing by
+    /// [`Duration::MAX`](embedded_platform::time::Duration::MAX) moves the clock to the end of
+    /// time instead of overflowing.
+    pub fn advance(&self, duration: embedded_platform::time::Duration) {
+        let wakers = {
+            let mut state = self.lock();
+            let target = state.now.saturating_add(duration.as_nanos());
+            let mut wakers = Vec::new();
+
+            while let Some((index, deadline)) = state
+                .timers
+                .iter()
+                .enumerate()
+                .filter_map(|(index, timer)| timer.deadline().map(|deadline| (index, deadline)))
+                .filter(|&(_, deadline)| deadline <= target)
+                .min_by_key(|&(index, deadline)| (deadline, index))
+            {
+                state.now = deadline;
+                if let Some(waker) = state.timers[index].expire() {
+                    wakers.push(waker);
+                }
+            }
+
+            state.now = target;
+            wakers
+        };
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Moves virtual time forward to the earliest deadline of any running timer.
+    ///
+    /// Returns `false` without changing the time if no timer is running.
+    pub fn advance_to_next_deadline(&self) -> bool {
+        let remaining = {
+            let state = self.lock();
+            state
+                .timers
+                .iter()
+                .filter_map(timer::State::deadline)
+                .min()
+                .map(|deadline| deadline - state.now)
+        };
+
+        match remaining {
+            Some(remaining) => {
+                let remaining = embedded_platform::time::Duration::from_nanos(remaining);
+                self.advance(remaining);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn lock(&self) -> sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+}
+
+impl embedded_platform::clock::Clock for VirtualClock {
+    fn now(&self) -> embedded_platform::time::Instant {
+        embedded_platform::time::Instant::from_nanos(self.lock().now)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::gpio;
+use embedded_platform::specs::feather;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error {
+    Elapsed,
+    Eof,
+    WriteZero,
+    /// No simulated I²C device acknowledged the specified address.
+    Nack(u8),
+    /// A type-erased pin was used in a mode that it isn't configured in.
+    WrongMode,
+    /// A pin was taken after it had already been taken.
+    PinInUse(feather::PinInUse<gpio::PinId>),
+    /// A pin was released in the role of a different pin.
+    WrongPin,
+}
+
+impl embedded_platform::io::ReadError for Error {
+    fn eof() -> Self {
+        Error::Eof
+    }
+}
+
+impl embedded_platform::io::WriteError for Error {
+    fn write_zero() -> Self {
+        Error::WriteZero
+    }
+}
+
+impl From<embedded_platform::timer::timeout::Elapsed> for Error {
+    fn from(_: embedded_platform::timer::timeout::Elapsed) -> Self {
+        Error::Elapsed
+    }
+}
+
+impl From<feather::PinInUse<gpio::PinId>> for Error {
+    fn from(err: feather::PinInUse<gpio::PinId>) -> Self {
+        Error::PinInUse(err)
+    }
+}
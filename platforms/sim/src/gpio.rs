@@ -0,0 +1,773 @@
+//! Simulated GPIO pins.
+//!
+//! Every pin on the simulated board is backed by shared state.  A [`Probe`] can be used to inspect
+//! what the application has written to a pin, and to drive its level from the outside.  Input pins
+//! are woken whenever their level changes, as if by an interrupt.
+use crate::adc;
+use crate::error;
+use core::fmt;
+use core::marker;
+use core::mem;
+use core::pin;
+use core::task;
+use embedded_platform::gpio::{DriveStrength, OpenDrainFlavor};
+use embedded_platform::specs::feather;
+use std::sync;
+
+/// The number of physical pins on the simulated board.
+pub const PIN_COUNT: usize = 32;
+/// The supply voltage of the simulated board, in millivolts.
+pub const SUPPLY_MILLIVOLTS: u32 = 3300;
+
+/// Identifies a physical pin on the simulated board.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PinId(pub u8);
+
+pub const SDA: PinId = PinId(0);
+pub const SCL: PinId = PinId(1);
+pub const D2: PinId = PinId(2);
+pub const D3: PinId = PinId(3);
+pub const D4: PinId = PinId(4);
+pub const D5: PinId = PinId(5);
+pub const D6: PinId = PinId(6);
+pub const D7: PinId = PinId(7);
+pub const D8: PinId = PinId(8);
+pub const P0: PinId = PinId(9);
+pub const TX: PinId = PinId(10);
+pub const RX: PinId = PinId(11);
+pub const MISO: PinId = PinId(12);
+pub const MOSI: PinId = PinId(13);
+pub const SCK: PinId = PinId(14);
+pub const A0: PinId = PinId(15);
+pub const A1: PinId = PinId(16);
+pub const A2: PinId = PinId(17);
+pub const A3: PinId = PinId(18);
+pub const A4: PinId = PinId(19);
+pub const A5: PinId = PinId(20);
+pub const MAIN_LED: PinId = PinId(21);
+
+impl fmt::Display for PinId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Type state for a pin in input mode.
+#[derive(Debug)]
+pub struct Input<M>(marker::PhantomData<M>);
+
+/// Type state for a pin in output mode.
+#[derive(Debug)]
+pub struct Output<M>(marker::PhantomData<M>);
+
+/// Type state for an input pin without any pull-up or pull-down resistor.
+#[derive(Clone, Copy, Debug)]
+pub struct Floating;
+
+/// Type state for an input pin with a pull-up resistor.
+#[derive(Clone, Copy, Debug)]
+pub struct PullUp;
+
+/// Type state for an input pin with a pull-down resistor.
+#[derive(Clone, Copy, Debug)]
+pub struct PullDown;
+
+/// Type state for an output pin in push-pull mode.
+#[derive(Clone, Copy, Debug)]
+pub struct PushPull;
+
+/// Type state for an output pin in open drain mode.
+#[derive(Clone, Copy, Debug)]
+pub struct OpenDrain;
+
+/// Type state for a pin in analog input mode.
+#[derive(Clone, Copy, Debug)]
+pub struct Analog;
+
+/// Type state for a type-erased pin, whose mode is only known at runtime.
+#[derive(Debug)]
+struct Any;
+
+/// The mode that a simulated pin is currently configured in.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Mode {
+    FloatingInput,
+    PullUpInput,
+    PullDownInput,
+    PushPullOutput,
+    OpenDrainOutput,
+    AnalogInput,
+}
+
+impl Mode {
+    fn is_input(self) -> bool {
+        match self {
+            Mode::FloatingInput | Mode::PullUpInput | Mode::PullDownInput => true,
+            Mode::PushPullOutput | Mode::OpenDrainOutput | Mode::AnalogInput => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct PinState {
+    mode: Mode,
+    output: bool,
+    drive_strength: DriveStrength,
+    open_drain_flavor: OpenDrainFlavor,
+    external: Option<bool>,
+    /// The voltage that is applied from the outside in millivolts, if it is known exactly.
+    voltage: Option<u32>,
+    /// The waveform that is applied from the outside, if any.
+    waveform: Option<adc::Waveform>,
+    history: Vec<bool>,
+    changes: u64,
+    wakers: Vec<task::Waker>,
+}
+
+impl PinState {
+    fn new() -> Self {
+        let mode = Mode::FloatingInput;
+        let output = false;
+        let drive_strength = DriveStrength::Standard;
+        let open_drain_flavor = OpenDrainFlavor::DriveLow;
+        let external = None;
+        let voltage = None;
+        let waveform = None;
+        let history = Vec::new();
+        let changes = 0;
+        let wakers = Vec::new();
+        Self {
+            mode,
+            output,
+            drive_strength,
+            open_drain_flavor,
+            external,
+            voltage,
+            waveform,
+            history,
+            changes,
+            wakers,
+        }
+    }
+
+    fn level(&self) -> bool {
+        match self.mode {
+            Mode::FloatingInput | Mode::PullDownInput | Mode::AnalogInput => {
+                self.external.unwrap_or(false)
+            }
+            Mode::PullUpInput => self.external.unwrap_or(true),
+            Mode::PushPullOutput => self.output,
+            // An open drain output only ever drives one of the levels; when released the line is
+            // assumed to have an external pull resistor towards the other level.
+            Mode::OpenDrainOutput => match self.open_drain_flavor {
+                OpenDrainFlavor::DriveLow => self.output && self.external.unwrap_or(true),
+                OpenDrainFlavor::DriveHigh => self.output || self.external.unwrap_or(false),
+            },
+        }
+    }
+
+    /// The voltage on the pin, where levels are at the supply or ground voltage.
+    fn millivolts(&self) -> u32 {
+        match self.voltage {
+            Some(voltage) => voltage,
+            None if self.level() => SUPPLY_MILLIVOLTS,
+            None => 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Bank(sync::Arc<sync::Mutex<Vec<PinState>>>);
+
+impl Bank {
+    pub(crate) fn new() -> Self {
+        Bank(sync::Arc::new(sync::Mutex::new(
+            (0..PIN_COUNT).map(|_| PinState::new()).collect(),
+        )))
+    }
+
+    /// Runs the specified function on the state of a pin, and wakes anyone waiting for the pin if
+    /// its level changed.
+    fn with<F, R>(&self, id: PinId, f: F) -> R
+    where
+        F: FnOnce(&mut PinState) -> R,
+    {
+        let (result, wakers) = {
+            let mut pins = self.0.lock().unwrap();
+            let state = &mut pins[usize::from(id.0)];
+            let before = state.level();
+            let result = f(state);
+            let wakers = if state.level() != before {
+                state.changes += 1;
+                mem::take(&mut state.wakers)
+            } else {
+                Vec::new()
+            };
+            (result, wakers)
+        };
+
+        for waker in wakers {
+            waker.wake();
+        }
+        result
+    }
+}
+
+/// A simulated pin on the simulated board.
+pub struct Pin<M> {
+    id: PinId,
+    bank: Bank,
+    /// The number of level changes, and the level, when the pin last observed a change.
+    listening: Option<(u64, bool)>,
+    mode: marker::PhantomData<M>,
+}
+
+impl<M> Pin<M> {
+    fn new(id: PinId, bank: Bank) -> Self {
+        let listening = None;
+        let mode = marker::PhantomData;
+        Self {
+            id,
+            bank,
+            listening,
+            mode,
+        }
+    }
+
+    fn into_mode<N>(self, mode: Mode, output: bool) -> Pin<N> {
+        self.configure(mode, output);
+        Pin::new(self.id, self.bank)
+    }
+
+    /// Re-configures the simulated pin without changing its type state, for peripherals that take
+    /// over a pin.
+    ///
+    /// This resets the drive strength and the open drain flavor of the pin.
+    pub(crate) fn configure(&self, mode: Mode, output: bool) {
+        self.bank.with(self.id, |state| {
+            state.mode = mode;
+            state.output = output;
+            state.drive_strength = DriveStrength::Standard;
+            state.open_drain_flavor = OpenDrainFlavor::DriveLow;
+        });
+    }
+
+    fn set_drive_strength(&self, strength: DriveStrength) {
+        self.bank
+            .with(self.id, |state| state.drive_strength = strength)
+    }
+
+    fn set_open_drain_flavor(&self, flavor: OpenDrainFlavor) {
+        self.bank
+            .with(self.id, |state| state.open_drain_flavor = flavor)
+    }
+
+    fn listen(&mut self) {
+        let listening = self
+            .bank
+            .with(self.id, |state| (state.changes, state.level()));
+        self.listening = Some(listening);
+    }
+
+    fn poll_level_change(&mut self, cx: &mut task::Context<'_>) -> task::Poll<bool> {
+        if self.listening.is_none() {
+            self.listen();
+        }
+
+        let (seen, level) = self.listening.unwrap();
+        let changes = self.bank.with(self.id, |state| {
+            if state.changes == seen {
+                state.wakers.push(cx.waker().clone());
+            }
+            state.changes
+        });
+
+        if changes == seen {
+            task::Poll::Pending
+        } else {
+            // Levels alternate, so every change can be reported in order even if several happened
+            // since the last poll.
+            self.listening = Some((seen + 1, !level));
+            task::Poll::Ready(!level)
+        }
+    }
+
+    /// The physical pin that this pin is bound to.
+    pub fn id(&self) -> PinId {
+        self.id
+    }
+
+    /// Turns this pin into an analog input.
+    pub(crate) fn into_analog(self) -> Pin<Analog> {
+        self.into_mode(Mode::AnalogInput, false)
+    }
+
+    /// Turns this pin into a type-erased pin, keeping its current mode.
+    pub fn degrade(self) -> AnyPin {
+        AnyPin(Pin::new(self.id, self.bank))
+    }
+}
+
+/// A simulated pin whose mode is only known at runtime, so that pins can be stored together.
+///
+/// Reading the pin while it is an output, or writing it while it is an input, fails with
+/// [`Error::WrongMode`](error::Error::WrongMode).
+#[derive(Debug)]
+pub struct AnyPin(Pin<Any>);
+
+impl AnyPin {
+    /// The physical pin that this pin is bound to.
+    pub fn id(&self) -> PinId {
+        self.0.id
+    }
+
+    /// The mode that this pin is currently configured in.
+    pub fn mode(&self) -> Mode {
+        self.0.bank.with(self.0.id, |state| state.mode)
+    }
+
+    /// Analog pins are neither digital inputs nor digital outputs.
+    fn check_mode(&self, input: bool) -> Result<(), error::Error> {
+        if self.mode() != Mode::AnalogInput && self.mode().is_input() == input {
+            Ok(())
+        } else {
+            Err(error::Error::WrongMode)
+        }
+    }
+
+    fn into_mode(self, mode: Mode, output: bool) -> Self {
+        AnyPin(self.0.into_mode(mode, output))
+    }
+}
+
+/// The pins of the simulated board that have not yet been taken by the application, and the roles
+/// that the other pins were taken as.
+#[derive(Debug)]
+pub(crate) struct Pins {
+    pins: Vec<Option<Pin<Input<Floating>>>>,
+    claims: Vec<Option<feather::Role>>,
+}
+
+impl Pins {
+    pub(crate) fn new(bank: &Bank) -> Self {
+        let pins = (0..PIN_COUNT as u8)
+            .map(|id| Some(Pin::new(PinId(id), bank.clone())))
+            .collect();
+        let claims = vec![None; PIN_COUNT];
+        Self { pins, claims }
+    }
+
+    /// Checks that the specified pin has not been taken yet.
+    pub(crate) fn check(&self, id: PinId) -> Result<(), feather::PinInUse<PinId>> {
+        match self.claims[usize::from(id.0)] {
+            Some(role) => Err(feather::PinInUse { pin: id, role }),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn try_take(
+        &mut self,
+        id: PinId,
+        role: feather::Role,
+    ) -> Result<Pin<Input<Floating>>, feather::PinInUse<PinId>> {
+        self.check(id)?;
+        self.claims[usize::from(id.0)] = Some(role);
+        Ok(self.pins[usize::from(id.0)]
+            .take()
+            .expect("unclaimed pins are available"))
+    }
+
+    /// Puts a pin back into the slot of the specified pin, so that it can be taken again in any
+    /// role.
+    ///
+    /// Fails with [`error::Error::WrongPin`] if the pin is a different one, which leaves both
+    /// slots unchanged.
+    pub(crate) fn release(
+        &mut self,
+        id: PinId,
+        pin: Pin<Input<Floating>>,
+    ) -> Result<(), error::Error> {
+        if pin.id != id {
+            return Err(error::Error::WrongPin);
+        }
+        let index = usize::from(id.0);
+        self.claims[index] = None;
+        self.pins[index] = Some(pin);
+        Ok(())
+    }
+
+    /// The pins that have been taken so far, and the roles that they were taken as.
+    pub(crate) fn claims(&self) -> impl Iterator<Item = (PinId, feather::Role)> + '_ {
+        self.claims
+            .iter()
+            .enumerate()
+            .filter_map(|(id, role)| role.map(|role| (PinId(id as u8), role)))
+    }
+}
+
+/// A handle for inspecting and driving the pins of a simulated board from the outside.
+#[derive(Clone, Debug)]
+pub struct Probe {
+    bank: Bank,
+}
+
+impl Probe {
+    pub(crate) fn new(bank: Bank) -> Self {
+        Self { bank }
+    }
+
+    /// The mode that the specified pin is currently configured in.
+    pub fn mode(&self, id: PinId) -> Mode {
+        self.bank.with(id, |state| state.mode)
+    }
+
+    /// The level that can currently be observed on the specified pin.
+    ///
+    /// This takes both the configuration of the pin and any external drivers into account.
+    pub fn level(&self, id: PinId) -> bool {
+        self.bank.with(id, |state| state.level())
+    }
+
+    /// The strength that the specified pin drives its levels with, if it is an output.
+    pub fn drive_strength(&self, id: PinId) -> DriveStrength {
+        self.bank.with(id, |state| state.drive_strength)
+    }
+
+    /// The level that the specified pin actively drives, if it is an open drain output.
+    pub fn open_drain_flavor(&self, id: PinId) -> OpenDrainFlavor {
+        self.bank.with(id, |state| state.open_drain_flavor)
+    }
+
+    /// All of the levels that the application has written to the specified pin, in order.
+    pub fn history(&self, id: PinId) -> Vec<bool> {
+        self.bank.with(id, |state| state.history.clone())
+    }
+
+    /// Forgets all of the levels that the application has written to the specified pin so far.
+    pub fn clear_history(&self, id: PinId) {
+        self.bank.with(id, |state| state.history.clear())
+    }
+
+    /// Drives the specified pin to a high or low level from the outside.
+    pub fn drive(&self, id: PinId, high: bool) {
+        self.bank.with(id, |state| {
+            state.external = Some(high);
+            state.voltage = None;
+            state.waveform = None;
+        })
+    }
+
+    /// Applies a voltage in millivolts to the specified pin from the outside, e.g. for analog
+    /// inputs.
+    ///
+    /// Digital inputs read the voltage as high if it is at least half of the supply voltage.
+    pub fn apply_voltage(&self, id: PinId, millivolts: u32) {
+        self.bank.with(id, |state| {
+            state.external = Some(millivolts >= SUPPLY_MILLIVOLTS / 2);
+            state.voltage = Some(millivolts);
+            state.waveform = None;
+        })
+    }
+
+    /// Applies a synthetic waveform to the specified pin from the outside.
+    ///
+    /// Only continuous sampling follows the waveform, since it knows when each of its samples is
+    /// taken.  Single readings and digital inputs see the pin as disconnected.
+    pub fn apply_waveform(&self, id: PinId, waveform: adc::Waveform) {
+        self.bank.with(id, |state| {
+            state.external = None;
+            state.voltage = None;
+            state.waveform = Some(waveform);
+        })
+    }
+
+    /// Stops driving the specified pin from the outside, leaving it to any pull resistors.
+    pub fn disconnect(&self, id: PinId) {
+        self.bank.with(id, |state| {
+            state.external = None;
+            state.voltage = None;
+            state.waveform = None;
+        })
+    }
+}
+
+impl Pin<Analog> {
+    /// The voltage on this pin, in millivolts.
+    pub(crate) fn millivolts(&self) -> u32 {
+        self.bank.with(self.id, |state| state.millivolts())
+    }
+
+    /// The voltage on this pin at the specified time, in millivolts, following any waveform that is
+    /// applied to it.
+    pub(crate) fn millivolts_at(&self, time: embedded_platform::time::Instant) -> u32 {
+        self.bank.with(self.id, |state| match state.waveform {
+            Some(waveform) => waveform.millivolts_at(time),
+            None => state.millivolts(),
+        })
+    }
+}
+
+impl<M> embedded_platform::gpio::Pin for Pin<M> {
+    type Error = error::Error;
+}
+
+impl<S> embedded_platform::gpio::InputPin for Pin<Input<S>> {
+    fn poll_get(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>> {
+        task::Poll::Ready(Ok(self.bank.with(self.id, |state| state.level())))
+    }
+}
+
+impl<S> embedded_platform::gpio::WaitInputPin for Pin<Input<S>>
+where
+    S: Unpin,
+{
+    fn poll_listen(
+        mut self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        self.listen();
+        task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_change(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>> {
+        self.poll_level_change(cx).map(Ok)
+    }
+}
+
+impl<S> embedded_platform::gpio::OutputPin for Pin<Output<S>> {
+    fn poll_set(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+        high: bool,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        self.bank.with(self.id, |state| {
+            state.output = high;
+            state.history.push(high);
+        });
+        task::Poll::Ready(Ok(()))
+    }
+}
+
+impl<S> embedded_platform::gpio::StatefulOutputPin for Pin<Output<S>> {
+    fn poll_is_set_high(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>> {
+        task::Poll::Ready(Ok(self.bank.with(self.id, |state| state.output)))
+    }
+}
+
+impl<S> embedded_platform::gpio::ConfigureDriveStrength for Pin<Output<S>> {
+    fn set_drive_strength(&mut self, strength: DriveStrength) -> Result<(), Self::Error> {
+        Pin::set_drive_strength(self, strength);
+        Ok(())
+    }
+}
+
+impl embedded_platform::gpio::ConfigureOpenDrain for Pin<Output<OpenDrain>> {
+    fn set_open_drain_flavor(&mut self, flavor: OpenDrainFlavor) -> Result<(), Self::Error> {
+        Pin::set_open_drain_flavor(self, flavor);
+        Ok(())
+    }
+}
+
+impl<M> embedded_platform::gpio::IntoFloatingInputPin for Pin<M> {
+    type FloatingInputPin = Pin<Input<Floating>>;
+
+    fn into_floating_input_pin(self) -> Result<Self::FloatingInputPin, Self::Error> {
+        Ok(self.into_mode(Mode::FloatingInput, false))
+    }
+}
+
+impl<M> embedded_platform::gpio::IntoPullUpInputPin for Pin<M> {
+    type PullUpInputPin = Pin<Input<PullUp>>;
+
+    fn into_pull_up_input_pin(self) -> Result<Self::PullUpInputPin, Self::Error> {
+        Ok(self.into_mode(Mode::PullUpInput, false))
+    }
+}
+
+impl<M> embedded_platform::gpio::IntoPullDownInputPin for Pin<M> {
+    type PullDownInputPin = Pin<Input<PullDown>>;
+
+    fn into_pull_down_input_pin(self) -> Result<Self::PullDownInputPin, Self::Error> {
+        Ok(self.into_mode(Mode::PullDownInput, false))
+    }
+}
+
+impl<M> embedded_platform::gpio::IntoPushPullOutputPin for Pin<M> {
+    type PushPullOutputPin = Pin<Output<PushPull>>;
+
+    fn into_push_pull_output_pin(
+        self,
+        initial_high: bool,
+    ) -> Result<Self::PushPullOutputPin, Self::Error> {
+        Ok(self.into_mode(Mode::PushPullOutput, initial_high))
+    }
+}
+
+impl<M> embedded_platform::gpio::IntoOpenDrainOutputPin for Pin<M> {
+    type OpenDrainOutputPin = Pin<Output<OpenDrain>>;
+
+    fn into_open_drain_output_pin(
+        self,
+        initial_high: bool,
+    ) -> Result<Self::OpenDrainOutputPin, Self::Error> {
+        Ok(self.into_mode(Mode::OpenDrainOutput, initial_high))
+    }
+}
+
+impl<M> embedded_platform::gpio::Degrade for Pin<M> {
+    type AnyPin = AnyPin;
+
+    fn degrade(self) -> Self::AnyPin {
+        Pin::degrade(self)
+    }
+}
+
+impl embedded_platform::gpio::Pin for AnyPin {
+    type Error = error::Error;
+}
+
+impl embedded_platform::gpio::InputPin for AnyPin {
+    fn poll_get(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>> {
+        self.check_mode(true)?;
+        task::Poll::Ready(Ok(self.0.bank.with(self.0.id, |state| state.level())))
+    }
+}
+
+impl embedded_platform::gpio::WaitInputPin for AnyPin {
+    fn poll_listen(
+        mut self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        self.check_mode(true)?;
+        self.0.listen();
+        task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_change(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>> {
+        self.check_mode(true)?;
+        self.0.poll_level_change(cx).map(Ok)
+    }
+}
+
+impl embedded_platform::gpio::OutputPin for AnyPin {
+    fn poll_set(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+        high: bool,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        self.check_mode(false)?;
+        self.0.bank.with(self.0.id, |state| {
+            state.output = high;
+            state.history.push(high);
+        });
+        task::Poll::Ready(Ok(()))
+    }
+}
+
+impl embedded_platform::gpio::StatefulOutputPin for AnyPin {
+    fn poll_is_set_high(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>> {
+        self.check_mode(false)?;
+        task::Poll::Ready(Ok(self.0.bank.with(self.0.id, |state| state.output)))
+    }
+}
+
+impl embedded_platform::gpio::ConfigureDriveStrength for AnyPin {
+    fn set_drive_strength(&mut self, strength: DriveStrength) -> Result<(), Self::Error> {
+        self.check_mode(false)?;
+        self.0.set_drive_strength(strength);
+        Ok(())
+    }
+}
+
+impl embedded_platform::gpio::ConfigureOpenDrain for AnyPin {
+    fn set_open_drain_flavor(&mut self, flavor: OpenDrainFlavor) -> Result<(), Self::Error> {
+        if self.mode() != Mode::OpenDrainOutput {
+            return Err(error::Error::WrongMode);
+        }
+        self.0.set_open_drain_flavor(flavor);
+        Ok(())
+    }
+}
+
+impl embedded_platform::gpio::IntoFloatingInputPin for AnyPin {
+    type FloatingInputPin = Self;
+
+    fn into_floating_input_pin(self) -> Result<Self::FloatingInputPin, Self::Error> {
+        Ok(self.into_mode(Mode::FloatingInput, false))
+    }
+}
+
+impl embedded_platform::gpio::IntoPullUpInputPin for AnyPin {
+    type PullUpInputPin = Self;
+
+    fn into_pull_up_input_pin(self) -> Result<Self::PullUpInputPin, Self::Error> {
+        Ok(self.into_mode(Mode::PullUpInput, false))
+    }
+}
+
+impl embedded_platform::gpio::IntoPullDownInputPin for AnyPin {
+    type PullDownInputPin = Self;
+
+    fn into_pull_down_input_pin(self) -> Result<Self::PullDownInputPin, Self::Error> {
+        Ok(self.into_mode(Mode::PullDownInput, false))
+    }
+}
+
+impl embedded_platform::gpio::IntoPushPullOutputPin for AnyPin {
+    type PushPullOutputPin = Self;
+
+    fn into_push_pull_output_pin(
+        self,
+        initial_high: bool,
+    ) -> Result<Self::PushPullOutputPin, Self::Error> {
+        Ok(self.into_mode(Mode::PushPullOutput, initial_high))
+    }
+}
+
+impl embedded_platform::gpio::IntoOpenDrainOutputPin for AnyPin {
+    type OpenDrainOutputPin = Self;
+
+    fn into_open_drain_output_pin(
+        self,
+        initial_high: bool,
+    ) -> Result<Self::OpenDrainOutputPin, Self::Error> {
+        Ok(self.into_mode(Mode::OpenDrainOutput, initial_high))
+    }
+}
+
+impl embedded_platform::gpio::Degrade for AnyPin {
+    type AnyPin = Self;
+
+    fn degrade(self) -> Self::AnyPin {
+        self
+    }
+}
+
+impl<M> fmt::Debug for Pin<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (mode, level) = self.bank.with(self.id, |state| (state.mode, state.level()));
+        f.debug_struct("Pin")
+            .field("id", &self.id)
+            .field("mode", &mode)
+            .field("level", &level)
+            .finish()
+    }
+}
@@ -0,0 +1,214 @@
+//! A simulated I²C bus.
+//!
+//! Devices are attached to the bus through a [`Probe`].  Data written by the application ends up in
+//! the log of the addressed device, while reads consume data that was queued up for that device.
+use crate::error;
+use crate::gpio;
+use core::pin;
+use core::task;
+use std::collections;
+use std::sync;
+
+#[derive(Debug, Default)]
+struct Device {
+    written: Vec<u8>,
+    readable: collections::VecDeque<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Bus(sync::Arc<sync::Mutex<collections::BTreeMap<u8, Device>>>);
+
+impl Bus {
+    pub(crate) fn new() -> Self {
+        Bus(sync::Arc::new(sync::Mutex::new(
+            collections::BTreeMap::new(),
+        )))
+    }
+
+    fn with<F, R>(&self, address: u8, f: F) -> Result<R, error::Error>
+    where
+        F: FnOnce(&mut Device) -> R,
+    {
+        let mut devices = self.0.lock().unwrap();
+        let device = devices
+            .get_mut(&address)
+            .ok_or(error::Error::Nack(address))?;
+        Ok(f(device))
+    }
+}
+
+#[derive(Debug)]
+pub struct I2c {
+    bus: Bus,
+}
+
+#[derive(Debug)]
+pub struct I2cRead {
+    bus: Bus,
+    address: u8,
+}
+
+#[derive(Debug)]
+pub struct I2cWrite {
+    bus: Bus,
+    address: u8,
+}
+
+#[derive(Debug)]
+pub struct I2cMapping {
+    bus: Bus,
+}
+
+impl I2cMapping {
+    pub(crate) fn new(bus: Bus) -> Self {
+        Self { bus }
+    }
+}
+
+impl
+    embedded_platform::i2c::I2cBusMapping<
+        gpio::Pin<gpio::Input<gpio::Floating>>,
+        gpio::Pin<gpio::Input<gpio::Floating>>,
+    > for I2cMapping
+{
+    type Error = error::Error;
+    type Bus = I2c;
+
+    fn poll_initialize(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+        sda: &mut gpio::Pin<gpio::Input<gpio::Floating>>,
+        scl: &mut gpio::Pin<gpio::Input<gpio::Floating>>,
+    ) -> task::Poll<Result<Self::Bus, Self::Error>>
+    where
+        Self: Sized,
+    {
+        task::Poll::Ready(Ok(I2c::new(self.bus.clone(), sda, scl)))
+    }
+}
+
+impl I2c {
+    pub(crate) fn new<SDA, SCL>(bus: Bus, sda: &gpio::Pin<SDA>, scl: &gpio::Pin<SCL>) -> Self {
+        sda.configure(gpio::Mode::OpenDrainOutput, true);
+        scl.configure(gpio::Mode::OpenDrainOutput, true);
+        Self { bus }
+    }
+}
+
+impl embedded_platform::i2c::I2cRead for I2c {
+    type Error = error::Error;
+    type Read = I2cRead;
+
+    fn poll_begin_read(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+        address: u8,
+    ) -> task::Poll<Result<Self::Read, Self::Error>> {
+        let bus = self.bus.clone();
+        task::Poll::Ready(bus.with(address, |_| ()).map(|()| I2cRead { bus, address }))
+    }
+}
+
+impl embedded_platform::io::Read for I2cRead {
+    type Error = error::Error;
+
+    fn poll_read(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+        buffer: &mut [u8],
+    ) -> task::Poll<Result<usize, Self::Error>> {
+        task::Poll::Ready(self.bus.with(self.address, |device| {
+            let len = buffer.len().min(device.readable.len());
+            for (dest, byte) in buffer.iter_mut().zip(device.readable.drain(..len)) {
+                *dest = byte;
+            }
+            len
+        }))
+    }
+}
+
+impl embedded_platform::i2c::I2cWrite for I2c {
+    type Error = error::Error;
+    type Write = I2cWrite;
+
+    fn poll_begin_write(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+        address: u8,
+    ) -> task::Poll<Result<Self::Write, Self::Error>> {
+        let bus = self.bus.clone();
+        task::Poll::Ready(
+            bus.with(address, |_| ())
+                .map(|()| I2cWrite { bus, address }),
+        )
+    }
+}
+
+impl embedded_platform::io::Write for I2cWrite {
+    type Error = error::Error;
+
+    fn poll_write(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+        bytes: &[u8],
+    ) -> task::Poll<Result<usize, Self::Error>> {
+        task::Poll::Ready(self.bus.with(self.address, |device| {
+            device.written.extend_from_slice(bytes);
+            bytes.len()
+        }))
+    }
+
+    fn poll_flush(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        task::Poll::Ready(Ok(()))
+    }
+}
+
+/// A handle for attaching simulated devices to the I²C bus and inspecting the traffic they see.
+#[derive(Clone, Debug)]
+pub struct Probe {
+    bus: Bus,
+}
+
+impl Probe {
+    pub(crate) fn new(bus: Bus) -> Self {
+        Self { bus }
+    }
+
+    /// Attaches a device to the bus, so that it will acknowledge the specified address.
+    pub fn attach(&self, address: u8) {
+        self.bus.0.lock().unwrap().entry(address).or_default();
+    }
+
+    /// Detaches a device from the bus, discarding any data that it has seen or queued up.
+    pub fn detach(&self, address: u8) {
+        self.bus.0.lock().unwrap().remove(&address);
+    }
+
+    /// Queues up data to be returned when the application reads from the specified address.
+    ///
+    /// Panics if no device is attached at the address.
+    pub fn queue_read(&self, address: u8, bytes: &[u8]) {
+        self.bus
+            .with(address, |device| device.readable.extend(bytes))
+            .unwrap_or_else(|_| panic!("no I²C device attached at address {:#04x}", address))
+    }
+
+    /// Takes all of the data that the application has written to the specified address so far.
+    ///
+    /// Panics if no device is attached at the address.
+    pub fn take_written(&self, address: u8) -> Vec<u8> {
+        self.bus
+            .with(address, |device| device.written.split_off(0))
+            .unwrap_or_else(|_| panic!("no I²C device attached at address {:#04x}", address))
+    }
+}
@@ -0,0 +1,600 @@
+//! # Simulated platform
+//!
+//! This crate provides [`Simulator`], a platform that runs on the host instead of on a device.  It
+//! implements both [`Platform`](embedded_platform::Platform) and the
+//! [Feather spec](embedded_platform::specs::feather::Feather), so that generic application code
+//! can be run on a development machine or in CI without a board attached.
+//!
+//! All of the simulated peripherals are backed by shared state.  Each peripheral module has a
+//! `Probe` type that can be obtained from the [`Simulator`] before handing it to the application,
+//! and that lets tests inspect what the application did and drive the peripherals from the
+//! outside.  For example, [`gpio::Probe::history`] returns the sequence of levels written to a pin.
+#![deny(
+    // missing_docs,
+    missing_debug_implementations,
+    missing_copy_implementations,
+    trivial_casts,
+    trivial_numeric_casts,
+    unstable_features,
+    unused_import_braces,
+    unused_qualifications,
+    clippy::all
+)]
+#![forbid(unsafe_code)]
+
+use core::cell;
+use core::executor;
+use core::future;
+use core::task;
+use embedded_platform::platform;
+use embedded_platform::platform::policy;
+use embedded_platform::specs::feather;
+use std::process;
+
+pub mod adc;
+pub mod clock;
+pub mod error;
+pub mod gpio;
+pub mod i2c;
+pub mod spi;
+pub mod timer;
+
+/// The number of tasks that applications can spawn.
+pub const MAX_TASKS: usize = 32;
+/// The maximum size in bytes of a spawned task.
+pub const MAX_TASK_SIZE: usize = 16 * 1024;
+
+static EXECUTOR: executor::Executor<MAX_TASKS, MAX_TASK_SIZE> = executor::Executor::new();
+
+#[derive(Debug)]
+pub struct Simulator {
+    bank: gpio::Bank,
+    pins: gpio::Pins,
+    i2c: i2c::Bus,
+    spi: spi::Bus,
+    clock: clock::VirtualClock,
+    timers: timer::Timers,
+}
+
+impl Simulator {
+    /// Creates a new simulated board, with all of its peripherals in their reset state.
+    pub fn new() -> Self {
+        let bank = gpio::Bank::new();
+        let pins = gpio::Pins::new(&bank);
+        let i2c = i2c::Bus::new();
+        let spi = spi::Bus::new();
+        let clock = clock::VirtualClock::new();
+        let timers = timer::Timers::new(&clock);
+
+        Self {
+            bank,
+            pins,
+            i2c,
+            spi,
+            clock,
+            timers,
+        }
+    }
+
+    /// Creates a handle for inspecting and driving the GPIO pins of this board.
+    pub fn gpio_probe(&self) -> gpio::Probe {
+        gpio::Probe::new(self.bank.clone())
+    }
+
+    /// Creates a handle for attaching devices to the main I²C bus of this board.
+    pub fn i2c_probe(&self) -> i2c::Probe {
+        i2c::Probe::new(self.i2c.clone())
+    }
+
+    /// Creates a handle for inspecting the traffic on the main SPI bus of this board.
+    pub fn spi_probe(&self) -> spi::Probe {
+        spi::Probe::new(self.spi.clone())
+    }
+
+    /// Creates a handle for inspecting one of the timers of this board.
+    ///
+    /// Panics if there is no timer with the specified index.
+    pub fn timer_probe(&self, index: usize) -> timer::Probe {
+        self.timers.probes[index].clone()
+    }
+
+    /// Creates a mapping that can be used to initialize the main I²C bus from the SDA and SCL pins.
+    pub fn main_i2c_mapping(&self) -> i2c::I2cMapping {
+        i2c::I2cMapping::new(self.i2c.clone())
+    }
+
+    /// Takes the main SPI bus, failing if its `SCK`, `MOSI` or `MISO` pin was already taken.
+    pub fn try_take_main_spi(&mut self) -> Result<spi::Spi, feather::PinInUse<gpio::PinId>> {
+        self.pins.check(gpio::SCK)?;
+        self.pins.check(gpio::MOSI)?;
+        self.pins.check(gpio::MISO)?;
+        let sck = self.pins.try_take(gpio::SCK, feather::Role::Sck)?;
+        let mosi = self.pins.try_take(gpio::MOSI, feather::Role::Mosi)?;
+        let miso = self.pins.try_take(gpio::MISO, feather::Role::Miso)?;
+        Ok(spi::Spi::new(self.spi.clone(), &sck, &mosi, &miso))
+    }
+
+    pub fn take_main_spi(&mut self) -> spi::Spi {
+        self.try_take_main_spi()
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// The pins that the application has taken so far, and the roles that they were taken as.
+    pub fn claimed_pins(&self) -> impl Iterator<Item = (gpio::PinId, feather::Role)> + '_ {
+        self.pins.claims()
+    }
+
+    pub fn take_timer0(&mut self) -> timer::Timer {
+        self.timers.timer0.take().expect("timer 0 is already taken")
+    }
+
+    /// Puts timer 0 back, so that it can be taken again, stopping it if it is running.
+    ///
+    /// Panics if the timer is a different one.
+    pub fn release_timer0(&mut self, timer: timer::Timer) {
+        self.timers.release(0, timer)
+    }
+
+    pub fn take_timer1(&mut self) -> timer::Timer {
+        self.timers.timer1.take().expect("timer 1 is already taken")
+    }
+
+    /// Puts timer 1 back, so that it can be taken again, stopping it if it is running.
+    ///
+    /// Panics if the timer is a different one.
+    pub fn release_timer1(&mut self, timer: timer::Timer) {
+        self.timers.release(1, timer)
+    }
+
+    pub fn take_timer2(&mut self) -> timer::Timer {
+        self.timers.timer2.take().expect("timer 2 is already taken")
+    }
+
+    /// Puts timer 2 back, so that it can be taken again, stopping it if it is running.
+    ///
+    /// Panics if the timer is a different one.
+    pub fn release_timer2(&mut self, timer: timer::Timer) {
+        self.timers.release(2, timer)
+    }
+
+    pub fn take_timer3(&mut self) -> timer::Timer {
+        self.timers.timer3.take().expect("timer 3 is already taken")
+    }
+
+    /// Puts timer 3 back, so that it can be taken again, stopping it if it is running.
+    ///
+    /// Panics if the timer is a different one.
+    pub fn release_timer3(&mut self, timer: timer::Timer) {
+        self.timers.release(3, timer)
+    }
+}
+
+impl Default for Simulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl platform::Platform for Simulator {
+    type Error = error::Error;
+    type Clock = clock::VirtualClock;
+
+    /// Runs the application on the host.
+    ///
+    /// Whenever no task is ready, virtual time skips ahead to the next timer deadline.  The process
+    /// exits with status 0 once the application has completed and no spawned task can make
+    /// progress any more.  A halt outcome exits with status 1 and a reset outcome with status 2,
+    /// since the host can't be reset.  The backoff delay before a retry skips ahead the virtual time
+    /// of the failed attempt.
+    fn main<I, F>(policy: policy::Policy<Self::Error>, mut run: I) -> !
+    where
+        I: FnMut(Self, executor::Spawner) -> F,
+        F: future::Future<Output = Result<(), Self::Error>>,
+    {
+        let clock = cell::RefCell::new(None);
+        let run = |platform: Self, spawner| {
+            clock.replace(Some(platform::Platform::clock(&platform)));
+            run(platform, spawner)
+        };
+        let delay = |backoff| {
+            if let Some(clock) = clock.borrow().as_ref() {
+                clock.advance(backoff);
+            }
+            future::ready(())
+        };
+        let main = |spawner| policy::supervise(policy, spawner, run, delay);
+        let wait = || {
+            if !advance_clock(&clock) {
+                panic!("the application is waiting, but no timer is running");
+            }
+        };
+
+        match EXECUTOR.block_on(main, wait) {
+            policy::Outcome::Completed => {
+                EXECUTOR.run_until_stalled();
+                while advance_clock(&clock) {
+                    EXECUTOR.run_until_stalled();
+                }
+                process::exit(0)
+            }
+            policy::Outcome::Reset => {
+                eprintln!("the application requested a system reset");
+                process::exit(2)
+            }
+            policy::Outcome::Halt(stage) => {
+                eprintln!("the application halted after failing to {}", stage);
+                process::exit(1)
+            }
+        }
+    }
+
+    fn poll_initialize(_cx: &mut task::Context<'_>) -> task::Poll<Result<Self, Self::Error>> {
+        task::Poll::Ready(Ok(Self::new()))
+    }
+
+    /// Returns a handle to the virtual clock that drives the timers of this board.
+    fn clock(&self) -> Self::Clock {
+        self.clock.clone()
+    }
+}
+
+fn advance_clock(clock: &cell::RefCell<Option<clock::VirtualClock>>) -> bool {
+    clock
+        .borrow()
+        .as_ref()
+        .is_some_and(clock::VirtualClock::advance_to_next_deadline)
+}
+
+impl feather::Feather for Simulator {
+    type PinId = gpio::PinId;
+    /// The simulated main LED is wired active-low, like on many Feather boards, so that
+    /// applications are exercised against an inverted pin.  The GPIO probe observes the physical
+    /// level of the pin, i.e. low while the LED is on.
+    type MainLed = embedded_platform::gpio::Inverted<gpio::Pin<gpio::Input<gpio::Floating>>>;
+    type MainI2cMapping = i2c::I2cMapping;
+
+    type SDA = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type SCL = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type D2 = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type D3 = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type D4 = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type D5 = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type D6 = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type D7 = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type D8 = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type P0 = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type TX = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type RX = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type MISO = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type MOSI = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type SCK = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type A5 = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type A4 = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type A3 = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type A2 = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type A1 = gpio::Pin<gpio::Input<gpio::Floating>>;
+    type A0 = gpio::Pin<gpio::Input<gpio::Floating>>;
+
+    fn try_take_main_led(&mut self) -> Result<Self::MainLed, feather::PinInUse<Self::PinId>> {
+        self.pins
+            .try_take(gpio::MAIN_LED, feather::Role::MainLed)
+            .map(embedded_platform::gpio::Inverted::new)
+    }
+
+    fn try_take_main_i2c(
+        &mut self,
+    ) -> Result<feather::MainI2cBus<Self>, feather::PinInUse<Self::PinId>> {
+        self.pins.check(gpio::SDA)?;
+        self.pins.check(gpio::SCL)?;
+        let sda = self.pins.try_take(gpio::SDA, feather::Role::Sda)?;
+        let scl = self.pins.try_take(gpio::SCL, feather::Role::Scl)?;
+        Ok(i2c::I2c::new(self.i2c.clone(), &sda, &scl))
+    }
+
+    fn try_take_sda(&mut self) -> Result<Self::SDA, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::SDA, feather::Role::Sda)
+    }
+
+    fn try_take_scl(&mut self) -> Result<Self::SCL, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::SCL, feather::Role::Scl)
+    }
+
+    fn try_take_d2(&mut self) -> Result<Self::D2, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::D2, feather::Role::D2)
+    }
+
+    fn try_take_d3(&mut self) -> Result<Self::D3, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::D3, feather::Role::D3)
+    }
+
+    fn try_take_d4(&mut self) -> Result<Self::D4, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::D4, feather::Role::D4)
+    }
+
+    fn try_take_d5(&mut self) -> Result<Self::D5, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::D5, feather::Role::D5)
+    }
+
+    fn try_take_d6(&mut self) -> Result<Self::D6, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::D6, feather::Role::D6)
+    }
+
+    fn try_take_d7(&mut self) -> Result<Self::D7, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::D7, feather::Role::D7)
+    }
+
+    fn try_take_d8(&mut self) -> Result<Self::D8, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::D8, feather::Role::D8)
+    }
+
+    fn try_take_p0(&mut self) -> Result<Self::P0, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::P0, feather::Role::P0)
+    }
+
+    fn try_take_tx(&mut self) -> Result<Self::TX, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::TX, feather::Role::Tx)
+    }
+
+    fn try_take_rx(&mut self) -> Result<Self::RX, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::RX, feather::Role::Rx)
+    }
+
+    fn try_take_miso(&mut self) -> Result<Self::MISO, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::MISO, feather::Role::Miso)
+    }
+
+    fn try_take_mosi(&mut self) -> Result<Self::MOSI, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::MOSI, feather::Role::Mosi)
+    }
+
+    fn try_take_sck(&mut self) -> Result<Self::SCK, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::SCK, feather::Role::Sck)
+    }
+
+    fn try_take_a5(&mut self) -> Result<Self::A5, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::A5, feather::Role::A5)
+    }
+
+    fn try_take_a4(&mut self) -> Result<Self::A4, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::A4, feather::Role::A4)
+    }
+
+    fn try_take_a3(&mut self) -> Result<Self::A3, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::A3, feather::Role::A3)
+    }
+
+    fn try_take_a2(&mut self) -> Result<Self::A2, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::A2, feather::Role::A2)
+    }
+
+    fn try_take_a1(&mut self) -> Result<Self::A1, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::A1, feather::Role::A1)
+    }
+
+    fn try_take_a0(&mut self) -> Result<Self::A0, feather::PinInUse<Self::PinId>> {
+        self.pins.try_take(gpio::A0, feather::Role::A0)
+    }
+
+    fn release_main_led<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::MainLed,
+            Error = Self::Error,
+        >,
+    {
+        self.pins
+            .release(gpio::MAIN_LED, pin.into_floating_input_pin()?.into_inner())
+    }
+
+    fn release_sda<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::SDA,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::SDA, pin.into_floating_input_pin()?)
+    }
+
+    fn release_scl<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::SCL,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::SCL, pin.into_floating_input_pin()?)
+    }
+
+    fn release_d2<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::D2,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::D2, pin.into_floating_input_pin()?)
+    }
+
+    fn release_d3<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::D3,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::D3, pin.into_floating_input_pin()?)
+    }
+
+    fn release_d4<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::D4,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::D4, pin.into_floating_input_pin()?)
+    }
+
+    fn release_d5<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::D5,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::D5, pin.into_floating_input_pin()?)
+    }
+
+    fn release_d6<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::D6,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::D6, pin.into_floating_input_pin()?)
+    }
+
+    fn release_d7<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::D7,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::D7, pin.into_floating_input_pin()?)
+    }
+
+    fn release_d8<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::D8,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::D8, pin.into_floating_input_pin()?)
+    }
+
+    fn release_p0<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::P0,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::P0, pin.into_floating_input_pin()?)
+    }
+
+    fn release_tx<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::TX,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::TX, pin.into_floating_input_pin()?)
+    }
+
+    fn release_rx<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::RX,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::RX, pin.into_floating_input_pin()?)
+    }
+
+    fn release_miso<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::MISO,
+            Error = Self::Error,
+        >,
+    {
+        self.pins
+            .release(gpio::MISO, pin.into_floating_input_pin()?)
+    }
+
+    fn release_mosi<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::MOSI,
+            Error = Self::Error,
+        >,
+    {
+        self.pins
+            .release(gpio::MOSI, pin.into_floating_input_pin()?)
+    }
+
+    fn release_sck<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::SCK,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::SCK, pin.into_floating_input_pin()?)
+    }
+
+    fn release_a5<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::A5,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::A5, pin.into_floating_input_pin()?)
+    }
+
+    fn release_a4<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::A4,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::A4, pin.into_floating_input_pin()?)
+    }
+
+    fn release_a3<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::A3,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::A3, pin.into_floating_input_pin()?)
+    }
+
+    fn release_a2<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::A2,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::A2, pin.into_floating_input_pin()?)
+    }
+
+    fn release_a1<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::A1,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::A1, pin.into_floating_input_pin()?)
+    }
+
+    fn release_a0<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: embedded_platform::gpio::IntoFloatingInputPin<
+            FloatingInputPin = Self::A0,
+            Error = Self::Error,
+        >,
+    {
+        self.pins.release(gpio::A0, pin.into_floating_input_pin()?)
+    }
+}
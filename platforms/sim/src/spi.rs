@@ -0,0 +1,163 @@
+//! A simulated SPI bus.
+//!
+//! Every transaction that the application performs is recorded, and can be inspected through a
+//! [`Probe`].  Data clocked in on MISO is taken from a queue that tests can fill up.
+use crate::error;
+use crate::gpio;
+use core::pin;
+use core::task;
+use std::collections;
+use std::sync;
+
+/// The byte that is clocked in on MISO when no data has been queued up.
+pub const IDLE_MISO: u8 = 0xff;
+
+#[derive(Debug, Default)]
+struct State {
+    transactions: Vec<Vec<u8>>,
+    miso: collections::VecDeque<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Bus(sync::Arc<sync::Mutex<State>>);
+
+impl Bus {
+    pub(crate) fn new() -> Self {
+        Bus(sync::Arc::new(sync::Mutex::new(State::default())))
+    }
+
+    fn exchange(&self, tx: &[u8], rx: &mut [u8]) {
+        let mut state = self.0.lock().unwrap();
+        let state = &mut *state;
+        if let Some(transaction) = state.transactions.last_mut() {
+            transaction.extend_from_slice(tx);
+        }
+        for byte in rx {
+            *byte = state.miso.pop_front().unwrap_or(IDLE_MISO);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Spi {
+    bus: Bus,
+}
+
+#[derive(Debug)]
+pub struct Transaction<'t> {
+    spi: &'t mut Spi,
+}
+
+#[derive(Debug)]
+pub struct Transfer<'a> {
+    bus: Bus,
+    buffer: &'a mut [u8],
+}
+
+#[derive(Debug)]
+pub struct TransferSplit<'a> {
+    bus: Bus,
+    tx_buffer: &'a [u8],
+    rx_buffer: &'a mut [u8],
+}
+
+impl Spi {
+    pub(crate) fn new<SCK, MOSI, MISO>(
+        bus: Bus,
+        sck: &gpio::Pin<SCK>,
+        mosi: &gpio::Pin<MOSI>,
+        miso: &gpio::Pin<MISO>,
+    ) -> Self {
+        sck.configure(gpio::Mode::PushPullOutput, false);
+        mosi.configure(gpio::Mode::PushPullOutput, false);
+        miso.configure(gpio::Mode::FloatingInput, false);
+        Self { bus }
+    }
+}
+
+impl<'t, 'a> embedded_platform::spi::Spi<'t, 'a> for Spi
+where
+    't: 'a,
+{
+    type Error = error::Error;
+    type Transaction = Transaction<'t>;
+
+    fn transaction(&'t mut self) -> Self::Transaction {
+        self.bus.0.lock().unwrap().transactions.push(Vec::new());
+        Transaction { spi: self }
+    }
+}
+
+impl<'t, 'a> embedded_platform::spi::SpiTransaction<'t, 'a> for Transaction<'t> {
+    type Error = error::Error;
+
+    type Transfer = Transfer<'a>;
+    type TransferSplit = TransferSplit<'a>;
+
+    fn transfer(&'a mut self, buffer: &'a mut [u8]) -> Result<Self::Transfer, Self::Error> {
+        let bus = self.spi.bus.clone();
+        Ok(Transfer { bus, buffer })
+    }
+
+    fn transfer_split(
+        &'a mut self,
+        tx_buffer: &'a [u8],
+        rx_buffer: &'a mut [u8],
+    ) -> Result<Self::TransferSplit, Self::Error> {
+        let bus = self.spi.bus.clone();
+        Ok(TransferSplit {
+            bus,
+            tx_buffer,
+            rx_buffer,
+        })
+    }
+}
+
+impl<'a> embedded_platform::spi::SpiTransfer<'a> for Transfer<'a> {
+    type Error = error::Error;
+
+    fn poll_complete(
+        mut self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        let this = &mut *self;
+        let tx = this.buffer.to_vec();
+        this.bus.exchange(&tx, this.buffer);
+        task::Poll::Ready(Ok(()))
+    }
+}
+
+impl<'a> embedded_platform::spi::SpiTransfer<'a> for TransferSplit<'a> {
+    type Error = error::Error;
+
+    fn poll_complete(
+        mut self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        let this = &mut *self;
+        this.bus.exchange(this.tx_buffer, this.rx_buffer);
+        task::Poll::Ready(Ok(()))
+    }
+}
+
+/// A handle for feeding data to and inspecting the traffic on the simulated SPI bus.
+#[derive(Clone, Debug)]
+pub struct Probe {
+    bus: Bus,
+}
+
+impl Probe {
+    pub(crate) fn new(bus: Bus) -> Self {
+        Self { bus }
+    }
+
+    /// Queues up data to be clocked in on MISO during upcoming transfers.
+    pub fn queue_miso(&self, bytes: &[u8]) {
+        self.bus.0.lock().unwrap().miso.extend(bytes)
+    }
+
+    /// Takes the data clocked out on MOSI so far, with one entry per transaction.
+    pub fn take_transactions(&self) -> Vec<Vec<u8>> {
+        self.bus.0.lock().unwrap().transactions.split_off(0)
+    }
+}
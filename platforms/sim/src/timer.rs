@@ -0,0 +1,299 @@
+//! Simulated hardware timers.
+//!
+//! Simulated timers are driven by a [`VirtualClock`](crate::clock::VirtualClock), and expire as
+//! virtual time is advanced past their deadlines.  A [`Probe`] can be used to inspect how the
+//! application configured a timer.
+use crate::clock;
+use crate::error;
+use core::mem;
+use core::pin;
+use core::task;
+
+/// The mode that a simulated timer has been configured in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    Oneshot(embedded_platform::time::Duration),
+    Periodic(embedded_platform::time::Rate),
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct State {
+    mode: Option<Mode>,
+    period: u64,
+    deadline: Option<u64>,
+    started: Option<u64>,
+    stopped: Option<u64>,
+    pending: u32,
+    waker: Option<task::Waker>,
+}
+
+impl State {
+    pub(crate) fn deadline(&self) -> Option<u64> {
+        self.deadline
+    }
+
+    pub(crate) fn expire(&mut self) -> Option<task::Waker> {
+        self.pending += 1;
+        match self.mode {
+            Some(Mode::Periodic(_)) => {
+                self.started = self.deadline;
+                // A deadline past the end of virtual time is never reached.
+                self.deadline = self
+                    .deadline
+                    .and_then(|deadline| deadline.checked_add(self.period));
+            }
+            _ => {
+                self.stopped = self.deadline;
+                self.deadline = None;
+            }
+        }
+        self.waker.take()
+    }
+
+    fn start(&mut self, now: u64) {
+        self.deadline = now.checked_add(self.period);
+        self.started = Some(now);
+        self.stopped = None;
+        self.pending = 0;
+    }
+}
+
+#[derive(Debug)]
+pub struct Timer {
+    clock: clock::VirtualClock,
+    index: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct Timers {
+    pub timer0: Option<Timer>,
+    pub timer1: Option<Timer>,
+    pub timer2: Option<Timer>,
+    pub timer3: Option<Timer>,
+    pub probes: [Probe; 4],
+}
+
+impl Timer {
+    pub(crate) fn new(clock: clock::VirtualClock, index: usize) -> Self {
+        Self { clock, index }
+    }
+
+    fn with<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut State, u64) -> R,
+    {
+        let mut state = self.clock.lock();
+        let now = state.now;
+        f(&mut state.timers[self.index], now)
+    }
+
+    /// Starts this timer outside of a task, returning the time at which it was started.
+    pub(crate) fn start_immediately(&self) -> embedded_platform::time::Instant {
+        let started = self.with(|state, now| {
+            state.start(now);
+            now
+        });
+        embedded_platform::time::Instant::from_nanos(started)
+    }
+
+    fn into_mode(self, mode: Mode, period: u64) -> Self {
+        self.with(|state, _| {
+            state.mode = Some(mode);
+            // A zero period would make a periodic timer expire infinitely often.
+            state.period = period.max(1);
+            state.deadline = None;
+            state.started = None;
+            state.stopped = None;
+            state.pending = 0;
+        });
+        self
+    }
+}
+
+impl Timers {
+    pub(crate) fn new(clock: &clock::VirtualClock) -> Self {
+        let timer0 = clock.timer();
+        let timer1 = clock.timer();
+        let timer2 = clock.timer();
+        let timer3 = clock.timer();
+
+        let probes = [
+            Probe::new(&timer0),
+            Probe::new(&timer1),
+            Probe::new(&timer2),
+            Probe::new(&timer3),
+        ];
+
+        let timer0 = Some(timer0);
+        let timer1 = Some(timer1);
+        let timer2 = Some(timer2);
+        let timer3 = Some(timer3);
+
+        Timers {
+            timer0,
+            timer1,
+            timer2,
+            timer3,
+            probes,
+        }
+    }
+
+    /// Puts a timer back into the specified slot, stopping it and forgetting its mode.
+    ///
+    /// Panics if the timer doesn't belong in that slot.
+    pub(crate) fn release(&mut self, slot: usize, timer: Timer) {
+        assert_eq!(
+            timer.index, self.probes[slot].index,
+            "a different timer can't be released as timer {}",
+            slot
+        );
+        timer.with(|state, _| *state = State::default());
+        let slot = match slot {
+            0 => &mut self.timer0,
+            1 => &mut self.timer1,
+            2 => &mut self.timer2,
+            _ => &mut self.timer3,
+        };
+        *slot = Some(timer);
+    }
+}
+
+impl embedded_platform::timer::Timer for Timer {
+    type Error = error::Error;
+
+    fn poll_start(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        self.with(|state, now| state.start(now));
+        task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_tick(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        self.with(|state, _| {
+            if state.pending > 0 {
+                state.pending -= 1;
+                task::Poll::Ready(Ok(()))
+            } else {
+                state.waker = Some(cx.waker().clone());
+                task::Poll::Pending
+            }
+        })
+    }
+
+    fn poll_ticks(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<u32, Self::Error>> {
+        self.with(|state, _| {
+            if state.pending > 0 {
+                task::Poll::Ready(Ok(mem::take(&mut state.pending)))
+            } else {
+                state.waker = Some(cx.waker().clone());
+                task::Poll::Pending
+            }
+        })
+    }
+}
+
+impl embedded_platform::timer::CancellableTimer for Timer {
+    fn poll_cancel(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        self.with(|state, now| {
+            if state.deadline.take().is_some() {
+                state.stopped = Some(now);
+            }
+            state.pending = 0;
+        });
+        task::Poll::Ready(Ok(()))
+    }
+}
+
+impl embedded_platform::timer::RestartableTimer for Timer {
+    fn poll_restart(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+        delay: embedded_platform::time::Duration,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        self.with(|state, now| {
+            state.period = delay.as_nanos().max(1);
+            state.mode = Some(match state.mode {
+                Some(Mode::Periodic(_)) => {
+                    let period = embedded_platform::time::Duration::from_nanos(state.period);
+                    Mode::Periodic(embedded_platform::time::Rate::from_period(period))
+                }
+                _ => Mode::Oneshot(delay),
+            });
+            state.start(now);
+        });
+        task::Poll::Ready(Ok(()))
+    }
+}
+
+impl embedded_platform::timer::CountingTimer for Timer {
+    fn poll_elapsed(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<embedded_platform::time::Duration, Self::Error>> {
+        let elapsed = self.with(|state, now| {
+            state
+                .started
+                .map_or(0, |started| state.stopped.unwrap_or(now) - started)
+        });
+        task::Poll::Ready(Ok(embedded_platform::time::Duration::from_nanos(elapsed)))
+    }
+}
+
+impl embedded_platform::timer::IntoPeriodicTimer for Timer {
+    type PeriodicTimer = Self;
+
+    fn into_periodic_timer(
+        self,
+        rate: embedded_platform::time::Rate,
+    ) -> Result<Self::PeriodicTimer, Self::Error> {
+        let period = rate.period().as_nanos();
+        Ok(self.into_mode(Mode::Periodic(rate), period))
+    }
+}
+
+impl embedded_platform::timer::IntoOneshotTimer for Timer {
+    type OneshotTimer = Self;
+
+    fn into_oneshot_timer(
+        self,
+        delay: embedded_platform::time::Duration,
+    ) -> Result<Self::OneshotTimer, Self::Error> {
+        let period = delay.as_nanos();
+        Ok(self.into_mode(Mode::Oneshot(delay), period))
+    }
+}
+
+/// A handle for inspecting a simulated timer from the outside.
+#[derive(Clone, Debug)]
+pub struct Probe {
+    clock: clock::VirtualClock,
+    index: usize,
+}
+
+impl Probe {
+    fn new(timer: &Timer) -> Self {
+        let clock = timer.clock.clone();
+        let index = timer.index;
+        Self { clock, index }
+    }
+
+    /// The mode that the application has configured the timer in, if any.
+    pub fn mode(&self) -> Option<Mode> {
+        self.clock.lock().timers[self.index].mode
+    }
+
+    /// Whether the timer has been started and has a deadline that it will expire at.
+    pub fn is_running(&self) -> bool {
+        self.clock.lock().timers[self.index].deadline.is_some()
+    }
+}
@@ -0,0 +1,153 @@
+use embedded_platform::adc::{Buffer, Config, Gain, Reading, Reference, BUFFER_LEN};
+use embedded_platform::prelude::*;
+use embedded_platform::specs::feather::{AnalogFeather, Feather};
+use embedded_platform::time::{Duration, Rate};
+use futures::stream::Stream;
+use sim_platform::adc::Waveform;
+use sim_platform::gpio;
+use sim_platform::Simulator;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+fn poll<F>(future: &mut F) -> Poll<F::Output>
+where
+    F: Future + Unpin,
+{
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    Pin::new(future).poll(&mut cx)
+}
+
+fn next<S>(stream: &mut S) -> Poll<Option<S::Item>>
+where
+    S: Stream + Unpin,
+{
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    Pin::new(stream).poll_next(&mut cx)
+}
+
+fn millivolts(buffer: &Buffer) -> Vec<i32> {
+    buffer
+        .readings()
+        .map(|reading| reading.millivolts)
+        .collect()
+}
+
+/// Reads `A0` of any board with analog inputs once.
+fn read_a0<F>(board: &mut F, config: Config) -> Poll<Result<Reading, F::Error>>
+where
+    F: AnalogFeather,
+{
+    let mut pin = board.take_a0().into_analog_input_pin(config)?;
+    poll(&mut pin.read())
+}
+
+#[test]
+fn readings_are_scaled_to_millivolts() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    probe.apply_voltage(gpio::A0, 1800);
+
+    let reading = read_a0(&mut sim, Config::default());
+    let expected = Reading {
+        raw: 2048,
+        millivolts: 1800,
+    };
+    assert_eq!(reading, Poll::Ready(Ok(expected)));
+    assert_eq!(probe.mode(gpio::A0), gpio::Mode::AnalogInput);
+}
+
+#[test]
+fn readings_saturate_at_full_scale() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let config = Config {
+        gain: Gain::OneQuarter,
+        reference: Reference::Supply,
+    };
+    let mut pin = sim.take_a3().into_analog_input_pin(config).unwrap();
+
+    probe.drive(gpio::A3, true);
+    let reading = poll(&mut pin.read()).map(|reading| reading.unwrap().raw);
+    assert_eq!(reading, Poll::Ready(4095));
+
+    probe.apply_voltage(gpio::A3, 825);
+    let reading = poll(&mut pin.read()).map(|reading| reading.unwrap().millivolts);
+    assert_eq!(reading, Poll::Ready(825));
+}
+
+#[test]
+fn sampling_follows_waveforms() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let clock = sim.clock();
+    let waveform = Waveform::Square {
+        low: 0,
+        high: 1800,
+        frequency: Rate::from_khz(1),
+    };
+    probe.apply_waveform(gpio::A1, waveform);
+    let pin = sim
+        .take_a1()
+        .into_analog_input_pin(Config::default())
+        .unwrap();
+    let mut sampler = sim
+        .take_timer0()
+        .into_sampler(pin, Rate::from_khz(8))
+        .unwrap();
+    let mut buffers = sampler.buffers();
+
+    assert!(next(&mut buffers).is_pending());
+    clock.advance(Duration::from_micros(125) * (BUFFER_LEN as u32 - 1));
+    assert!(next(&mut buffers).is_pending());
+    clock.advance(Duration::from_micros(125));
+    let buffer = match next(&mut buffers) {
+        Poll::Ready(Some(Ok(buffer))) => buffer,
+        other => panic!("expected a buffer, got {:?}", other),
+    };
+
+    // Samples are taken at the ticks of the timer, the first one a period after it was started.
+    let period = [1800, 1800, 1800, 0, 0, 0, 0, 1800];
+    let expected: Vec<i32> = period.iter().cycle().take(BUFFER_LEN).cloned().collect();
+    assert_eq!(millivolts(&buffer), expected);
+    assert_eq!(buffer.overruns(), 0);
+    assert!(next(&mut buffers).is_pending());
+}
+
+#[test]
+fn sampling_reports_overruns() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let clock = sim.clock();
+    let waveform = Waveform::Sawtooth {
+        low: 0,
+        high: 3200,
+        frequency: Rate::from_hz(1),
+    };
+    probe.apply_waveform(gpio::A2, waveform);
+    let config = Config {
+        gain: Gain::OneQuarter,
+        reference: Reference::Supply,
+    };
+    let pin = sim.take_a2().into_analog_input_pin(config).unwrap();
+    let mut sampler = sim
+        .take_timer1()
+        .into_sampler(pin, Rate::from_hz(1024))
+        .unwrap();
+
+    clock.advance(Duration::from_millis(1000) * 3 * BUFFER_LEN as u32 / 1024);
+    let buffer = match next(&mut sampler.buffers()) {
+        Poll::Ready(Some(Ok(buffer))) => buffer,
+        other => panic!("expected a buffer, got {:?}", other),
+    };
+    assert_eq!(buffer.overruns(), 2);
+    // The newest buffer is kept, which starts with the 129th sample of the sawtooth, at 403 mV.
+    assert_eq!(buffer.raw()[0], 500);
+
+    clock.advance(Duration::from_millis(1000) * BUFFER_LEN as u32 / 1024);
+    let buffer = match next(&mut sampler.buffers()) {
+        Poll::Ready(Some(Ok(buffer))) => buffer,
+        other => panic!("expected a buffer, got {:?}", other),
+    };
+    assert_eq!(buffer.overruns(), 0);
+}
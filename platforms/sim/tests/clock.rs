@@ -0,0 +1,102 @@
+use embedded_platform::clock::Clock;
+use embedded_platform::prelude::*;
+use embedded_platform::time::{Duration, Instant};
+use futures::task::ArcWake;
+use sim_platform::clock::VirtualClock;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+/// A waker that records its name in a shared log when it is woken.
+struct Recorder {
+    name: &'static str,
+    log: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl ArcWake for Recorder {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.log.lock().unwrap().push(arc_self.name);
+    }
+}
+
+/// Creates a oneshot timer with the specified delay, and registers a waker that records `name`.
+fn oneshot(
+    clock: &VirtualClock,
+    delay: Duration,
+    name: &'static str,
+    log: &Arc<Mutex<Vec<&'static str>>>,
+) -> sim_platform::timer::Timer {
+    let mut timer = clock.timer().into_oneshot_timer(delay).unwrap();
+    futures::executor::block_on(timer.start()).unwrap();
+    let log = log.clone();
+    let waker = futures::task::waker(Arc::new(Recorder { name, log }));
+    let mut cx = Context::from_waker(&waker);
+    let tick = embedded_platform::timer::Timer::poll_tick(Pin::new(&mut timer), &mut cx);
+    assert_eq!(tick, Poll::Pending);
+    timer
+}
+
+#[test]
+fn advance_moves_time_forward() {
+    let clock = VirtualClock::new();
+    assert_eq!(clock.now(), Instant::from_nanos(0));
+
+    clock.advance(Duration::from_millis(3));
+    clock.advance(Duration::from_micros(250));
+    assert_eq!(clock.now(), Instant::from_nanos(3_250_000));
+}
+
+#[test]
+fn advance_saturates_at_the_end_of_time() {
+    let clock = VirtualClock::new();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let _timer = oneshot(&clock, Duration::from_seconds(1), "timer", &log);
+
+    clock.advance(Duration::from_millis(1500));
+    clock.advance(Duration::MAX);
+    assert_eq!(clock.now(), Instant::from_nanos(u64::MAX));
+    clock.advance(Duration::from_seconds(1));
+    assert_eq!(clock.now(), Instant::from_nanos(u64::MAX));
+    assert_eq!(*log.lock().unwrap(), vec!["timer"]);
+
+    // A timer that is started at the end of time never expires.
+    let _never = oneshot(&clock, Duration::from_seconds(1), "never", &log);
+    assert!(!clock.advance_to_next_deadline());
+}
+
+#[test]
+fn wakers_fire_in_deadline_order() {
+    let clock = VirtualClock::new();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let _late = oneshot(&clock, Duration::from_millis(3), "late", &log);
+    let _early = oneshot(&clock, Duration::from_millis(1), "early", &log);
+    let _tie_first = oneshot(&clock, Duration::from_millis(2), "tie first", &log);
+    let _tie_second = oneshot(&clock, Duration::from_millis(2), "tie second", &log);
+
+    clock.advance(Duration::from_millis(5));
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec!["early", "tie first", "tie second", "late"]
+    );
+}
+
+#[test]
+fn advance_to_next_deadline_stops_at_the_earliest_deadline() {
+    let clock = VirtualClock::new();
+    let log = Arc::new(Mutex::new(Vec::new()));
+    assert!(!clock.advance_to_next_deadline());
+    assert_eq!(clock.now(), Instant::from_nanos(0));
+
+    let _second = oneshot(&clock, Duration::from_millis(7), "second", &log);
+    let _first = oneshot(&clock, Duration::from_millis(4), "first", &log);
+
+    assert!(clock.advance_to_next_deadline());
+    assert_eq!(clock.now(), Instant::from_nanos(4_000_000));
+    assert_eq!(*log.lock().unwrap(), vec!["first"]);
+
+    assert!(clock.advance_to_next_deadline());
+    assert_eq!(clock.now(), Instant::from_nanos(7_000_000));
+    assert_eq!(*log.lock().unwrap(), vec!["first", "second"]);
+
+    assert!(!clock.advance_to_next_deadline());
+}
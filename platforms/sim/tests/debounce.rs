@@ -0,0 +1,81 @@
+use embedded_platform::prelude::*;
+use embedded_platform::specs::feather::Feather;
+use embedded_platform::time::Duration;
+use futures::stream::Stream;
+use sim_platform::gpio;
+use sim_platform::Simulator;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+fn poll<F>(future: &mut F) -> Poll<F::Output>
+where
+    F: Future + Unpin,
+{
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    Pin::new(future).poll(&mut cx)
+}
+
+fn next<S>(stream: &mut S) -> Poll<Option<S::Item>>
+where
+    S: Stream + Unpin,
+{
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    Pin::new(stream).poll_next(&mut cx)
+}
+
+#[test]
+fn bounces_are_filtered_out() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let clock = sim.clock();
+    let timer = sim.take_timer0();
+    let mut button = sim
+        .take_d2()
+        .into_pull_up_input_pin()
+        .unwrap()
+        .debounce(timer, Duration::from_millis(20))
+        .unwrap();
+
+    assert_eq!(poll(&mut button.get()), Poll::Ready(Ok(true)));
+    let mut changes = button.changes();
+    assert_eq!(next(&mut changes), Poll::Pending);
+
+    // Bounce for a while, never staying low for a whole settle time.
+    for _ in 0..5 {
+        probe.drive(gpio::D2, false);
+        clock.advance(Duration::from_millis(10));
+        assert_eq!(next(&mut changes), Poll::Pending);
+        probe.drive(gpio::D2, true);
+        clock.advance(Duration::from_millis(5));
+        assert_eq!(next(&mut changes), Poll::Pending);
+    }
+
+    probe.drive(gpio::D2, false);
+    clock.advance(Duration::from_millis(15));
+    assert_eq!(next(&mut changes), Poll::Pending);
+    clock.advance(Duration::from_millis(5));
+    assert_eq!(next(&mut changes), Poll::Ready(Some(Ok(false))));
+    assert_eq!(next(&mut changes), Poll::Pending);
+}
+
+#[test]
+fn get_reads_stable_level() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let clock = sim.clock();
+    let timer = sim.take_timer1();
+    let mut pin = sim
+        .take_d3()
+        .into_floating_input_pin()
+        .unwrap()
+        .debounce(timer, Duration::from_millis(8))
+        .unwrap();
+
+    assert_eq!(poll(&mut pin.get()), Poll::Ready(Ok(false)));
+    probe.drive(gpio::D3, true);
+    clock.advance(Duration::from_millis(6));
+    assert_eq!(poll(&mut pin.get()), Poll::Ready(Ok(false)));
+    clock.advance(Duration::from_millis(2));
+    assert_eq!(poll(&mut pin.get()), Poll::Ready(Ok(true)));
+}
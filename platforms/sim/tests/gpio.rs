@@ -0,0 +1,337 @@
+use embedded_platform::adc::Config;
+use embedded_platform::gpio::{DriveStrength, OpenDrainFlavor};
+use embedded_platform::prelude::*;
+use embedded_platform::specs::feather;
+use embedded_platform::specs::feather::{AnalogFeather, Feather};
+use futures::stream::Stream;
+use sim_platform::gpio;
+use sim_platform::Simulator;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+fn poll<F>(future: &mut F) -> Poll<F::Output>
+where
+    F: Future + Unpin,
+{
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    Pin::new(future).poll(&mut cx)
+}
+
+fn next<S>(stream: &mut S) -> Poll<Option<S::Item>>
+where
+    S: Stream + Unpin,
+{
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    Pin::new(stream).poll_next(&mut cx)
+}
+
+#[test]
+fn wait_for_level_completes_immediately_if_already_there() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let mut pin = sim.take_d2().into_pull_up_input_pin().unwrap();
+
+    assert_eq!(poll(&mut pin.wait_for_high()), Poll::Ready(Ok(())));
+
+    let mut low = pin.wait_for_low();
+    assert_eq!(poll(&mut low), Poll::Pending);
+    probe.drive(gpio::D2, false);
+    assert_eq!(poll(&mut low), Poll::Ready(Ok(())));
+}
+
+#[test]
+fn edges_only_count_after_listening() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let mut pin = sim.take_d2().into_floating_input_pin().unwrap();
+    probe.drive(gpio::D2, true);
+
+    let mut rising = pin.wait_for_rising_edge();
+    assert_eq!(poll(&mut rising), Poll::Pending);
+    probe.drive(gpio::D2, false);
+    assert_eq!(poll(&mut rising), Poll::Pending);
+    probe.drive(gpio::D2, true);
+    assert_eq!(poll(&mut rising), Poll::Ready(Ok(())));
+
+    let mut falling = pin.wait_for_falling_edge();
+    assert_eq!(poll(&mut falling), Poll::Pending);
+    probe.drive(gpio::D2, false);
+    assert_eq!(poll(&mut falling), Poll::Ready(Ok(())));
+
+    let mut any = pin.wait_for_any_edge();
+    assert_eq!(poll(&mut any), Poll::Pending);
+    probe.disconnect(gpio::D2);
+    assert_eq!(poll(&mut any), Poll::Pending);
+    probe.drive(gpio::D2, true);
+    assert_eq!(poll(&mut any), Poll::Ready(Ok(())));
+}
+
+#[test]
+fn changes_reports_every_change_in_order() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let mut pin = sim.take_d3().into_pull_down_input_pin().unwrap();
+    let mut changes = pin.changes();
+
+    assert_eq!(next(&mut changes), Poll::Pending);
+    probe.drive(gpio::D3, true);
+    probe.drive(gpio::D3, false);
+    probe.drive(gpio::D3, true);
+
+    assert_eq!(next(&mut changes), Poll::Ready(Some(Ok(true))));
+    assert_eq!(next(&mut changes), Poll::Ready(Some(Ok(false))));
+    assert_eq!(next(&mut changes), Poll::Ready(Some(Ok(true))));
+    assert_eq!(next(&mut changes), Poll::Pending);
+}
+
+#[test]
+fn waiting_task_is_woken_on_change() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let mut pin = sim.take_d4().into_floating_input_pin().unwrap();
+
+    let handle = std::thread::spawn(move || {
+        futures::executor::block_on(pin.wait_for_rising_edge()).unwrap();
+    });
+    while !handle.is_finished() {
+        probe.drive(gpio::D4, false);
+        probe.drive(gpio::D4, true);
+        std::thread::yield_now();
+    }
+    handle.join().unwrap();
+}
+
+#[test]
+fn main_led_records_its_level_history() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    // The LED is wired active-low, so the physical level is the inverse of the LED state.
+    let mut led = sim.take_main_led().into_push_pull_output_pin(true).unwrap();
+    assert_eq!(probe.mode(gpio::MAIN_LED), gpio::Mode::PushPullOutput);
+    assert!(!probe.level(gpio::MAIN_LED));
+
+    assert_eq!(poll(&mut led.set(false)), Poll::Ready(Ok(())));
+    assert_eq!(poll(&mut led.set(true)), Poll::Ready(Ok(())));
+    assert_eq!(poll(&mut led.set(false)), Poll::Ready(Ok(())));
+    assert!(probe.level(gpio::MAIN_LED));
+    assert_eq!(probe.history(gpio::MAIN_LED), vec![true, false, true]);
+}
+
+#[test]
+fn main_led_is_lit_when_set_high() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let mut led = sim
+        .take_main_led()
+        .into_push_pull_output_pin(false)
+        .unwrap();
+    assert!(probe.level(gpio::MAIN_LED));
+
+    assert_eq!(poll(&mut led.set(true)), Poll::Ready(Ok(())));
+    assert!(!probe.level(gpio::MAIN_LED));
+    assert_eq!(poll(&mut led.is_set_high()), Poll::Ready(Ok(true)));
+}
+
+#[test]
+fn toggle_inverts_the_level_that_is_set() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let mut pin = sim.take_d3().into_push_pull_output_pin(true).unwrap();
+    assert_eq!(poll(&mut pin.is_set_high()), Poll::Ready(Ok(true)));
+
+    assert_eq!(poll(&mut pin.toggle()), Poll::Ready(Ok(())));
+    assert_eq!(poll(&mut pin.is_set_high()), Poll::Ready(Ok(false)));
+    assert_eq!(poll(&mut pin.toggle()), Poll::Ready(Ok(())));
+    assert_eq!(probe.history(gpio::D3), vec![false, true]);
+}
+
+#[test]
+fn degraded_pins_can_be_stored_together() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let mut bar = [
+        sim.take_d2().degrade(),
+        sim.take_d3().into_pull_up_input_pin().unwrap().degrade(),
+        sim.take_d4().degrade(),
+    ]
+    .map(|pin| pin.into_push_pull_output_pin(false).unwrap());
+
+    for pin in &mut bar {
+        assert_eq!(poll(&mut pin.set(true)), Poll::Ready(Ok(())));
+    }
+    assert_eq!(probe.mode(gpio::D3), gpio::Mode::PushPullOutput);
+    assert!(probe.level(gpio::D2) && probe.level(gpio::D3) && probe.level(gpio::D4));
+}
+
+#[test]
+fn degraded_pins_check_their_mode() {
+    let mut sim = Simulator::new();
+    let mut pin = sim.take_d2().degrade();
+    assert_eq!(
+        poll(&mut pin.set(true)),
+        Poll::Ready(Err(sim_platform::error::Error::WrongMode))
+    );
+
+    let mut pin = pin.into_open_drain_output_pin(true).unwrap();
+    assert_eq!(poll(&mut pin.set(false)), Poll::Ready(Ok(())));
+    assert_eq!(
+        poll(&mut pin.get()),
+        Poll::Ready(Err(sim_platform::error::Error::WrongMode))
+    );
+}
+
+#[test]
+fn output_configuration_is_applied_until_the_pin_is_reconfigured() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let mut pin = sim.take_d2().into_open_drain_output_pin(false).unwrap();
+    assert_eq!(probe.drive_strength(gpio::D2), DriveStrength::Standard);
+    assert_eq!(probe.open_drain_flavor(gpio::D2), OpenDrainFlavor::DriveLow);
+    assert!(!probe.level(gpio::D2));
+
+    pin.set_drive_strength(DriveStrength::High).unwrap();
+    pin.set_open_drain_flavor(OpenDrainFlavor::DriveHigh)
+        .unwrap();
+    assert_eq!(probe.drive_strength(gpio::D2), DriveStrength::High);
+    assert_eq!(
+        probe.open_drain_flavor(gpio::D2),
+        OpenDrainFlavor::DriveHigh
+    );
+    // The pin releases the line while low, and it is left to the external pull-down.
+    assert!(!probe.level(gpio::D2));
+    probe.drive(gpio::D2, true);
+    assert!(probe.level(gpio::D2));
+    probe.disconnect(gpio::D2);
+    assert_eq!(poll(&mut pin.set(true)), Poll::Ready(Ok(())));
+    assert!(probe.level(gpio::D2));
+
+    let _pin = pin.into_push_pull_output_pin(false).unwrap();
+    assert_eq!(probe.drive_strength(gpio::D2), DriveStrength::Standard);
+    assert_eq!(probe.open_drain_flavor(gpio::D2), OpenDrainFlavor::DriveLow);
+}
+
+#[test]
+fn degraded_pins_check_their_mode_when_configured() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let mut pin = sim.take_d2().degrade();
+    assert_eq!(
+        pin.set_drive_strength(DriveStrength::High),
+        Err(sim_platform::error::Error::WrongMode)
+    );
+
+    let mut pin = pin.into_push_pull_output_pin(false).unwrap();
+    assert_eq!(pin.set_drive_strength(DriveStrength::High), Ok(()));
+    assert_eq!(probe.drive_strength(gpio::D2), DriveStrength::High);
+    assert_eq!(
+        pin.set_open_drain_flavor(OpenDrainFlavor::DriveHigh),
+        Err(sim_platform::error::Error::WrongMode)
+    );
+
+    let mut pin = pin.into_open_drain_output_pin(false).unwrap();
+    assert_eq!(
+        pin.set_open_drain_flavor(OpenDrainFlavor::DriveHigh),
+        Ok(())
+    );
+    assert_eq!(
+        probe.open_drain_flavor(gpio::D2),
+        OpenDrainFlavor::DriveHigh
+    );
+}
+
+#[test]
+fn degraded_analog_pins_are_neither_inputs_nor_outputs() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let mut pin = sim
+        .take_a0()
+        .into_analog_input_pin(Config::default())
+        .unwrap()
+        .into_inner()
+        .degrade();
+
+    assert_eq!(
+        poll(&mut pin.get()),
+        Poll::Ready(Err(sim_platform::error::Error::WrongMode))
+    );
+    assert_eq!(
+        poll(&mut pin.set(true)),
+        Poll::Ready(Err(sim_platform::error::Error::WrongMode))
+    );
+    assert_eq!(
+        pin.set_drive_strength(DriveStrength::High),
+        Err(sim_platform::error::Error::WrongMode)
+    );
+    assert_eq!(probe.mode(gpio::A0), gpio::Mode::AnalogInput);
+    assert!(!probe.level(gpio::A0));
+}
+
+#[test]
+fn taking_a_pin_twice_reports_the_first_role() {
+    let mut sim = Simulator::new();
+    let _sck = sim.take_sck();
+
+    let err = sim.try_take_main_spi().unwrap_err();
+    assert_eq!(err.pin, gpio::SCK);
+    assert_eq!(err.role, feather::Role::Sck);
+    assert_eq!(err.to_string(), "pin 14 is already taken as Sck");
+
+    // The pins of the bus that were still available haven't been claimed.
+    assert_eq!(
+        sim.claimed_pins().collect::<Vec<_>>(),
+        vec![(gpio::SCK, feather::Role::Sck)]
+    );
+    assert!(sim.try_take_mosi().is_ok());
+}
+
+#[test]
+fn claimed_pins_lists_all_roles() {
+    let mut sim = Simulator::new();
+    let _led = sim.take_main_led();
+    let _d2 = sim.try_take_d2().unwrap();
+
+    assert_eq!(
+        sim.claimed_pins().collect::<Vec<_>>(),
+        vec![
+            (gpio::D2, feather::Role::D2),
+            (gpio::MAIN_LED, feather::Role::MainLed)
+        ]
+    );
+}
+
+#[test]
+fn released_pins_can_be_taken_again() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let led = sim.take_main_led().into_push_pull_output_pin(true).unwrap();
+    assert!(sim.try_take_main_led().is_err());
+
+    sim.release_main_led(led).unwrap();
+    assert_eq!(probe.mode(gpio::MAIN_LED), gpio::Mode::FloatingInput);
+    assert_eq!(sim.claimed_pins().count(), 0);
+
+    let _led = sim.try_take_main_led().unwrap();
+    assert_eq!(
+        sim.claimed_pins().collect::<Vec<_>>(),
+        vec![(gpio::MAIN_LED, feather::Role::MainLed)]
+    );
+}
+
+#[test]
+fn pins_are_released_into_their_own_slot() {
+    let mut sim = Simulator::new();
+    let _d2 = sim.take_d2();
+    let d3 = sim.take_d3();
+    assert_eq!(
+        sim.release_d2(d3),
+        Err(sim_platform::error::Error::WrongPin)
+    );
+
+    assert_eq!(
+        sim.claimed_pins().collect::<Vec<_>>(),
+        vec![(gpio::D2, feather::Role::D2), (gpio::D3, feather::Role::D3)]
+    );
+    assert!(sim.try_take_d2().is_err());
+    assert!(sim.try_take_d3().is_err());
+}
@@ -0,0 +1,51 @@
+use embedded_platform::platform::policy::{Action, Policy};
+use embedded_platform::platform::Platform;
+use embedded_platform::time::Duration;
+use futures::future;
+use std::env;
+use std::process;
+
+/// The environment variable that selects the policy that the child process runs `main` with.
+const CHILD: &str = "SIM_MAIN_CHILD";
+
+/// Runs a failing application under `Simulator::main` when started as a child process by one of
+/// the tests below, and does nothing otherwise.
+#[test]
+fn child() {
+    let policy = match env::var(CHILD).as_deref() {
+        Ok("succeed") => sim_platform::Simulator::main(Policy::new(), |_, _| future::ready(Ok(()))),
+        Ok("halt") => Policy::new()
+            .on_run_error(Action::Retry(2))
+            .backoff(Duration::from_millis(10)),
+        Ok("reset") => Policy::new().on_run_error(Action::Reset),
+        _ => return,
+    };
+
+    sim_platform::Simulator::main(policy, |_, _| {
+        future::ready(Err(sim_platform::error::Error::WrongMode))
+    })
+}
+
+fn exit_code(policy: &str) -> Option<i32> {
+    let status = process::Command::new(env::current_exe().unwrap())
+        .args(["--exact", "child", "--nocapture", "--test-threads=1"])
+        .env(CHILD, policy)
+        .status()
+        .unwrap();
+    status.code()
+}
+
+#[test]
+fn main_exits_with_zero_once_the_application_completes() {
+    assert_eq!(exit_code("succeed"), Some(0));
+}
+
+#[test]
+fn main_exits_with_one_on_halt() {
+    assert_eq!(exit_code("halt"), Some(1));
+}
+
+#[test]
+fn main_exits_with_two_on_reset() {
+    assert_eq!(exit_code("reset"), Some(2));
+}
@@ -0,0 +1,79 @@
+use embedded_platform::prelude::*;
+use embedded_platform::pwm::software::Generator;
+use embedded_platform::pwm::{Config, Duty, Polarity};
+use embedded_platform::specs::feather::Feather;
+use embedded_platform::time::{Duration, Rate};
+use futures::stream::Stream;
+use sim_platform::gpio;
+use sim_platform::Simulator;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+fn poll<F>(future: &mut F) -> Poll<F::Output>
+where
+    F: Future + Unpin,
+{
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    Pin::new(future).poll(&mut cx)
+}
+
+fn next<S>(stream: &mut S) -> Poll<Option<S::Item>>
+where
+    S: Stream + Unpin,
+{
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    Pin::new(stream).poll_next(&mut cx)
+}
+
+#[test]
+fn software_pwm_follows_the_duty_cycle() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let clock = sim.clock();
+    let config = Config::new(Rate::from_hz(100)).duty(Duty::from_percent(20));
+    let mut pwm = Generator::new(sim.take_timer0())
+        .into_pwm_pin(sim.take_d2(), config)
+        .unwrap();
+    probe.clear_history(gpio::D2);
+
+    assert_eq!(next(&mut pwm), Poll::Pending);
+    assert!(probe.level(gpio::D2));
+    clock.advance(Duration::from_micros(1900));
+    assert_eq!(next(&mut pwm), Poll::Pending);
+    assert!(probe.level(gpio::D2));
+    clock.advance(Duration::from_micros(100));
+    assert_eq!(next(&mut pwm), Poll::Pending);
+    assert!(!probe.level(gpio::D2));
+    clock.advance(Duration::from_millis(8));
+    assert_eq!(next(&mut pwm), Poll::Ready(Some(Ok(()))));
+    assert_eq!(probe.history(gpio::D2), vec![true, false]);
+
+    // The new duty cycle applies from the next period on, and the pin already is inactive.
+    assert_eq!(poll(&mut pwm.set_duty(Duty::ZERO)), Poll::Ready(Ok(())));
+    assert_eq!(next(&mut pwm), Poll::Pending);
+    clock.advance(Duration::from_millis(10));
+    assert_eq!(next(&mut pwm), Poll::Ready(Some(Ok(()))));
+    assert_eq!(probe.history(gpio::D2), vec![true, false]);
+}
+
+#[test]
+fn software_pwm_respects_polarity() {
+    let mut sim = Simulator::new();
+    let probe = sim.gpio_probe();
+    let clock = sim.clock();
+    let config = Config::new(Rate::from_hz(50))
+        .polarity(Polarity::ActiveLow)
+        .duty(Duty::FULL);
+    let mut pwm = Generator::new(sim.take_timer1())
+        .into_pwm_pin(sim.take_d3(), config)
+        .unwrap();
+    assert!(probe.level(gpio::D3));
+
+    assert_eq!(next(&mut pwm), Poll::Pending);
+    assert!(!probe.level(gpio::D3));
+    clock.advance(Duration::from_millis(20));
+    assert_eq!(next(&mut pwm), Poll::Ready(Some(Ok(()))));
+    assert_eq!(next(&mut pwm), Poll::Pending);
+    assert!(!probe.level(gpio::D3));
+}
@@ -0,0 +1,66 @@
+use embedded_platform::prelude::*;
+use embedded_platform::time::Duration;
+use embedded_platform::timer::ticks::MissedTickBehavior;
+use futures::stream::Stream;
+use sim_platform::clock::VirtualClock;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+fn next<S>(stream: &mut S) -> Poll<Option<S::Item>>
+where
+    S: Stream + Unpin,
+{
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    Pin::new(stream).poll_next(&mut cx)
+}
+
+fn periodic(clock: &VirtualClock) -> sim_platform::timer::Timer {
+    let mut timer = clock.timer().into_periodic_timer(1000u32.hz()).unwrap();
+    futures::executor::block_on(timer.start()).unwrap();
+    timer
+}
+
+#[test]
+fn burst_catches_up_with_every_tick() {
+    let clock = VirtualClock::new();
+    let mut timer = periodic(&clock);
+    let mut ticks = timer.ticks_with(MissedTickBehavior::Burst);
+
+    clock.advance(Duration::from_micros(3_500));
+    assert_eq!(next(&mut ticks), Poll::Ready(Some(Ok(2))));
+    assert_eq!(next(&mut ticks), Poll::Ready(Some(Ok(1))));
+    assert_eq!(next(&mut ticks), Poll::Ready(Some(Ok(0))));
+    assert_eq!(next(&mut ticks), Poll::Pending);
+
+    clock.advance(Duration::from_micros(500));
+    assert_eq!(next(&mut ticks), Poll::Ready(Some(Ok(0))));
+}
+
+#[test]
+fn skip_keeps_original_schedule() {
+    let clock = VirtualClock::new();
+    let mut timer = periodic(&clock);
+    let mut ticks = timer.ticks_with(MissedTickBehavior::Skip);
+
+    clock.advance(Duration::from_micros(3_500));
+    assert_eq!(next(&mut ticks), Poll::Ready(Some(Ok(2))));
+    assert_eq!(next(&mut ticks), Poll::Pending);
+
+    clock.advance(Duration::from_micros(500));
+    assert_eq!(next(&mut ticks), Poll::Ready(Some(Ok(0))));
+}
+
+#[test]
+fn delay_restarts_schedule() {
+    let clock = VirtualClock::new();
+    let mut timer = periodic(&clock);
+    let mut ticks = timer.ticks_with(MissedTickBehavior::Delay);
+
+    clock.advance(Duration::from_micros(3_500));
+    assert_eq!(next(&mut ticks), Poll::Ready(Some(Ok(2))));
+
+    clock.advance(Duration::from_micros(500));
+    assert_eq!(next(&mut ticks), Poll::Pending);
+    clock.advance(Duration::from_micros(500));
+    assert_eq!(next(&mut ticks), Poll::Ready(Some(Ok(0))));
+}
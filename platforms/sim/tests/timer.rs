@@ -0,0 +1,40 @@
+use embedded_platform::prelude::*;
+use embedded_platform::time::Rate;
+use sim_platform::Simulator;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+fn poll<F>(future: &mut F) -> Poll<F::Output>
+where
+    F: Future + Unpin,
+{
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    Pin::new(future).poll(&mut cx)
+}
+
+#[test]
+fn released_timers_are_stopped() {
+    let mut sim = Simulator::new();
+    let probe = sim.timer_probe(1);
+    let mut timer = sim
+        .take_timer1()
+        .into_periodic_timer(Rate::from_hz(10))
+        .unwrap();
+    assert_eq!(poll(&mut timer.start()), Poll::Ready(Ok(())));
+    assert!(probe.is_running());
+
+    sim.release_timer1(timer);
+    assert!(!probe.is_running());
+    assert_eq!(probe.mode(), None);
+
+    let _timer = sim.take_timer1();
+}
+
+#[test]
+#[should_panic]
+fn timers_are_released_into_their_own_slot() {
+    let mut sim = Simulator::new();
+    let timer = sim.take_timer1();
+    sim.release_timer0(timer);
+}
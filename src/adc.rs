@@ -0,0 +1,263 @@
+//! Analog inputs.
+//!
+//! An [`AnalogInputPin`] measures the voltage on a pin using an analog-to-digital converter, and
+//! reports it as a [`Reading`] in both the raw counts of the converter and in millivolts.  Pins
+//! are turned into analog inputs using [`IntoAnalogInputPin`], with a [`Config`] that selects the
+//! gain and reference voltage, and thereby the range of voltages that can be measured.
+//!
+//! Signals that change quickly, such as audio or vibrations, can instead be sampled continuously
+//! at a fixed rate.  A generator that implements [`IntoSampler`], usually a timer that triggers
+//! the converter, turns an analog input into a [`Sampler`], which fills [`Buffer`]s in the
+//! background and hands them out one at a time, e.g. using [`SamplerExt::buffers`].
+use crate::gpio;
+use crate::time;
+use core::pin;
+use core::slice;
+use core::task;
+
+pub mod buffers;
+pub mod read;
+
+/// The number of samples in each [`Buffer`] of a [`Sampler`].
+pub const BUFFER_LEN: usize = 64;
+
+/// The amplification that is applied to an input before it is measured.
+///
+/// Smaller gains increase the range of voltages that can be measured, at the cost of precision.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Gain {
+    OneSixth,
+    OneFifth,
+    OneQuarter,
+    OneThird,
+    OneHalf,
+    One,
+    Two,
+    Four,
+}
+
+/// The voltage that inputs are measured against.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Reference {
+    /// A fixed reference voltage that is generated internally by the platform.
+    Internal,
+    /// A reference voltage that is derived from the supply voltage, so that readings are relative
+    /// to the supply, e.g. for potentiometers connected across the supply.
+    Supply,
+}
+
+/// The configuration of an analog input.
+///
+/// The default configuration uses the internal reference with the smallest gain.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Config {
+    pub gain: Gain,
+    pub reference: Reference,
+}
+
+/// A measurement of the voltage on an analog input.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Reading {
+    /// The value that was reported by the converter.
+    pub raw: i32,
+    /// The measured voltage, in millivolts.
+    pub millivolts: i32,
+}
+
+/// A buffer of consecutive samples of an analog input, that were taken at a fixed rate.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Buffer {
+    samples: [i16; BUFFER_LEN],
+    resolution: u32,
+    full_scale: u32,
+    overruns: u32,
+}
+
+/// A pin that can measure the voltage that is applied to it.
+pub trait AnalogInputPin {
+    /// The common error type for all analog input operations.
+    type Error;
+
+    /// Polls a measurement of this pin to completion.
+    fn poll_read(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<Reading, Self::Error>>;
+}
+
+/// Extension functions for instances of [`AnalogInputPin`].
+pub trait AnalogInputPinExt: AnalogInputPin {
+    /// Measures the voltage on this pin.
+    fn read(&mut self) -> read::Read<Self>
+    where
+        Self: Unpin,
+    {
+        read::read(self)
+    }
+}
+
+impl<A> AnalogInputPinExt for A where A: AnalogInputPin {}
+
+/// A pin that can be turned into an [`AnalogInputPin`].
+pub trait IntoAnalogInputPin: gpio::Pin {
+    /// The type of an [`AnalogInputPin`] that measures the voltage on this pin.
+    type AnalogInputPin: AnalogInputPin<Error = Self::Error> + Unpin;
+
+    /// Attempts to re-configure this pin into the new mode.
+    fn into_analog_input_pin(self, config: Config) -> Result<Self::AnalogInputPin, Self::Error>;
+}
+
+/// An analog input that is sampled continuously at a fixed rate.
+///
+/// Samplers fill one buffer while the previous one waits to be taken.  When a buffer is filled
+/// before the previous one was taken, the previous one is discarded, and the discarded buffers are
+/// reported by [`Buffer::overruns`] of the next buffer that is taken.
+pub trait Sampler {
+    /// The common error type for all sampling operations.
+    type Error;
+
+    /// Polls taking the next filled buffer.
+    ///
+    /// Sampling starts when the sampler is created, so the first buffer may already be waiting.
+    fn poll_buffer(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<Buffer, Self::Error>>;
+}
+
+/// Extension functions for instances of [`Sampler`].
+pub trait SamplerExt: Sampler {
+    /// Returns a stream of the buffers that this sampler fills.
+    fn buffers(&mut self) -> buffers::Buffers<Self>
+    where
+        Self: Unpin,
+    {
+        buffers::buffers(self)
+    }
+}
+
+impl<A> SamplerExt for A where A: Sampler {}
+
+/// A generator of sample triggers, that can sample analog inputs of type `P` continuously.
+pub trait IntoSampler<P> {
+    /// The error type of the resulting sampler.
+    type Error;
+    /// The type of the [`Sampler`] that samples the analog input.
+    type Sampler: Sampler<Error = Self::Error> + Unpin;
+
+    /// Starts sampling the analog input at the specified rate.
+    fn into_sampler(self, pin: P, rate: time::Rate) -> Result<Self::Sampler, Self::Error>;
+}
+
+impl Gain {
+    /// Returns the gain as a fraction of a numerator and a denominator.
+    pub fn ratio(self) -> (u32, u32) {
+        match self {
+            Gain::OneSixth => (1, 6),
+            Gain::OneFifth => (1, 5),
+            Gain::OneQuarter => (1, 4),
+            Gain::OneThird => (1, 3),
+            Gain::OneHalf => (1, 2),
+            Gain::One => (1, 1),
+            Gain::Two => (2, 1),
+            Gain::Four => (4, 1),
+        }
+    }
+}
+
+impl Config {
+    /// Returns the input voltage in millivolts that corresponds to the full range of the
+    /// converter, given the voltage of the selected reference.
+    pub fn full_scale(&self, reference_millivolts: u32) -> u32 {
+        let (numerator, denominator) = self.gain.ratio();
+        reference_millivolts * denominator / numerator
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let gain = Gain::OneSixth;
+        let reference = Reference::Internal;
+        Self { gain, reference }
+    }
+}
+
+impl Reading {
+    /// Creates a reading from a raw value of a converter with the specified resolution in bits,
+    /// whose full range corresponds to `full_scale` millivolts.
+    pub fn new(raw: i32, resolution: u32, full_scale: u32) -> Self {
+        let millivolts = (i64::from(raw) * i64::from(full_scale)) >> resolution;
+        let millivolts = millivolts as i32;
+        Self { raw, millivolts }
+    }
+}
+
+impl Buffer {
+    /// Creates a buffer from the raw values of a converter with the specified resolution in bits,
+    /// whose full range corresponds to `full_scale` millivolts.
+    ///
+    /// `overruns` is the number of buffers that were discarded right before this one.
+    pub fn new(
+        samples: [i16; BUFFER_LEN],
+        resolution: u32,
+        full_scale: u32,
+        overruns: u32,
+    ) -> Self {
+        Self {
+            samples,
+            resolution,
+            full_scale,
+            overruns,
+        }
+    }
+
+    /// The values that were reported by the converter, oldest first.
+    pub fn raw(&self) -> &[i16] {
+        &self.samples
+    }
+
+    /// The samples of this buffer as readings, oldest first.
+    pub fn readings(&self) -> Readings<'_> {
+        let samples = self.samples.iter();
+        let resolution = self.resolution;
+        let full_scale = self.full_scale;
+        Readings {
+            samples,
+            resolution,
+            full_scale,
+        }
+    }
+
+    /// The number of buffers that were discarded since the previous buffer was taken, because they
+    /// weren't taken in time.
+    pub fn overruns(&self) -> u32 {
+        self.overruns
+    }
+}
+
+/// An iterator over the samples of a [`Buffer`] as [`Reading`]s.
+#[derive(Clone, Debug)]
+pub struct Readings<'a> {
+    samples: slice::Iter<'a, i16>,
+    resolution: u32,
+    full_scale: u32,
+}
+
+impl Iterator for Readings<'_> {
+    type Item = Reading;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let raw = *self.samples.next()?;
+        Some(Reading::new(
+            i32::from(raw),
+            self.resolution,
+            self.full_scale,
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.samples.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Readings<'_> {}
@@ -0,0 +1,38 @@
+//! Defines a stream of the buffers that are filled by a sampler.
+use core::pin;
+use core::task;
+
+/// A stream of the buffers that a sampler fills.
+///
+/// The stream never ends.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct Buffers<'a, A>
+where
+    A: super::Sampler + Unpin + ?Sized,
+{
+    sampler: &'a mut A,
+}
+
+/// Creates a new [`Buffers`] stream for the provided sampler.
+pub fn buffers<A>(sampler: &mut A) -> Buffers<A>
+where
+    A: super::Sampler + Unpin + ?Sized,
+{
+    Buffers { sampler }
+}
+
+impl<A> futures::stream::Stream for Buffers<'_, A>
+where
+    A: super::Sampler + Unpin + ?Sized,
+{
+    type Item = Result<super::Buffer, A::Error>;
+
+    fn poll_next(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        pin::Pin::new(&mut *this.sampler).poll_buffer(cx).map(Some)
+    }
+}
@@ -0,0 +1,35 @@
+//! Defines futures for measuring the voltage on an analog input pin.
+use core::future;
+use core::pin;
+use core::task;
+
+/// A future which measures the voltage on an analog input pin.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Read<'a, A>
+where
+    A: super::AnalogInputPin + Unpin + ?Sized,
+{
+    pin: &'a mut A,
+}
+
+/// Creates a new [`Read`] for the provided analog input pin, that, when polled, will measure the
+/// voltage on it.
+pub fn read<A>(pin: &mut A) -> Read<A>
+where
+    A: super::AnalogInputPin + Unpin + ?Sized,
+{
+    Read { pin }
+}
+
+impl<A> future::Future for Read<'_, A>
+where
+    A: super::AnalogInputPin + Unpin + ?Sized,
+{
+    type Output = Result<super::Reading, A::Error>;
+
+    fn poll(mut self: pin::Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let this = &mut *self;
+        pin::Pin::new(&mut *this.pin).poll_read(cx)
+    }
+}
@@ -0,0 +1,23 @@
+//! Monotonic clocks, for timestamping events and measuring elapsed time.
+//!
+//! Unlike a [`Timer`](crate::timer::Timer), a clock can be shared freely, and reading it does not
+//! require exclusive access to any peripheral.
+use crate::time;
+use core::fmt;
+
+/// A clock that never goes backwards.
+pub trait Clock: fmt::Debug {
+    /// Returns the current time.
+    fn now(&self) -> time::Instant;
+}
+
+pub trait ClockExt: Clock {
+    /// Returns the time that has passed since the specified instant.
+    ///
+    /// Returns a zero duration if the instant is in the future.
+    fn elapsed(&self, since: time::Instant) -> time::Duration {
+        self.now().saturating_duration_since(since)
+    }
+}
+
+impl<A> ClockExt for A where A: Clock {}
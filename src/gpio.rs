@@ -1,16 +1,40 @@
 //! General input/output pins.
 //!
 //! The [`InputPin`] and [`OutputPin`] traits define pins that can be read and written digitally
-//! (i.e. either in a low or high state).
+//! (i.e. either in a low or high state).  Output pins that implement [`StatefulOutputPin`] can
+//! additionally read back the level that they are set to, and be toggled.
+//!
+//! Groups of pins, such as parallel buses, can be read and written as words using the
+//! [`InputPort`] and [`OutputPort`] traits.  Pins that are wired active-low can be wrapped in
+//! [`Inverted`], so that high always means active.  Some output pins support additional
+//! configuration, such as [`ConfigureDriveStrength`] and [`ConfigureOpenDrain`].
+//!
+//! Input pins that implement [`WaitInputPin`] can additionally be waited on until their level
+//! changes, e.g. using [`WaitInputPinExt::wait_for_rising_edge`] or
+//! [`WaitInputPinExt::changes`], without busy polling.
+//!
+//! Push buttons can be debounced using [`InputPinExt::debounce`], and turned into a stream of
+//! clicks and long presses using [`InputPinExt::button`].
 //!
 //! There are additionally various `Into*` traits that allow users to re-configure pins to switch
 //! between different modes of operation, e.g. [`IntoFloatingInputPin`] turns a pin into an
 //! [`InputPin`] that does not employ any pull-up or pull-down resistors.
+use crate::time;
+use crate::timer;
 use core::pin;
 use core::task;
 
+pub mod button;
+pub mod changes;
+pub mod debounce;
 pub mod get;
+pub mod is_set;
+pub mod read_word;
+pub mod sequential;
 pub mod set;
+pub mod toggle;
+pub mod wait;
+pub mod write_word;
 
 /// A generic pin that can't be interacted with.
 pub trait Pin {
@@ -38,10 +62,113 @@ pub trait InputPinExt: InputPin {
     {
         get::get(self)
     }
+
+    /// Filters out bounces of this pin, by sampling it with the specified timer and only accepting
+    /// levels that stay stable for the specified settle time.
+    fn debounce<T>(
+        self,
+        timer: T,
+        settle: time::Duration,
+    ) -> Result<debounce::Debounced<Self, T::PeriodicTimer>, Self::Error>
+    where
+        Self: Sized + Unpin,
+        T: timer::IntoPeriodicTimer<Error = Self::Error>,
+    {
+        debounce::Debounced::new(self, timer, settle)
+    }
+
+    /// Turns this pin into a stream of the clicks, double-clicks and long presses of the push
+    /// button that is connected to it, sampling it with the specified timer.
+    fn button<T>(
+        self,
+        timer: T,
+        config: button::Config,
+    ) -> Result<button::Button<Self, T::PeriodicTimer>, Self::Error>
+    where
+        Self: Sized + Unpin,
+        T: timer::IntoPeriodicTimer<Error = Self::Error>,
+    {
+        button::Button::new(self, timer, config)
+    }
 }
 
 impl<A> InputPinExt for A where A: InputPin {}
 
+/// An input pin that can wait for its level to change, typically using interrupts.
+pub trait WaitInputPin: InputPin {
+    /// Polls the start of listening for level changes, relative to the current level of this pin.
+    ///
+    /// Any changes that were detected earlier, but that haven't been returned by
+    /// [`WaitInputPin::poll_change`] yet, are discarded.
+    fn poll_listen(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>>;
+
+    /// Polls for the next level change of this pin, and returns the new level.
+    ///
+    /// Changes are detected in the background while the pin is listening, so that changes
+    /// happening between two calls aren't missed.  If the pin isn't listening yet, it starts
+    /// listening first.
+    fn poll_change(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>>;
+}
+
+/// Extension functions for instances of [`WaitInputPin`].
+pub trait WaitInputPinExt: WaitInputPin {
+    /// Waits until this pin is high, which might already be the case.
+    fn wait_for_high(&mut self) -> wait::WaitForLevel<Self>
+    where
+        Self: Unpin,
+    {
+        wait::wait_for_level(self, true)
+    }
+
+    /// Waits until this pin is low, which might already be the case.
+    fn wait_for_low(&mut self) -> wait::WaitForLevel<Self>
+    where
+        Self: Unpin,
+    {
+        wait::wait_for_level(self, false)
+    }
+
+    /// Waits until this pin goes from low to high.
+    fn wait_for_rising_edge(&mut self) -> wait::WaitForEdge<Self>
+    where
+        Self: Unpin,
+    {
+        wait::wait_for_edge(self, wait::Edge::Rising)
+    }
+
+    /// Waits until this pin goes from high to low.
+    fn wait_for_falling_edge(&mut self) -> wait::WaitForEdge<Self>
+    where
+        Self: Unpin,
+    {
+        wait::wait_for_edge(self, wait::Edge::Falling)
+    }
+
+    /// Waits until the level of this pin changes in either direction.
+    fn wait_for_any_edge(&mut self) -> wait::WaitForEdge<Self>
+    where
+        Self: Unpin,
+    {
+        wait::wait_for_edge(self, wait::Edge::Any)
+    }
+
+    /// Returns a stream of the levels that this pin changes to.
+    fn changes(&mut self) -> changes::Changes<Self>
+    where
+        Self: Unpin,
+    {
+        changes::changes(self)
+    }
+}
+
+impl<A> WaitInputPinExt for A where A: WaitInputPin {}
+
 /// A pin that can be written to.
 pub trait OutputPin: Pin {
     /// Polls a write operation of this pin to completion.
@@ -65,6 +192,148 @@ pub trait OutputPinExt: OutputPin {
 
 impl<A> OutputPinExt for A where A: OutputPin {}
 
+/// An output pin that can read back the level that it is driving.
+pub trait StatefulOutputPin: OutputPin {
+    /// Polls whether this pin is currently set to be high.
+    ///
+    /// This is the level that was last written to the pin, which might differ from the level that
+    /// can be measured on it, e.g. for an open drain pin that is pulled low externally.
+    fn poll_is_set_high(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>>;
+
+    /// Polls a toggle of this pin to completion, i.e. sets it to the opposite of the level that it
+    /// is currently set to.
+    fn poll_toggle(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        let high = futures::ready!(self.as_mut().poll_is_set_high(cx))?;
+        self.poll_set(cx, !high)
+    }
+}
+
+/// Extension functions for instances of [`StatefulOutputPin`].
+pub trait StatefulOutputPinExt: StatefulOutputPin {
+    /// Gets whether this pin is currently set to be high.
+    fn is_set_high(&mut self) -> is_set::IsSetHigh<Self>
+    where
+        Self: Unpin,
+    {
+        is_set::is_set_high(self)
+    }
+
+    /// Sets this pin to the opposite of the level that it is currently set to.
+    fn toggle(&mut self) -> toggle::Toggle<Self>
+    where
+        Self: Unpin,
+    {
+        toggle::toggle(self)
+    }
+}
+
+impl<A> StatefulOutputPinExt for A where A: StatefulOutputPin {}
+
+/// The strength that an output pin drives its levels with.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DriveStrength {
+    /// The default drive strength of the platform.
+    Standard,
+    /// A higher drive strength, e.g. for LEDs or long wires, at the cost of power consumption and
+    /// more electromagnetic interference.
+    High,
+}
+
+/// The level that an open drain output pin actively drives; the pin floats at the other level.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum OpenDrainFlavor {
+    /// The pin drives the low level, and relies on a pull-up resistor for the high level, as
+    /// e.g. on I²C buses.
+    DriveLow,
+    /// The pin drives the high level, and relies on a pull-down resistor for the low level.  This
+    /// is also known as open source.
+    DriveHigh,
+}
+
+/// An output pin whose drive strength can be configured.
+///
+/// The configuration is reset when the pin is re-configured using the `Into*` traits.
+pub trait ConfigureDriveStrength: OutputPin {
+    /// Sets the strength that this pin drives its levels with.
+    fn set_drive_strength(&mut self, strength: DriveStrength) -> Result<(), Self::Error>;
+}
+
+/// An open drain output pin whose flavor can be configured.
+///
+/// The configuration is reset when the pin is re-configured using the `Into*` traits.
+pub trait ConfigureOpenDrain: OutputPin {
+    /// Sets which level this pin actively drives.
+    fn set_open_drain_flavor(&mut self, flavor: OpenDrainFlavor) -> Result<(), Self::Error>;
+}
+
+/// A group of pins that can be read in one operation, as the bits of a word.
+///
+/// Bit `i` of the word is the level of the `i`th pin of the group.
+pub trait InputPort: Pin {
+    /// Polls a read of all pins of this group to completion.
+    fn poll_read_word(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<u32, Self::Error>>;
+}
+
+/// Extension functions for instances of [`InputPort`].
+pub trait InputPortExt: InputPort {
+    /// Reads the levels of all pins of this group.
+    fn read_word(&mut self) -> read_word::ReadWord<Self>
+    where
+        Self: Unpin,
+    {
+        read_word::read_word(self)
+    }
+}
+
+impl<A> InputPortExt for A where A: InputPort {}
+
+/// A group of pins that can be written in one operation, as the bits of a word.
+///
+/// Bit `i` of the word is the level of the `i`th pin of the group.  Implementations for pins that
+/// share a hardware port change all pins at once, whereas [`sequential::Sequential`] can group
+/// arbitrary pins, but changes them one after the other.
+pub trait OutputPort: Pin {
+    /// Polls a write of the pins of this group to completion.
+    ///
+    /// Only the pins whose bits are set in the mask are written; the others keep their level.
+    fn poll_write_word(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        mask: u32,
+        word: u32,
+    ) -> task::Poll<Result<(), Self::Error>>;
+}
+
+/// Extension functions for instances of [`OutputPort`].
+pub trait OutputPortExt: OutputPort {
+    /// Sets the levels of all pins of this group.
+    fn write_word(&mut self, word: u32) -> write_word::WriteWord<Self>
+    where
+        Self: Unpin,
+    {
+        write_word::write_word(self, !0, word)
+    }
+
+    /// Sets the levels of the pins of this group whose bits are set in the mask.
+    fn write_masked(&mut self, mask: u32, word: u32) -> write_word::WriteWord<Self>
+    where
+        Self: Unpin,
+    {
+        write_word::write_word(self, mask, word)
+    }
+}
+
+impl<A> OutputPortExt for A where A: OutputPort {}
+
 /// A pin that can be turned into an [`InputPin`] that does not employ any pull-up or pull-down
 /// resistors.
 pub trait IntoFloatingInputPin: Pin {
@@ -117,6 +386,217 @@ pub trait IntoPushPullOutputPin: Pin {
     ) -> Result<Self::PushPullOutputPin, Self::Error>;
 }
 
+/// A pin that can be turned into a type-erased pin, so that pins of different types can be stored
+/// together, e.g. in an array.
+///
+/// The mode of a type-erased pin is only known at runtime.  It can be re-configured using the
+/// `Into*` traits like any other pin, but using it in the wrong mode, e.g. writing to it while it
+/// is an input, fails with an error.
+pub trait Degrade: Pin {
+    /// The type of a type-erased pin.
+    type AnyPin: InputPin<Error = Self::Error> + OutputPin<Error = Self::Error> + Unpin;
+
+    /// Turns this pin into a type-erased pin, keeping its current mode.
+    fn degrade(self) -> Self::AnyPin;
+}
+
+/// An adapter for pins that are wired active-low, which inverts all levels that are read from or
+/// written to the underlying pin.
+///
+/// For example, an LED that is connected between a pin and the supply voltage is lit when the pin
+/// is low; wrapping the pin makes setting it high turn the LED on.  Re-configuring the pin using
+/// the `Into*` traits keeps it inverted.
+#[derive(Clone, Copy, Debug)]
+pub struct Inverted<P>(P);
+
+impl<P> Inverted<P> {
+    /// Creates a new inverted pin, wrapping the specified pin.
+    pub fn new(pin: P) -> Self {
+        Inverted(pin)
+    }
+
+    /// Takes apart this inverted pin, returning the underlying pin.
+    pub fn into_inner(self) -> P {
+        self.0
+    }
+}
+
+impl<P> Pin for Inverted<P>
+where
+    P: Pin,
+{
+    type Error = P::Error;
+}
+
+impl<P> InputPin for Inverted<P>
+where
+    P: InputPin + Unpin,
+{
+    fn poll_get(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>> {
+        pin::Pin::new(&mut self.0)
+            .poll_get(cx)
+            .map(|result| result.map(|high| !high))
+    }
+}
+
+impl<P> WaitInputPin for Inverted<P>
+where
+    P: WaitInputPin + Unpin,
+{
+    fn poll_listen(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        pin::Pin::new(&mut self.0).poll_listen(cx)
+    }
+
+    fn poll_change(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>> {
+        pin::Pin::new(&mut self.0)
+            .poll_change(cx)
+            .map(|result| result.map(|high| !high))
+    }
+}
+
+impl<P> OutputPin for Inverted<P>
+where
+    P: OutputPin + Unpin,
+{
+    fn poll_set(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        high: bool,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        pin::Pin::new(&mut self.0).poll_set(cx, !high)
+    }
+}
+
+impl<P> StatefulOutputPin for Inverted<P>
+where
+    P: StatefulOutputPin + Unpin,
+{
+    fn poll_is_set_high(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>> {
+        pin::Pin::new(&mut self.0)
+            .poll_is_set_high(cx)
+            .map(|result| result.map(|high| !high))
+    }
+
+    fn poll_toggle(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        pin::Pin::new(&mut self.0).poll_toggle(cx)
+    }
+}
+
+impl<P> ConfigureDriveStrength for Inverted<P>
+where
+    P: ConfigureDriveStrength + Unpin,
+{
+    fn set_drive_strength(&mut self, strength: DriveStrength) -> Result<(), Self::Error> {
+        self.0.set_drive_strength(strength)
+    }
+}
+
+impl<P> ConfigureOpenDrain for Inverted<P>
+where
+    P: ConfigureOpenDrain + Unpin,
+{
+    /// Sets which level this pin actively drives, as seen through the inversion: an inverted pin
+    /// that drives its low level drives the underlying pin high.
+    fn set_open_drain_flavor(&mut self, flavor: OpenDrainFlavor) -> Result<(), Self::Error> {
+        let flavor = match flavor {
+            OpenDrainFlavor::DriveLow => OpenDrainFlavor::DriveHigh,
+            OpenDrainFlavor::DriveHigh => OpenDrainFlavor::DriveLow,
+        };
+        self.0.set_open_drain_flavor(flavor)
+    }
+}
+
+impl<P> IntoFloatingInputPin for Inverted<P>
+where
+    P: IntoFloatingInputPin,
+{
+    type FloatingInputPin = Inverted<P::FloatingInputPin>;
+
+    fn into_floating_input_pin(self) -> Result<Self::FloatingInputPin, Self::Error> {
+        self.0.into_floating_input_pin().map(Inverted)
+    }
+}
+
+impl<P> IntoPullUpInputPin for Inverted<P>
+where
+    P: IntoPullUpInputPin,
+{
+    type PullUpInputPin = Inverted<P::PullUpInputPin>;
+
+    fn into_pull_up_input_pin(self) -> Result<Self::PullUpInputPin, Self::Error> {
+        self.0.into_pull_up_input_pin().map(Inverted)
+    }
+}
+
+impl<P> IntoPullDownInputPin for Inverted<P>
+where
+    P: IntoPullDownInputPin,
+{
+    type PullDownInputPin = Inverted<P::PullDownInputPin>;
+
+    fn into_pull_down_input_pin(self) -> Result<Self::PullDownInputPin, Self::Error> {
+        self.0.into_pull_down_input_pin().map(Inverted)
+    }
+}
+
+impl<P> IntoOpenDrainOutputPin for Inverted<P>
+where
+    P: IntoOpenDrainOutputPin,
+{
+    type OpenDrainOutputPin = Inverted<P::OpenDrainOutputPin>;
+
+    fn into_open_drain_output_pin(
+        self,
+        initial_high: bool,
+    ) -> Result<Self::OpenDrainOutputPin, Self::Error> {
+        self.0
+            .into_open_drain_output_pin(!initial_high)
+            .map(Inverted)
+    }
+}
+
+impl<P> IntoPushPullOutputPin for Inverted<P>
+where
+    P: IntoPushPullOutputPin,
+{
+    type PushPullOutputPin = Inverted<P::PushPullOutputPin>;
+
+    fn into_push_pull_output_pin(
+        self,
+        initial_high: bool,
+    ) -> Result<Self::PushPullOutputPin, Self::Error> {
+        self.0
+            .into_push_pull_output_pin(!initial_high)
+            .map(Inverted)
+    }
+}
+
+impl<P> Degrade for Inverted<P>
+where
+    P: Degrade,
+{
+    type AnyPin = Inverted<P::AnyPin>;
+
+    fn degrade(self) -> Self::AnyPin {
+        Inverted(self.0.degrade())
+    }
+}
+
 /// A virtual pin that is not actually connected to a physical pin.
 ///
 /// The pin will always read a fixed value, can be configured to be in any mode, and will always
@@ -140,7 +620,24 @@ impl InputPin for NoConnect {
         self: pin::Pin<&mut Self>,
         _cx: &mut task::Context<'_>,
     ) -> task::Poll<Result<bool, Self::Error>> {
-        task::Poll::Ready(Ok(false))
+        task::Poll::Ready(Ok(self.0))
+    }
+}
+
+impl WaitInputPin for NoConnect {
+    fn poll_listen(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_change(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>> {
+        // The level of a pin that isn't connected never changes.
+        task::Poll::Pending
     }
 }
 
@@ -154,6 +651,28 @@ impl OutputPin for NoConnect {
     }
 }
 
+impl StatefulOutputPin for NoConnect {
+    fn poll_is_set_high(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>> {
+        // Writes are no-ops, so the pin stays at the level that it always reads.
+        task::Poll::Ready(Ok(self.0))
+    }
+}
+
+impl ConfigureDriveStrength for NoConnect {
+    fn set_drive_strength(&mut self, _strength: DriveStrength) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl ConfigureOpenDrain for NoConnect {
+    fn set_open_drain_flavor(&mut self, _flavor: OpenDrainFlavor) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
 impl IntoFloatingInputPin for NoConnect {
     type FloatingInputPin = Self;
 
@@ -199,3 +718,11 @@ impl IntoPushPullOutputPin for NoConnect {
         Ok(self)
     }
 }
+
+impl Degrade for NoConnect {
+    type AnyPin = Self;
+
+    fn degrade(self) -> Self::AnyPin {
+        self
+    }
+}
@@ -0,0 +1,211 @@
+//! Recognition of clicks, double-clicks and long presses of push buttons.
+//!
+//! A [`Button`] samples an input pin periodically using a timer, and turns the presses and releases
+//! that it observes into a stream of [`Event`]s, according to the thresholds in its [`Config`].
+//! Sampling a pin every few milliseconds already filters out most contact bounce, but a
+//! [`Debounced`](super::debounce::Debounced) pin can be used for especially noisy buttons.
+use crate::time;
+use crate::timer;
+use core::pin;
+use core::task;
+
+/// A gesture that was recognized on a button.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Event {
+    /// The button was pressed and released once.
+    Click,
+    /// The button was clicked twice in quick succession.
+    DoubleClick,
+    /// The button has been held down for a long time.  This is reported while the button is still
+    /// held, and no click is reported when it is released afterwards.
+    LongPress,
+}
+
+/// The level that a button pin is at while the button is pressed.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Active {
+    /// The button pulls the pin low when pressed, e.g. towards ground against a pull-up resistor.
+    Low,
+    /// The button pulls the pin high when pressed.
+    High,
+}
+
+/// The thresholds that gestures are recognized with.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Config {
+    active: Active,
+    sample_period: time::Duration,
+    double_click: time::Duration,
+    long_press: time::Duration,
+}
+
+/// A stream of the gestures made on a button.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct Button<P, T> {
+    pin: P,
+    timer: T,
+    config: Config,
+    started: bool,
+    state: State,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum State {
+    Idle,
+    Pressed { clicks: u32, held: time::Duration },
+    Released { since: time::Duration },
+    LongPressed,
+}
+
+impl Config {
+    /// Creates a new configuration with default thresholds: the pin is sampled every 10 ms,
+    /// double-clicks have to follow within 300 ms, and long presses last 1 s.
+    pub fn new(active: Active) -> Self {
+        let sample_period = time::Duration::from_millis(10);
+        let double_click = time::Duration::from_millis(300);
+        let long_press = time::Duration::from_seconds(1);
+        Self {
+            active,
+            sample_period,
+            double_click,
+            long_press,
+        }
+    }
+
+    /// Sets how often the pin is sampled.
+    ///
+    /// Panics if the period is zero.
+    pub fn sample_period(mut self, period: time::Duration) -> Self {
+        assert!(!period.is_zero(), "sample period must be non-zero");
+        self.sample_period = period;
+        self
+    }
+
+    /// Sets how soon the second click of a double-click has to start after the first click.
+    ///
+    /// Clicks are only reported once this time has passed without another press, so a zero
+    /// duration disables double-clicks and makes clicks be reported right away.
+    pub fn double_click(mut self, window: time::Duration) -> Self {
+        self.double_click = window;
+        self
+    }
+
+    /// Sets how long the button has to be held down for a long press.
+    pub fn long_press(mut self, threshold: time::Duration) -> Self {
+        self.long_press = threshold;
+        self
+    }
+}
+
+impl<P, T> Button<P, T>
+where
+    P: super::InputPin + Unpin,
+    T: timer::Timer<Error = P::Error> + Unpin,
+{
+    /// Creates a new button from a pin and a timer that is used to sample the pin.
+    pub fn new<I>(pin: P, timer: I, config: Config) -> Result<Self, P::Error>
+    where
+        I: timer::IntoPeriodicTimer<PeriodicTimer = T, Error = P::Error>,
+    {
+        let timer = timer.into_periodic_timer(time::Rate::from_period(config.sample_period))?;
+        let started = false;
+        let state = State::Idle;
+        Ok(Self {
+            pin,
+            timer,
+            config,
+            started,
+            state,
+        })
+    }
+
+    /// Takes apart this button, returning the underlying pin and timer.
+    pub fn into_inner(self) -> (P, T) {
+        (self.pin, self.timer)
+    }
+
+    /// Advances the state machine by a sample of the pin, taken after the specified time.
+    fn update(&mut self, pressed: bool, elapsed: time::Duration) -> Option<Event> {
+        let config = self.config;
+        let (state, event) = match self.state {
+            State::Idle if pressed => {
+                let clicks = 0;
+                let held = time::Duration::ZERO;
+                (State::Pressed { clicks, held }, None)
+            }
+            State::Idle => (State::Idle, None),
+            State::Pressed { clicks, held } if pressed => {
+                let held = held.saturating_add(elapsed);
+                if held >= config.long_press {
+                    (State::LongPressed, Some(Event::LongPress))
+                } else {
+                    (State::Pressed { clicks, held }, None)
+                }
+            }
+            State::Pressed { clicks: 0, .. } if !config.double_click.is_zero() => {
+                let since = time::Duration::ZERO;
+                (State::Released { since }, None)
+            }
+            State::Pressed { clicks: 0, .. } => (State::Idle, Some(Event::Click)),
+            State::Pressed { .. } => (State::Idle, Some(Event::DoubleClick)),
+            State::Released { .. } if pressed => {
+                let clicks = 1;
+                let held = time::Duration::ZERO;
+                (State::Pressed { clicks, held }, None)
+            }
+            State::Released { since } => {
+                let since = since.saturating_add(elapsed);
+                if since >= config.double_click {
+                    (State::Idle, Some(Event::Click))
+                } else {
+                    (State::Released { since }, None)
+                }
+            }
+            State::LongPressed if pressed => (State::LongPressed, None),
+            State::LongPressed => (State::Idle, None),
+        };
+        self.state = state;
+        event
+    }
+}
+
+impl<P, T> futures::stream::Stream for Button<P, T>
+where
+    P: super::InputPin + Unpin,
+    T: timer::Timer<Error = P::Error> + Unpin,
+{
+    type Item = Result<Event, P::Error>;
+
+    fn poll_next(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        if !this.started {
+            if let Err(err) = futures::ready!(pin::Pin::new(&mut this.timer).poll_start(cx)) {
+                return task::Poll::Ready(Some(Err(err)));
+            }
+            this.started = true;
+        }
+
+        loop {
+            let ticks = match futures::ready!(pin::Pin::new(&mut this.timer).poll_ticks(cx)) {
+                Ok(ticks) => ticks,
+                Err(err) => return task::Poll::Ready(Some(Err(err))),
+            };
+            // Pins are expected to be readable right away, like the timer was just ready.
+            let high = match futures::ready!(pin::Pin::new(&mut this.pin).poll_get(cx)) {
+                Ok(high) => high,
+                Err(err) => return task::Poll::Ready(Some(Err(err))),
+            };
+
+            let pressed = high == (this.config.active == Active::High);
+            let elapsed = this.config.sample_period.saturating_mul(ticks);
+            if let Some(event) = this.update(pressed, elapsed) {
+                return task::Poll::Ready(Some(Ok(event)));
+            }
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//! Defines a stream of the level changes of a GPIO pin.
+use core::pin;
+use core::task;
+
+/// A stream of the levels that a GPIO pin changes to.
+///
+/// The stream starts listening for changes when it is first polled, and never ends.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct Changes<'a, A>
+where
+    A: super::WaitInputPin + Unpin + ?Sized,
+{
+    pin: &'a mut A,
+    listening: bool,
+}
+
+/// Creates a new [`Changes`] stream for the provided GPIO pin.
+pub fn changes<A>(pin: &mut A) -> Changes<A>
+where
+    A: super::WaitInputPin + Unpin + ?Sized,
+{
+    let listening = false;
+    Changes { pin, listening }
+}
+
+impl<A> futures::stream::Stream for Changes<'_, A>
+where
+    A: super::WaitInputPin + Unpin + ?Sized,
+{
+    type Item = Result<bool, A::Error>;
+
+    fn poll_next(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        if !this.listening {
+            if let Err(err) = futures::ready!(pin::Pin::new(&mut *this.pin).poll_listen(cx)) {
+                return task::Poll::Ready(Some(Err(err)));
+            }
+            this.listening = true;
+        }
+
+        pin::Pin::new(&mut *this.pin).poll_change(cx).map(Some)
+    }
+}
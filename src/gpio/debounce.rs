@@ -0,0 +1,181 @@
+//! Debouncing of noisy input pins, such as mechanical buttons.
+//!
+//! A [`Debounced`] pin samples the underlying pin periodically, and only accepts a new level once
+//! it has been read consistently for the whole settle time.  It implements [`InputPin`], which
+//! reads the stable level, and [`WaitInputPin`], so that
+//! [`WaitInputPinExt::changes`](super::WaitInputPinExt::changes) yields stable level changes.
+//!
+//! [`InputPin`]: super::InputPin
+//! [`WaitInputPin`]: super::WaitInputPin
+use crate::time;
+use crate::timer;
+use core::pin;
+use core::task;
+
+/// The number of samples that are taken during one settle time.
+const SAMPLES: u32 = 4;
+
+/// An input pin that filters out bounces of an underlying pin, using a periodic timer to sample
+/// it.
+#[derive(Debug)]
+pub struct Debounced<P, T> {
+    pin: P,
+    timer: T,
+    state: State,
+    /// The level that was last reported as a change.
+    reported: bool,
+    /// The number of consecutive samples that differed from the stable level.
+    count: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum State {
+    Unstarted,
+    Starting,
+    Idle(bool),
+    Sampling(bool),
+}
+
+impl<P, T> Debounced<P, T>
+where
+    P: super::InputPin + Unpin,
+    T: timer::Timer<Error = P::Error> + Unpin,
+{
+    /// Creates a new debounced pin, that accepts a level once it has been stable for the specified
+    /// settle time.
+    ///
+    /// The timer is turned into a periodic timer that samples the pin a few times per settle
+    /// time.  Sampling starts when the debounced pin is first polled.
+    pub fn new<I>(pin: P, timer: I, settle: time::Duration) -> Result<Self, P::Error>
+    where
+        I: timer::IntoPeriodicTimer<PeriodicTimer = T, Error = P::Error>,
+    {
+        let period = (settle / SAMPLES).max(time::Duration::from_nanos(1));
+        let timer = timer.into_periodic_timer(time::Rate::from_period(period))?;
+        let state = State::Unstarted;
+        let reported = false;
+        let count = 0;
+        Ok(Self {
+            pin,
+            timer,
+            state,
+            reported,
+            count,
+        })
+    }
+
+    /// Takes apart this debounced pin, returning the underlying pin and timer.
+    pub fn into_inner(self) -> (P, T) {
+        (self.pin, self.timer)
+    }
+
+    /// Starts sampling, if that hasn't happened yet, using the current level as the stable level.
+    fn poll_start(&mut self, cx: &mut task::Context<'_>) -> task::Poll<Result<(), P::Error>> {
+        loop {
+            match self.state {
+                State::Unstarted => {
+                    futures::ready!(pin::Pin::new(&mut self.timer).poll_start(cx))?;
+                    self.state = State::Starting;
+                }
+                State::Starting => {
+                    let high = futures::ready!(pin::Pin::new(&mut self.pin).poll_get(cx))?;
+                    self.state = State::Idle(high);
+                    self.reported = high;
+                }
+                State::Idle(_) | State::Sampling(_) => return task::Poll::Ready(Ok(())),
+            }
+        }
+    }
+
+    /// Waits for the next sample, and updates the stable level accordingly.
+    fn poll_sample(&mut self, cx: &mut task::Context<'_>) -> task::Poll<Result<(), P::Error>> {
+        if let State::Idle(stable) = self.state {
+            futures::ready!(pin::Pin::new(&mut self.timer).poll_tick(cx))?;
+            self.state = State::Sampling(stable);
+        }
+
+        if let State::Sampling(stable) = self.state {
+            let high = futures::ready!(pin::Pin::new(&mut self.pin).poll_get(cx))?;
+            self.count = if high == stable { 0 } else { self.count + 1 };
+            if self.count >= SAMPLES {
+                self.count = 0;
+                self.state = State::Idle(high);
+            } else {
+                self.state = State::Idle(stable);
+            }
+        }
+
+        task::Poll::Ready(Ok(()))
+    }
+
+    fn stable(&self) -> bool {
+        match self.state {
+            State::Idle(stable) | State::Sampling(stable) => stable,
+            State::Unstarted | State::Starting => unreachable!("debounced pin is not started"),
+        }
+    }
+}
+
+impl<P, T> super::Pin for Debounced<P, T>
+where
+    P: super::InputPin,
+{
+    type Error = P::Error;
+}
+
+impl<P, T> super::InputPin for Debounced<P, T>
+where
+    P: super::InputPin + Unpin,
+    T: timer::Timer<Error = P::Error> + Unpin,
+{
+    /// Reads the stable level of the pin, after taking all samples that are due into account.
+    fn poll_get(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>> {
+        let this = &mut *self;
+        futures::ready!(this.poll_start(cx))?;
+
+        loop {
+            match this.poll_sample(cx) {
+                task::Poll::Ready(Ok(())) => {}
+                task::Poll::Ready(Err(err)) => return task::Poll::Ready(Err(err)),
+                task::Poll::Pending => return task::Poll::Ready(Ok(this.stable())),
+            }
+        }
+    }
+}
+
+impl<P, T> super::WaitInputPin for Debounced<P, T>
+where
+    P: super::InputPin + Unpin,
+    T: timer::Timer<Error = P::Error> + Unpin,
+{
+    fn poll_listen(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        let this = &mut *self;
+        futures::ready!(this.poll_start(cx))?;
+        this.reported = this.stable();
+        task::Poll::Ready(Ok(()))
+    }
+
+    /// Polls for the next change of the stable level of the pin.
+    fn poll_change(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<bool, Self::Error>> {
+        let this = &mut *self;
+        futures::ready!(this.poll_start(cx))?;
+
+        loop {
+            let stable = this.stable();
+            if stable != this.reported {
+                this.reported = stable;
+                return task::Poll::Ready(Ok(stable));
+            }
+            futures::ready!(this.poll_sample(cx))?;
+        }
+    }
+}
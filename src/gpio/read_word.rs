@@ -0,0 +1,34 @@
+//! Defines futures for reading a group of GPIO pins.
+use core::future;
+use core::pin;
+use core::task;
+
+/// A future which reads the levels of a group of GPIO pins.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadWord<'a, A>
+where
+    A: super::InputPort + Unpin + ?Sized,
+{
+    port: &'a mut A,
+}
+
+/// Creates a new [`ReadWord`] for the provided group of GPIO pins.
+pub fn read_word<A>(port: &mut A) -> ReadWord<A>
+where
+    A: super::InputPort + Unpin + ?Sized,
+{
+    ReadWord { port }
+}
+
+impl<A> future::Future for ReadWord<'_, A>
+where
+    A: super::InputPort + Unpin + ?Sized,
+{
+    type Output = Result<u32, A::Error>;
+
+    fn poll(mut self: pin::Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let this = &mut *self;
+        pin::Pin::new(&mut *this.port).poll_read_word(cx)
+    }
+}
@@ -0,0 +1,239 @@
+//! A fallback for grouping arbitrary pins into ports.
+//!
+//! A [`Sequential`] group accesses its pins one after the other, so unlike ports that are backed
+//! by hardware, the pins of a group don't change at the same time while it is being written.  It
+//! can however group pins of different types, e.g. pins on different hardware ports, by using a
+//! tuple of pins, or an array for pins of the same type.
+use core::pin;
+use core::task;
+
+/// The largest number of pins in a group, i.e. the number of bits in a word.
+const MAX_PINS: usize = 32;
+
+/// A collection of pins that can be grouped into a [`Sequential`] group.
+pub trait Pins {
+    /// The common error type of the pins.
+    type Error;
+
+    /// Returns the number of pins in this collection.
+    fn len(&self) -> usize;
+
+    /// Returns whether this collection contains no pins.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A collection of input pins that can be grouped into a [`Sequential`] group.
+pub trait InputPins: Pins {
+    /// Returns the pin with the specified index.
+    fn input_pin(
+        &mut self,
+        index: usize,
+    ) -> &mut (dyn super::InputPin<Error = Self::Error> + Unpin + '_);
+}
+
+/// A collection of output pins that can be grouped into a [`Sequential`] group.
+pub trait OutputPins: Pins {
+    /// Returns the pin with the specified index.
+    fn output_pin(
+        &mut self,
+        index: usize,
+    ) -> &mut (dyn super::OutputPin<Error = Self::Error> + Unpin + '_);
+}
+
+/// A group of pins that are read or written one after the other.
+#[derive(Debug)]
+pub struct Sequential<T> {
+    pins: T,
+    /// The index of the next pin to access, if an operation has been interrupted.
+    next: usize,
+    /// The levels that have been read so far, if a read has been interrupted.
+    word: u32,
+}
+
+impl<T> Sequential<T>
+where
+    T: Pins,
+{
+    /// Creates a new group of the specified pins, where the first pin corresponds to the lowest
+    /// bit of words.
+    ///
+    /// Panics if there are more pins than bits in a word.
+    pub fn new(pins: T) -> Self {
+        assert!(
+            pins.len() <= MAX_PINS,
+            "a group can contain at most {} pins",
+            MAX_PINS
+        );
+        let next = 0;
+        let word = 0;
+        Self { pins, next, word }
+    }
+
+    /// Takes apart this group, returning the underlying pins.
+    pub fn into_inner(self) -> T {
+        self.pins
+    }
+}
+
+impl<T> super::Pin for Sequential<T>
+where
+    T: Pins,
+{
+    type Error = T::Error;
+}
+
+impl<T> super::InputPort for Sequential<T>
+where
+    T: InputPins + Unpin,
+{
+    fn poll_read_word(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<u32, Self::Error>> {
+        let this = &mut *self;
+
+        while this.next < this.pins.len() {
+            let index = this.next;
+            let high = match pin::Pin::new(this.pins.input_pin(index)).poll_get(cx) {
+                task::Poll::Ready(Ok(high)) => high,
+                task::Poll::Ready(Err(err)) => {
+                    this.next = 0;
+                    this.word = 0;
+                    return task::Poll::Ready(Err(err));
+                }
+                task::Poll::Pending => return task::Poll::Pending,
+            };
+            this.word |= u32::from(high) << index;
+            this.next += 1;
+        }
+
+        let word = this.word;
+        this.next = 0;
+        this.word = 0;
+        task::Poll::Ready(Ok(word))
+    }
+}
+
+impl<T> super::OutputPort for Sequential<T>
+where
+    T: OutputPins + Unpin,
+{
+    fn poll_write_word(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        mask: u32,
+        word: u32,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        let this = &mut *self;
+
+        while this.next < this.pins.len() {
+            let index = this.next;
+            if mask & 1 << index != 0 {
+                let high = word & 1 << index != 0;
+                match pin::Pin::new(this.pins.output_pin(index)).poll_set(cx, high) {
+                    task::Poll::Ready(Ok(())) => {}
+                    task::Poll::Ready(Err(err)) => {
+                        this.next = 0;
+                        return task::Poll::Ready(Err(err));
+                    }
+                    task::Poll::Pending => return task::Poll::Pending,
+                }
+            }
+            this.next += 1;
+        }
+
+        this.next = 0;
+        task::Poll::Ready(Ok(()))
+    }
+}
+
+impl<P, const N: usize> Pins for [P; N]
+where
+    P: super::Pin,
+{
+    type Error = P::Error;
+
+    fn len(&self) -> usize {
+        N
+    }
+}
+
+impl<P, const N: usize> InputPins for [P; N]
+where
+    P: super::InputPin + Unpin,
+{
+    fn input_pin(
+        &mut self,
+        index: usize,
+    ) -> &mut (dyn super::InputPin<Error = Self::Error> + Unpin + '_) {
+        &mut self[index]
+    }
+}
+
+impl<P, const N: usize> OutputPins for [P; N]
+where
+    P: super::OutputPin + Unpin,
+{
+    fn output_pin(
+        &mut self,
+        index: usize,
+    ) -> &mut (dyn super::OutputPin<Error = Self::Error> + Unpin + '_) {
+        &mut self[index]
+    }
+}
+
+macro_rules! tuple_pins {
+    ($len:expr => $($typ:ident @ $index:tt,)*) => {
+        impl<E, $($typ),*> Pins for ($($typ,)*)
+        where
+            $($typ: super::Pin<Error = E>,)*
+        {
+            type Error = E;
+
+            fn len(&self) -> usize {
+                $len
+            }
+        }
+
+        impl<E, $($typ),*> InputPins for ($($typ,)*)
+        where
+            $($typ: super::InputPin<Error = E> + Unpin,)*
+        {
+            fn input_pin(
+                &mut self,
+                index: usize,
+            ) -> &mut (dyn super::InputPin<Error = Self::Error> + Unpin + '_) {
+                match index {
+                    $($index => &mut self.$index,)*
+                    _ => panic!("pin index out of range: {}", index),
+                }
+            }
+        }
+
+        impl<E, $($typ),*> OutputPins for ($($typ,)*)
+        where
+            $($typ: super::OutputPin<Error = E> + Unpin,)*
+        {
+            fn output_pin(
+                &mut self,
+                index: usize,
+            ) -> &mut (dyn super::OutputPin<Error = Self::Error> + Unpin + '_) {
+                match index {
+                    $($index => &mut self.$index,)*
+                    _ => panic!("pin index out of range: {}", index),
+                }
+            }
+        }
+    };
+}
+
+tuple_pins!(1 => A @ 0,);
+tuple_pins!(2 => A @ 0, B @ 1,);
+tuple_pins!(3 => A @ 0, B @ 1, C @ 2,);
+tuple_pins!(4 => A @ 0, B @ 1, C @ 2, D @ 3,);
+tuple_pins!(5 => A @ 0, B @ 1, C @ 2, D @ 3, F @ 4,);
+tuple_pins!(6 => A @ 0, B @ 1, C @ 2, D @ 3, F @ 4, G @ 5,);
+tuple_pins!(7 => A @ 0, B @ 1, C @ 2, D @ 3, F @ 4, G @ 5, H @ 6,);
+tuple_pins!(8 => A @ 0, B @ 1, C @ 2, D @ 3, F @ 4, G @ 5, H @ 6, I @ 7,);
@@ -0,0 +1,35 @@
+//! Defines futures for toggling a GPIO pin.
+use core::future;
+use core::pin;
+use core::task;
+
+/// A future which toggles a GPIO pin.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Toggle<'a, A>
+where
+    A: super::StatefulOutputPin + Unpin + ?Sized,
+{
+    pin: &'a mut A,
+}
+
+/// Creates a new [`Toggle`] for the provided GPIO pin, that, when polled, will drive it to the
+/// opposite of the level that it is currently set to.
+pub fn toggle<A>(pin: &mut A) -> Toggle<A>
+where
+    A: super::StatefulOutputPin + Unpin + ?Sized,
+{
+    Toggle { pin }
+}
+
+impl<A> future::Future for Toggle<'_, A>
+where
+    A: super::StatefulOutputPin + Unpin + ?Sized,
+{
+    type Output = Result<(), A::Error>;
+
+    fn poll(mut self: pin::Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let this = &mut *self;
+        pin::Pin::new(&mut *this.pin).poll_toggle(cx)
+    }
+}
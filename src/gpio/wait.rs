@@ -0,0 +1,135 @@
+//! Defines futures for waiting on the level of a GPIO pin.
+use core::future;
+use core::pin;
+use core::task;
+
+/// A direction in which the level of a GPIO pin can change.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Edge {
+    /// The pin goes from low to high.
+    Rising,
+    /// The pin goes from high to low.
+    Falling,
+    /// The pin changes in either direction.
+    Any,
+}
+
+/// A future which waits until a GPIO pin is at a certain level.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WaitForLevel<'a, A>
+where
+    A: super::WaitInputPin + Unpin + ?Sized,
+{
+    pin: &'a mut A,
+    high: bool,
+    state: LevelState,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum LevelState {
+    Listen,
+    Check,
+    Wait,
+}
+
+/// A future which waits until a GPIO pin changes level in a certain direction.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WaitForEdge<'a, A>
+where
+    A: super::WaitInputPin + Unpin + ?Sized,
+{
+    pin: &'a mut A,
+    edge: Edge,
+    listening: bool,
+}
+
+/// Creates a new [`WaitForLevel`] for the provided GPIO pin, that completes once the pin is at the
+/// specified high or low level.
+pub fn wait_for_level<A>(pin: &mut A, high: bool) -> WaitForLevel<A>
+where
+    A: super::WaitInputPin + Unpin + ?Sized,
+{
+    let state = LevelState::Listen;
+    WaitForLevel { pin, high, state }
+}
+
+/// Creates a new [`WaitForEdge`] for the provided GPIO pin, that completes once the pin changes
+/// level in the specified direction.
+///
+/// Only changes that happen after the future is first polled are taken into account.
+pub fn wait_for_edge<A>(pin: &mut A, edge: Edge) -> WaitForEdge<A>
+where
+    A: super::WaitInputPin + Unpin + ?Sized,
+{
+    let listening = false;
+    WaitForEdge {
+        pin,
+        edge,
+        listening,
+    }
+}
+
+impl<A> future::Future for WaitForLevel<'_, A>
+where
+    A: super::WaitInputPin + Unpin + ?Sized,
+{
+    type Output = Result<(), A::Error>;
+
+    fn poll(mut self: pin::Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let this = &mut *self;
+
+        loop {
+            match this.state {
+                LevelState::Listen => {
+                    // Start listening before checking the level, so that no change can slip
+                    // through in between.
+                    futures::ready!(pin::Pin::new(&mut *this.pin).poll_listen(cx))?;
+                    this.state = LevelState::Check;
+                }
+                LevelState::Check => {
+                    let high = futures::ready!(pin::Pin::new(&mut *this.pin).poll_get(cx))?;
+                    if high == this.high {
+                        return task::Poll::Ready(Ok(()));
+                    }
+                    this.state = LevelState::Wait;
+                }
+                LevelState::Wait => {
+                    let high = futures::ready!(pin::Pin::new(&mut *this.pin).poll_change(cx))?;
+                    if high == this.high {
+                        return task::Poll::Ready(Ok(()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<A> future::Future for WaitForEdge<'_, A>
+where
+    A: super::WaitInputPin + Unpin + ?Sized,
+{
+    type Output = Result<(), A::Error>;
+
+    fn poll(mut self: pin::Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let this = &mut *self;
+
+        if !this.listening {
+            futures::ready!(pin::Pin::new(&mut *this.pin).poll_listen(cx))?;
+            this.listening = true;
+        }
+
+        loop {
+            let high = futures::ready!(pin::Pin::new(&mut *this.pin).poll_change(cx))?;
+            let done = match this.edge {
+                Edge::Rising => high,
+                Edge::Falling => !high,
+                Edge::Any => true,
+            };
+            if done {
+                return task::Poll::Ready(Ok(()));
+            }
+        }
+    }
+}
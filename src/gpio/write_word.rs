@@ -0,0 +1,37 @@
+//! Defines futures for writing a group of GPIO pins.
+use core::future;
+use core::pin;
+use core::task;
+
+/// A future which sets the levels of a group of GPIO pins.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct WriteWord<'a, A>
+where
+    A: super::OutputPort + Unpin + ?Sized,
+{
+    port: &'a mut A,
+    mask: u32,
+    word: u32,
+}
+
+/// Creates a new [`WriteWord`] for the provided group of GPIO pins, that, when polled, will drive
+/// the pins selected by the mask to the levels of the corresponding bits of the word.
+pub fn write_word<A>(port: &mut A, mask: u32, word: u32) -> WriteWord<A>
+where
+    A: super::OutputPort + Unpin + ?Sized,
+{
+    WriteWord { port, mask, word }
+}
+
+impl<A> future::Future for WriteWord<'_, A>
+where
+    A: super::OutputPort + Unpin + ?Sized,
+{
+    type Output = Result<(), A::Error>;
+
+    fn poll(mut self: pin::Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let this = &mut *self;
+        pin::Pin::new(&mut *this.port).poll_write_word(cx, this.mask, this.word)
+    }
+}
@@ -24,8 +24,8 @@
 //!     (e.g. `Option::take`).  This wastes a dozen or so instructions at startup, which is a
 //!     one-time cost.
 //!   * All APIs are async-first, so that code won't have to block and we can be power efficient.
-//!     This does require an executor, and one can be made that doesn't require `alloc`, yet to be
-//!     written.
+//!     This does require an executor; the one in [`executor`] doesn't require `alloc`, and is used
+//!     by the platform crates to run the application and any tasks that it spawns.
 //!   * The crate uses its own HAL-like traits for e.g. `OutputPin` or `I2cRead` to enable async
 //!     APIs as well as smooth over any incompatibilities between `embedded_hal::gpio::v1` and
 //!     `embedded_hal::gpio::v2` etc.
@@ -82,15 +82,19 @@
 )]
 #![forbid(unsafe_code)]
 
+pub mod adc;
+pub mod clock;
 pub mod gpio;
 pub mod i2c;
 pub mod io;
 pub mod platform;
 pub mod prelude;
+pub mod pwm;
 pub mod serial;
 pub mod specs;
 pub mod spi;
 pub mod time;
 pub mod timer;
 
+pub use core::executor;
 pub use platform::Platform;
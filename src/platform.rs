@@ -1,18 +1,29 @@
+use crate::clock;
+use core::executor;
 use core::fmt;
 use core::future;
 use core::task;
 
 pub mod initialize;
+pub mod policy;
 
 pub trait Platform: fmt::Debug + Sized {
     type Error;
+    type Clock: clock::Clock + Clone;
 
-    fn main<I, F>(run: I) -> !
+    /// Initializes the platform and runs the application on it, forever.
+    ///
+    /// Errors from initializing the platform or from the application are handled according to the
+    /// specified policy.  The application closure is called again whenever the policy retries.
+    fn main<I, F>(policy: policy::Policy<Self::Error>, run: I) -> !
     where
-        I: FnOnce(Self) -> F,
+        I: FnMut(Self, executor::Spawner) -> F,
         F: future::Future<Output = Result<(), Self::Error>>;
 
     fn poll_initialize(cx: &mut task::Context<'_>) -> task::Poll<Result<Self, Self::Error>>;
+
+    /// Returns a handle to the monotonic clock of this platform.
+    fn clock(&self) -> Self::Clock;
 }
 
 pub trait PlatformExt: Platform {
@@ -0,0 +1,207 @@
+//! Policies for handling errors that make it all the way up to [`Platform::main`].
+//!
+//! A [`Policy`] decides what happens when the platform fails to initialize, or when the
+//! application returns an error: errors are first reported to an optional sink, and then the
+//! configured [`Action`] is taken.  The decision logic lives in [`supervise`], which is generic
+//! over the platform so that it can be exercised against a simulated or mocked platform.
+//!
+//! [`Platform::main`]: super::Platform::main
+use crate::platform;
+use crate::time;
+use core::executor;
+use core::fmt;
+use core::future;
+
+/// The stage of the application lifecycle that an error happened in.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Stage {
+    /// The platform failed to initialize.
+    Initialize,
+    /// The application returned an error.
+    Run,
+}
+
+/// What to do after an error has happened.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Action {
+    /// Re-initialize the platform and run the application again, up to the specified number of
+    /// consecutive times.  Once the retries are used up, the application halts.
+    Retry(u32),
+    /// Reset the whole system.
+    Reset,
+    /// Stop running the application.  Platforms indicate the stage that failed using an
+    /// indicator pattern, e.g. by blinking the main LED.
+    Halt,
+}
+
+/// The final result of supervising an application, which the platform acts on.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Outcome {
+    /// The application completed successfully; spawned tasks should keep running.
+    Completed,
+    /// The system should be reset.
+    Reset,
+    /// The application should be halted because of an error in the specified stage.
+    Halt(Stage),
+}
+
+/// A policy for handling initialization and application errors.
+///
+/// The default policy halts on any error without reporting it anywhere, and retries immediately.
+pub struct Policy<E> {
+    on_initialize_error: Action,
+    on_run_error: Action,
+    backoff: time::Duration,
+    sink: Option<fn(Stage, &E)>,
+}
+
+impl<E> Policy<E> {
+    /// Creates a policy that halts on any error.
+    pub const fn new() -> Self {
+        let on_initialize_error = Action::Halt;
+        let on_run_error = Action::Halt;
+        let backoff = time::Duration::ZERO;
+        let sink = None;
+        Self {
+            on_initialize_error,
+            on_run_error,
+            backoff,
+            sink,
+        }
+    }
+
+    /// Sets the action to take when the platform fails to initialize.
+    pub const fn on_initialize_error(mut self, action: Action) -> Self {
+        self.on_initialize_error = action;
+        self
+    }
+
+    /// Sets the action to take when the application returns an error.
+    pub const fn on_run_error(mut self, action: Action) -> Self {
+        self.on_run_error = action;
+        self
+    }
+
+    /// Sets the delay before the first retry of a failed stage.
+    ///
+    /// The delay doubles with every further consecutive failure of the same stage, saturating at
+    /// the largest representable duration.
+    pub const fn backoff(mut self, delay: time::Duration) -> Self {
+        self.backoff = delay;
+        self
+    }
+
+    /// Registers a sink that every error is reported to, before any action is taken.
+    pub const fn report_to(mut self, sink: fn(Stage, &E)) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// Returns the delay before retrying, or the outcome if the policy doesn't retry.
+    fn handle(&self, stage: Stage, error: &E, failures: u32) -> Result<time::Duration, Outcome> {
+        if let Some(sink) = self.sink {
+            sink(stage, error);
+        }
+
+        let action = match stage {
+            Stage::Initialize => self.on_initialize_error,
+            Stage::Run => self.on_run_error,
+        };
+
+        match action {
+            Action::Retry(retries) if failures <= retries => Ok(self.delay(failures)),
+            Action::Retry(_) | Action::Halt => Err(Outcome::Halt(stage)),
+            Action::Reset => Err(Outcome::Reset),
+        }
+    }
+
+    fn delay(&self, failures: u32) -> time::Duration {
+        let factor = 1u32.checked_shl(failures - 1).unwrap_or(u32::MAX);
+        self.backoff.saturating_mul(factor)
+    }
+}
+
+impl<E> Clone for Policy<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E> Copy for Policy<E> {}
+
+impl<E> Default for Policy<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> fmt::Debug for Policy<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Policy")
+            .field("on_initialize_error", &self.on_initialize_error)
+            .field("on_run_error", &self.on_run_error)
+            .field("backoff", &self.backoff)
+            .field("sink", &self.sink.is_some())
+            .finish()
+    }
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::Initialize => f.write_str("initialize"),
+            Stage::Run => f.write_str("run"),
+        }
+    }
+}
+
+/// Initializes the platform and runs the application, applying the policy to any errors.
+///
+/// Retries count consecutive failures of the same stage; a successful initialization resets the
+/// count for initialization errors.  Each retry of the application re-initializes the platform.
+///
+/// Before every retry, the future returned by `delay` is awaited with the backoff delay of the
+/// policy, unless that delay is zero.
+pub async fn supervise<P, I, F, D, G>(
+    policy: Policy<P::Error>,
+    spawner: executor::Spawner,
+    mut run: I,
+    mut delay: D,
+) -> Outcome
+where
+    P: platform::Platform,
+    I: FnMut(P, executor::Spawner) -> F,
+    F: future::Future<Output = Result<(), P::Error>>,
+    D: FnMut(time::Duration) -> G,
+    G: future::Future<Output = ()>,
+{
+    use platform::PlatformExt;
+
+    let mut initialize_failures = 0;
+    let mut run_failures = 0;
+
+    loop {
+        let result = match P::initialize().await {
+            Ok(platform) => {
+                initialize_failures = 0;
+                match run(platform, spawner).await {
+                    Ok(()) => return Outcome::Completed,
+                    Err(error) => {
+                        run_failures += 1;
+                        policy.handle(Stage::Run, &error, run_failures)
+                    }
+                }
+            }
+            Err(error) => {
+                initialize_failures += 1;
+                policy.handle(Stage::Initialize, &error, initialize_failures)
+            }
+        };
+
+        match result {
+            Ok(backoff) if backoff.is_zero() => {}
+            Ok(backoff) => delay(backoff).await,
+            Err(outcome) => return outcome,
+        }
+    }
+}
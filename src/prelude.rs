@@ -1,10 +1,23 @@
+pub use crate::adc::AnalogInputPinExt;
+pub use crate::adc::IntoAnalogInputPin;
+pub use crate::adc::IntoSampler;
+pub use crate::adc::SamplerExt;
+pub use crate::clock::Clock;
+pub use crate::clock::ClockExt;
+pub use crate::gpio::ConfigureDriveStrength;
+pub use crate::gpio::ConfigureOpenDrain;
+pub use crate::gpio::Degrade;
 pub use crate::gpio::InputPinExt;
+pub use crate::gpio::InputPortExt;
 pub use crate::gpio::IntoFloatingInputPin;
 pub use crate::gpio::IntoOpenDrainOutputPin;
 pub use crate::gpio::IntoPullDownInputPin;
 pub use crate::gpio::IntoPullUpInputPin;
 pub use crate::gpio::IntoPushPullOutputPin;
 pub use crate::gpio::OutputPinExt;
+pub use crate::gpio::OutputPortExt;
+pub use crate::gpio::StatefulOutputPinExt;
+pub use crate::gpio::WaitInputPinExt;
 pub use crate::i2c::I2cBusMappingExt;
 pub use crate::i2c::I2cReadExt;
 pub use crate::i2c::I2cWriteExt;
@@ -12,8 +25,13 @@ pub use crate::io::ReadExt;
 pub use crate::io::WriteExt;
 pub use crate::platform::Platform;
 pub use crate::platform::PlatformExt;
-pub use crate::time::F32Ext;
+pub use crate::pwm::IntoPwmPin;
+pub use crate::pwm::PwmPinExt;
 pub use crate::time::U32Ext;
+pub use crate::timer::timeout::WithTimeout;
+pub use crate::timer::CancellableTimerExt;
+pub use crate::timer::CountingTimerExt;
 pub use crate::timer::IntoOneshotTimer;
 pub use crate::timer::IntoPeriodicTimer;
+pub use crate::timer::RestartableTimerExt;
 pub use crate::timer::TimerExt;
@@ -0,0 +1,170 @@
+//! Pulse-width modulated outputs.
+//!
+//! A [`PwmPin`] switches between its active and inactive level at a fixed rate, and spends a
+//! [`Duty`] cycle's fraction of each period at the active level, e.g. to dim an LED or to position
+//! a servo.  PWM pins are created from a pin and a generator that implements [`IntoPwmPin`], which
+//! is usually a PWM peripheral of the platform.
+//!
+//! Pins without a PWM peripheral can be modulated in software using a timer; see the [`software`]
+//! module.
+use crate::time;
+use core::pin;
+use core::task;
+
+pub mod set_duty;
+pub mod software;
+
+/// The fraction of each period that a PWM output spends at its active level.
+///
+/// The fraction is stored with 16 bits of precision, which platforms scale to the resolution of
+/// their hardware.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Duty(u16);
+
+/// The level that a PWM output has during the active part of each period.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Polarity {
+    /// The output is high while active, and low for the rest of the period.
+    ActiveHigh,
+    /// The output is low while active, and high for the rest of the period.
+    ActiveLow,
+}
+
+/// The configuration of a PWM output.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Config {
+    rate: time::Rate,
+    polarity: Polarity,
+    duty: Duty,
+}
+
+/// A pin that outputs a pulse-width modulated signal.
+pub trait PwmPin {
+    /// The common error type for all PWM operations.
+    type Error;
+
+    /// Polls changing the duty cycle of this pin to completion.
+    ///
+    /// The new duty cycle takes effect at the start of the next period, so that no period is cut
+    /// short.
+    fn poll_set_duty(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        duty: Duty,
+    ) -> task::Poll<Result<(), Self::Error>>;
+}
+
+/// Extension functions for instances of [`PwmPin`].
+pub trait PwmPinExt: PwmPin {
+    /// Changes the duty cycle of this pin.
+    fn set_duty(&mut self, duty: Duty) -> set_duty::SetDuty<Self>
+    where
+        Self: Unpin,
+    {
+        set_duty::set_duty(self, duty)
+    }
+}
+
+impl<A> PwmPinExt for A where A: PwmPin {}
+
+/// A generator of PWM signals, that can modulate pins of type `P`.
+pub trait IntoPwmPin<P> {
+    /// The error type of the resulting pin.
+    type Error;
+    /// The type of the [`PwmPin`] that modulates the pin.
+    type PwmPin: PwmPin<Error = Self::Error> + Unpin;
+
+    /// Starts modulating the pin as configured.
+    fn into_pwm_pin(self, pin: P, config: Config) -> Result<Self::PwmPin, Self::Error>;
+}
+
+impl Duty {
+    /// The largest number that fractions are expressed as a multiple of.
+    const MAX: u16 = u16::MAX;
+
+    /// A duty cycle that keeps the output inactive.
+    pub const ZERO: Self = Duty(0);
+
+    /// A duty cycle that keeps the output active.
+    pub const FULL: Self = Duty(Self::MAX);
+
+    /// Creates a duty cycle from a fraction.
+    ///
+    /// Panics if the denominator is zero or less than the numerator.
+    pub fn from_ratio(numerator: u32, denominator: u32) -> Self {
+        assert!(
+            denominator != 0 && numerator <= denominator,
+            "duty cycle must be between zero and one"
+        );
+        let duty = u64::from(numerator) * u64::from(Self::MAX) / u64::from(denominator);
+        Duty(duty as u16)
+    }
+
+    /// Creates a duty cycle from a percentage.
+    ///
+    /// Panics if the percentage is greater than 100.
+    pub fn from_percent(percent: u8) -> Self {
+        Self::from_ratio(u32::from(percent), 100)
+    }
+
+    /// Returns the share of `total` that this duty cycle amounts to, rounded to the nearest
+    /// integer.
+    ///
+    /// This is what hardware compare registers need to be programmed with, when their counter
+    /// counts to `total` once per period.
+    pub fn scale(self, total: u64) -> u64 {
+        let max = u128::from(Self::MAX);
+        ((u128::from(total) * u128::from(self.0) + max / 2) / max) as u64
+    }
+
+    /// Returns whether this duty cycle keeps the output inactive.
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns whether this duty cycle keeps the output active.
+    pub fn is_full(self) -> bool {
+        self.0 == Self::MAX
+    }
+}
+
+impl Config {
+    /// Creates a new configuration for an output with the specified rate, which starts out
+    /// active-high and inactive.
+    pub fn new(rate: time::Rate) -> Self {
+        let polarity = Polarity::ActiveHigh;
+        let duty = Duty::ZERO;
+        Self {
+            rate,
+            polarity,
+            duty,
+        }
+    }
+
+    /// Sets the level that the output has while it is active.
+    pub fn polarity(mut self, polarity: Polarity) -> Self {
+        self.polarity = polarity;
+        self
+    }
+
+    /// Sets the duty cycle that the output starts out with.
+    pub fn duty(mut self, duty: Duty) -> Self {
+        self.duty = duty;
+        self
+    }
+
+    /// The rate at which periods of the output start.
+    pub fn rate(&self) -> time::Rate {
+        self.rate
+    }
+
+    /// Whether the output is high while it is active.
+    pub fn is_active_high(&self) -> bool {
+        self.polarity == Polarity::ActiveHigh
+    }
+
+    /// The duty cycle that the output starts out with.
+    pub fn initial_duty(&self) -> Duty {
+        self.duty
+    }
+}
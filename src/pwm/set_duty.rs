@@ -0,0 +1,36 @@
+//! Defines futures for changing the duty cycle of a PWM pin.
+use core::future;
+use core::pin;
+use core::task;
+
+/// A future which changes the duty cycle of a PWM pin.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct SetDuty<'a, A>
+where
+    A: super::PwmPin + Unpin + ?Sized,
+{
+    pin: &'a mut A,
+    duty: super::Duty,
+}
+
+/// Creates a new [`SetDuty`] for the provided PWM pin, that, when polled, will change its duty
+/// cycle to the specified one.
+pub fn set_duty<A>(pin: &mut A, duty: super::Duty) -> SetDuty<A>
+where
+    A: super::PwmPin + Unpin + ?Sized,
+{
+    SetDuty { pin, duty }
+}
+
+impl<A> future::Future for SetDuty<'_, A>
+where
+    A: super::PwmPin + Unpin + ?Sized,
+{
+    type Output = Result<(), A::Error>;
+
+    fn poll(mut self: pin::Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let this = &mut *self;
+        pin::Pin::new(&mut *this.pin).poll_set_duty(cx, this.duty)
+    }
+}
@@ -0,0 +1,226 @@
+//! PWM outputs that are switched in software.
+//!
+//! A [`Generator`] turns any output pin into a PWM pin, by using a timer to switch the pin at the
+//! right times.  The resulting [`Pwm`] is a stream that yields once per period, and the signal only
+//! advances while the stream is polled, so it is best suited for slow signals such as dimming an
+//! LED; the timing is only as accurate as the executor is responsive.
+use crate::gpio;
+use crate::time;
+use crate::timer;
+use core::pin;
+use core::task;
+
+/// A generator of PWM signals that switches pins in software using a timer.
+#[derive(Debug)]
+pub struct Generator<T>(T);
+
+/// A PWM output that is switched in software, and a stream of its periods.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct Pwm<P, T> {
+    pin: P,
+    timer: T,
+    period: time::Duration,
+    active_high: bool,
+    duty: super::Duty,
+    /// The level that the pin was last set to, if known.
+    level: Option<bool>,
+    state: State,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum State {
+    /// A new period is about to begin.
+    Start,
+    /// The pin is being held at a level, and the period continues for `rest` afterwards if set.
+    Phase {
+        high: bool,
+        hold: time::Duration,
+        rest: Option<time::Duration>,
+        step: Step,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Step {
+    Set,
+    Arm,
+    Wait,
+}
+
+impl<T> Generator<T> {
+    /// Creates a new generator that uses the specified timer.
+    pub fn new(timer: T) -> Self {
+        Generator(timer)
+    }
+
+    /// Takes apart this generator, returning the underlying timer.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<P, T> super::IntoPwmPin<P> for Generator<T>
+where
+    P: gpio::IntoPushPullOutputPin,
+    T: timer::IntoOneshotTimer<Error = P::Error>,
+    T::OneshotTimer: timer::RestartableTimer,
+{
+    type Error = P::Error;
+    type PwmPin = Pwm<P::PushPullOutputPin, T::OneshotTimer>;
+
+    fn into_pwm_pin(self, pin: P, config: super::Config) -> Result<Self::PwmPin, Self::Error> {
+        let active_high = config.is_active_high();
+        let pin = pin.into_push_pull_output_pin(!active_high)?;
+        let timer = self.0.into_oneshot_timer(config.rate().period())?;
+        Ok(Pwm::new(pin, timer, config))
+    }
+}
+
+impl<P, T> Pwm<P, T>
+where
+    P: gpio::OutputPin + Unpin,
+    T: timer::RestartableTimer<Error = P::Error> + Unpin,
+{
+    /// Creates a new PWM output from a pin and a oneshot timer, that is used to time the changes
+    /// of the pin.
+    ///
+    /// The pin is expected to be at the inactive level already.
+    pub fn new(pin: P, timer: T, config: super::Config) -> Self {
+        let period = config.rate().period();
+        let active_high = config.is_active_high();
+        let duty = config.initial_duty();
+        let level = Some(!active_high);
+        let state = State::Start;
+        Self {
+            pin,
+            timer,
+            period,
+            active_high,
+            duty,
+            level,
+            state,
+        }
+    }
+
+    /// Takes apart this output, returning the underlying pin and timer.
+    pub fn into_inner(self) -> (P, T) {
+        (self.pin, self.timer)
+    }
+
+    /// Determines the phases of the next period from the current duty cycle.
+    fn start(&self) -> State {
+        let step = Step::Set;
+        let active = time::Duration::from_nanos(self.duty.scale(self.period.as_nanos()));
+        let inactive = self.period.saturating_sub(active);
+        if active.is_zero() || inactive.is_zero() {
+            let high = self.active_high != active.is_zero();
+            let hold = self.period;
+            let rest = None;
+            State::Phase {
+                high,
+                hold,
+                rest,
+                step,
+            }
+        } else {
+            let high = self.active_high;
+            let hold = active;
+            let rest = Some(inactive);
+            State::Phase {
+                high,
+                hold,
+                rest,
+                step,
+            }
+        }
+    }
+}
+
+impl<P, T> super::PwmPin for Pwm<P, T>
+where
+    P: gpio::OutputPin + Unpin,
+    T: timer::RestartableTimer<Error = P::Error> + Unpin,
+{
+    type Error = P::Error;
+
+    fn poll_set_duty(
+        mut self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+        duty: super::Duty,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        self.duty = duty;
+        task::Poll::Ready(Ok(()))
+    }
+}
+
+impl<P, T> futures::stream::Stream for Pwm<P, T>
+where
+    P: gpio::OutputPin + Unpin,
+    T: timer::RestartableTimer<Error = P::Error> + Unpin,
+{
+    type Item = Result<(), P::Error>;
+
+    fn poll_next(
+        mut self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        loop {
+            let (high, hold, rest, step) = match this.state {
+                State::Start => {
+                    this.state = this.start();
+                    continue;
+                }
+                State::Phase {
+                    high,
+                    hold,
+                    rest,
+                    step,
+                } => (high, hold, rest, step),
+            };
+
+            let result = match step {
+                Step::Set if this.level == Some(high) => Ok(Some(Step::Arm)),
+                Step::Set => {
+                    this.level = None;
+                    futures::ready!(pin::Pin::new(&mut this.pin).poll_set(cx, high)).map(|()| {
+                        this.level = Some(high);
+                        Some(Step::Arm)
+                    })
+                }
+                Step::Arm => futures::ready!(pin::Pin::new(&mut this.timer).poll_restart(cx, hold))
+                    .map(|()| Some(Step::Wait)),
+                Step::Wait => {
+                    futures::ready!(pin::Pin::new(&mut this.timer).poll_tick(cx)).map(|()| None)
+                }
+            };
+
+            this.state = match result {
+                Ok(Some(step)) => State::Phase {
+                    high,
+                    hold,
+                    rest,
+                    step,
+                },
+                Ok(None) => match rest {
+                    Some(rest) => State::Phase {
+                        high: !high,
+                        hold: rest,
+                        rest: None,
+                        step: Step::Set,
+                    },
+                    None => {
+                        this.state = State::Start;
+                        return task::Poll::Ready(Some(Ok(())));
+                    }
+                },
+                Err(err) => {
+                    this.state = State::Start;
+                    return task::Poll::Ready(Some(Err(err)));
+                }
+            };
+        }
+    }
+}
@@ -1,6 +1,8 @@
+use crate::adc;
 use crate::gpio;
 use crate::i2c;
 use crate::platform;
+use core::fmt;
 
 /// A platform that conforms to the [Adafruit Feather specification](https://learn.adafruit.com/adafruit-feather/feather-specification).
 ///
@@ -13,14 +15,22 @@ use crate::platform;
 ///     once that enters stable rust.
 ///   * `A0-A5` are capable of analog input in addition to GPIO.  The exception is the ESP8266
 ///     feather, which only has a working `A0`.  Hence analog traits are not required by the other
-///     pins, while in practice most boards will actually implement them.
+///     pins, while in practice most boards will actually implement them.  Boards whose `A0-A5`
+///     pins are all analog inputs are [`AnalogFeather`]s.
 ///   * `RX`/`TX` are bound to a hardware UART.
 ///   * `SDA`/`SCL` are bound to the main I²C bus.
 ///   * `SCK`/`MOSI`/`MISO` are bound to the main SPI bus.
 ///   * `P0` is mapped to something custom depending on the specific feather, usually a GPIO.
 ///
 /// Additionally, it is guaranteed that there is one main LED bound to a pin, but which one it is
-/// is left unspecified.
+/// is left unspecified.  It might share its pin with one of the other roles, e.g. `D7`, in which
+/// case only one of the two can be taken; the `try_take_*` methods report which [`Role`] a pin
+/// was already taken as.
+///
+/// Pins can be given back with the `release_*` methods once they are no longer needed, e.g. after
+/// a one-time calibration, in whatever mode they were configured in.  The platform turns them back
+/// into floating inputs, after which they can be taken again in any role.  Buses such as the main
+/// I²C bus keep their pins for as long as they exist, so their pins can't be released.
 ///
 /// The pins are placed roughly according to this illustration:
 ///
@@ -45,6 +55,14 @@ use crate::platform;
 ///      └──────────────────┘
 /// ```
 pub trait Feather: platform::Platform {
+    /// Identifies the physical pins of the platform, for reporting conflicts.
+    type PinId: fmt::Debug + fmt::Display + Copy;
+
+    /// The pin of the main LED, where setting the pin high always turns the LED on.
+    ///
+    /// Platforms whose LED is wired active-low declare this as an [`gpio::Inverted`] pin, and
+    /// hand out the underlying pin wrapped accordingly, so that applications never need to know
+    /// the polarity of the LED.
     type MainLed: gpio::IntoPushPullOutputPin<Error = Self::Error>;
     type MainI2cMapping: i2c::I2cBusMapping<Self::SDA, Self::SCL>;
 
@@ -72,31 +90,302 @@ pub trait Feather: platform::Platform {
     type A1: gpio::IntoFloatingInputPin<Error = Self::Error>;
     type A0: gpio::IntoFloatingInputPin<Error = Self::Error>;
 
-    fn take_main_led(&mut self) -> Self::MainLed;
-
-    fn take_main_i2c(
-        &mut self,
-    ) -> <Self::MainI2cMapping as i2c::I2cBusMapping<Self::SDA, Self::SCL>>::Bus;
-
-    fn take_sda(&mut self) -> Self::SDA;
-    fn take_scl(&mut self) -> Self::SCL;
-    fn take_d2(&mut self) -> Self::D2;
-    fn take_d3(&mut self) -> Self::D3;
-    fn take_d4(&mut self) -> Self::D4;
-    fn take_d5(&mut self) -> Self::D5;
-    fn take_d6(&mut self) -> Self::D6;
-    fn take_d7(&mut self) -> Self::D7;
-    fn take_d8(&mut self) -> Self::D8;
-    fn take_p0(&mut self) -> Self::P0;
-    fn take_tx(&mut self) -> Self::TX;
-    fn take_rx(&mut self) -> Self::RX;
-    fn take_miso(&mut self) -> Self::MISO;
-    fn take_mosi(&mut self) -> Self::MOSI;
-    fn take_sck(&mut self) -> Self::SCK;
-    fn take_a5(&mut self) -> Self::A5;
-    fn take_a4(&mut self) -> Self::A4;
-    fn take_a3(&mut self) -> Self::A3;
-    fn take_a2(&mut self) -> Self::A2;
-    fn take_a1(&mut self) -> Self::A1;
-    fn take_a0(&mut self) -> Self::A0;
+    /// Takes the pin of the main LED, failing if the pin was already taken, e.g. as `D7`.
+    fn try_take_main_led(&mut self) -> Result<Self::MainLed, PinInUse<Self::PinId>>;
+
+    /// Takes the main I²C bus, failing if its `SDA` or `SCL` pin was already taken.
+    fn try_take_main_i2c(&mut self) -> Result<MainI2cBus<Self>, PinInUse<Self::PinId>>;
+
+    fn try_take_sda(&mut self) -> Result<Self::SDA, PinInUse<Self::PinId>>;
+    fn try_take_scl(&mut self) -> Result<Self::SCL, PinInUse<Self::PinId>>;
+    fn try_take_d2(&mut self) -> Result<Self::D2, PinInUse<Self::PinId>>;
+    fn try_take_d3(&mut self) -> Result<Self::D3, PinInUse<Self::PinId>>;
+    fn try_take_d4(&mut self) -> Result<Self::D4, PinInUse<Self::PinId>>;
+    fn try_take_d5(&mut self) -> Result<Self::D5, PinInUse<Self::PinId>>;
+    fn try_take_d6(&mut self) -> Result<Self::D6, PinInUse<Self::PinId>>;
+    fn try_take_d7(&mut self) -> Result<Self::D7, PinInUse<Self::PinId>>;
+    fn try_take_d8(&mut self) -> Result<Self::D8, PinInUse<Self::PinId>>;
+    fn try_take_p0(&mut self) -> Result<Self::P0, PinInUse<Self::PinId>>;
+    fn try_take_tx(&mut self) -> Result<Self::TX, PinInUse<Self::PinId>>;
+    fn try_take_rx(&mut self) -> Result<Self::RX, PinInUse<Self::PinId>>;
+    fn try_take_miso(&mut self) -> Result<Self::MISO, PinInUse<Self::PinId>>;
+    fn try_take_mosi(&mut self) -> Result<Self::MOSI, PinInUse<Self::PinId>>;
+    fn try_take_sck(&mut self) -> Result<Self::SCK, PinInUse<Self::PinId>>;
+    fn try_take_a5(&mut self) -> Result<Self::A5, PinInUse<Self::PinId>>;
+    fn try_take_a4(&mut self) -> Result<Self::A4, PinInUse<Self::PinId>>;
+    fn try_take_a3(&mut self) -> Result<Self::A3, PinInUse<Self::PinId>>;
+    fn try_take_a2(&mut self) -> Result<Self::A2, PinInUse<Self::PinId>>;
+    fn try_take_a1(&mut self) -> Result<Self::A1, PinInUse<Self::PinId>>;
+    fn try_take_a0(&mut self) -> Result<Self::A0, PinInUse<Self::PinId>>;
+
+    /// Returns the pin of the main LED to the platform, turning it back into a floating input.
+    ///
+    /// The same applies to all of the other `release_*` methods.  Releasing a pin that shares its
+    /// physical pin with another role, e.g. `D7`, makes it available in both roles again.
+    fn release_main_led<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::MainLed, Error = Self::Error>;
+
+    fn release_sda<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::SDA, Error = Self::Error>;
+
+    fn release_scl<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::SCL, Error = Self::Error>;
+
+    fn release_d2<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::D2, Error = Self::Error>;
+
+    fn release_d3<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::D3, Error = Self::Error>;
+
+    fn release_d4<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::D4, Error = Self::Error>;
+
+    fn release_d5<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::D5, Error = Self::Error>;
+
+    fn release_d6<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::D6, Error = Self::Error>;
+
+    fn release_d7<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::D7, Error = Self::Error>;
+
+    fn release_d8<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::D8, Error = Self::Error>;
+
+    fn release_p0<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::P0, Error = Self::Error>;
+
+    fn release_tx<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::TX, Error = Self::Error>;
+
+    fn release_rx<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::RX, Error = Self::Error>;
+
+    fn release_miso<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::MISO, Error = Self::Error>;
+
+    fn release_mosi<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::MOSI, Error = Self::Error>;
+
+    fn release_sck<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::SCK, Error = Self::Error>;
+
+    fn release_a5<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::A5, Error = Self::Error>;
+
+    fn release_a4<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::A4, Error = Self::Error>;
+
+    fn release_a3<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::A3, Error = Self::Error>;
+
+    fn release_a2<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::A2, Error = Self::Error>;
+
+    fn release_a1<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::A1, Error = Self::Error>;
+
+    fn release_a0<P>(&mut self, pin: P) -> Result<(), Self::Error>
+    where
+        P: gpio::IntoFloatingInputPin<FloatingInputPin = Self::A0, Error = Self::Error>;
+
+    /// Takes the pin of the main LED.
+    ///
+    /// Panics if the pin was already taken.
+    fn take_main_led(&mut self) -> Self::MainLed {
+        self.try_take_main_led()
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Takes the main I²C bus.
+    ///
+    /// Panics if its `SDA` or `SCL` pin was already taken.
+    fn take_main_i2c(&mut self) -> MainI2cBus<Self> {
+        self.try_take_main_i2c()
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_sda(&mut self) -> Self::SDA {
+        self.try_take_sda().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_scl(&mut self) -> Self::SCL {
+        self.try_take_scl().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_d2(&mut self) -> Self::D2 {
+        self.try_take_d2().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_d3(&mut self) -> Self::D3 {
+        self.try_take_d3().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_d4(&mut self) -> Self::D4 {
+        self.try_take_d4().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_d5(&mut self) -> Self::D5 {
+        self.try_take_d5().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_d6(&mut self) -> Self::D6 {
+        self.try_take_d6().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_d7(&mut self) -> Self::D7 {
+        self.try_take_d7().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_d8(&mut self) -> Self::D8 {
+        self.try_take_d8().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_p0(&mut self) -> Self::P0 {
+        self.try_take_p0().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_tx(&mut self) -> Self::TX {
+        self.try_take_tx().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_rx(&mut self) -> Self::RX {
+        self.try_take_rx().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_miso(&mut self) -> Self::MISO {
+        self.try_take_miso().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_mosi(&mut self) -> Self::MOSI {
+        self.try_take_mosi().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_sck(&mut self) -> Self::SCK {
+        self.try_take_sck().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_a5(&mut self) -> Self::A5 {
+        self.try_take_a5().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_a4(&mut self) -> Self::A4 {
+        self.try_take_a4().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_a3(&mut self) -> Self::A3 {
+        self.try_take_a3().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_a2(&mut self) -> Self::A2 {
+        self.try_take_a2().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_a1(&mut self) -> Self::A1 {
+        self.try_take_a1().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn take_a0(&mut self) -> Self::A0 {
+        self.try_take_a0().unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+/// A [`Feather`] whose `A0-A5` pins can all be used as analog inputs.
+///
+/// This is implemented automatically for every such platform, so that drivers can require analog
+/// inputs by bounding on this trait instead of on each pin.
+pub trait AnalogFeather:
+    Feather<
+    A0: adc::IntoAnalogInputPin,
+    A1: adc::IntoAnalogInputPin,
+    A2: adc::IntoAnalogInputPin,
+    A3: adc::IntoAnalogInputPin,
+    A4: adc::IntoAnalogInputPin,
+    A5: adc::IntoAnalogInputPin,
+>
+{
+}
+
+impl<F> AnalogFeather for F where
+    F: Feather<
+        A0: adc::IntoAnalogInputPin,
+        A1: adc::IntoAnalogInputPin,
+        A2: adc::IntoAnalogInputPin,
+        A3: adc::IntoAnalogInputPin,
+        A4: adc::IntoAnalogInputPin,
+        A5: adc::IntoAnalogInputPin,
+    >
+{
+}
+
+/// The type of the main I²C bus of a [`Feather`].
+pub type MainI2cBus<F> = <<F as Feather>::MainI2cMapping as i2c::I2cBusMapping<
+    <F as Feather>::SDA,
+    <F as Feather>::SCL,
+>>::Bus;
+
+/// The roles that the pins of a [`Feather`] are taken as.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Role {
+    MainLed,
+    Sda,
+    Scl,
+    D2,
+    D3,
+    D4,
+    D5,
+    D6,
+    D7,
+    D8,
+    P0,
+    Tx,
+    Rx,
+    Miso,
+    Mosi,
+    Sck,
+    A5,
+    A4,
+    A3,
+    A2,
+    A1,
+    A0,
+}
+
+/// An error returned when taking a pin that was already taken.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PinInUse<P> {
+    /// The physical pin that was requested.
+    pub pin: P,
+    /// The role that the pin was previously taken as.
+    pub role: Role,
+}
+
+impl<P> fmt::Display for PinInUse<P>
+where
+    P: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pin {} is already taken as {:?}", self.pin, self.role)
+    }
 }
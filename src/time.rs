@@ -1,55 +1,383 @@
+use core::cmp;
+use core::convert;
+use core::fmt;
+use core::ops;
+
+const NANOS_PER_MICRO: u64 = 1_000;
+const NANOS_PER_MILLI: u64 = 1_000_000;
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+/// A frequency, stored as an exact fraction of hertz.
+///
+/// Rates never involve floating point arithmetic, so that they can be used on targets without an
+/// FPU, and so that high frequencies don't suffer from rounding.  The fraction is always kept in
+/// lowest terms.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Rate {
+    cycles: u64,
+    nanos: u64,
+}
+
+/// A span of time, with nanosecond precision.
+///
+/// Durations are stored as a 64-bit number of nanoseconds, which covers more than 584 years.
+/// Arithmetic operators panic on overflow; use the `checked_*` or `saturating_*` methods where
+/// that's a concern.
 #[repr(transparent)]
-#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
-pub struct Rate(f32);
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Duration(u64);
 
+/// The error returned when converting a [`core::time::Duration`] that is too long to fit in a
+/// [`Duration`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct OutOfRangeError;
+
+/// A point in time, as measured by a [`Clock`](crate::clock::Clock).
+///
+/// Instants are only meaningful relative to other instants from the same clock.  They are measured
+/// in nanoseconds, counting from an arbitrary epoch (usually when the clock was started).
 #[repr(transparent)]
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-pub struct Duration(u32);
+pub struct Instant(u64);
 
 impl Rate {
-    pub fn from_hz(hz: f32) -> Self {
-        Self(hz)
+    /// Panics if `hz` is zero.
+    pub fn from_hz(hz: u32) -> Self {
+        Self::new(u64::from(hz), NANOS_PER_SECOND)
     }
 
-    pub fn as_hz(self) -> f32 {
-        self.0
+    /// Panics if `khz` is zero.
+    pub fn from_khz(khz: u32) -> Self {
+        Self::new(u64::from(khz) * 1_000, NANOS_PER_SECOND)
+    }
+
+    /// Panics if `mhz` is zero.
+    pub fn from_mhz(mhz: u32) -> Self {
+        Self::new(u64::from(mhz) * 1_000_000, NANOS_PER_SECOND)
+    }
+
+    /// Creates the rate that has the specified period.  The conversion is exact.
+    ///
+    /// Panics if the period is zero.
+    pub fn from_period(period: Duration) -> Self {
+        Self::new(1, period.as_nanos())
+    }
+
+    /// Returns the number of whole cycles per second.
+    pub fn as_hz(self) -> u64 {
+        (u128::from(self.cycles) * u128::from(NANOS_PER_SECOND) / u128::from(self.nanos)) as u64
+    }
+
+    /// Returns the period of this rate, rounded down to whole nanoseconds.
+    ///
+    /// The conversion is exact for every rate whose period is a whole number of nanoseconds,
+    /// including all rates created with [`Rate::from_period`].
+    pub fn period(self) -> Duration {
+        Duration(self.nanos / self.cycles)
+    }
+
+    /// Returns the number of ticks of a counter running at `tick_rate` during one period of this
+    /// rate, rounded down, or `None` if that number doesn't fit in a `u64`.
+    ///
+    /// This is what hardware timers need to be programmed with to produce this rate.
+    pub fn period_ticks(self, tick_rate: Self) -> Option<u64> {
+        let ticks = u128::from(tick_rate.cycles) * u128::from(self.nanos)
+            / (u128::from(tick_rate.nanos) * u128::from(self.cycles));
+        convert::TryFrom::try_from(ticks).ok()
+    }
+
+    fn new(cycles: u64, nanos: u64) -> Self {
+        assert!(
+            cycles != 0 && nanos != 0,
+            "rate must be non-zero and finite"
+        );
+        let divisor = gcd(cycles, nanos);
+        let cycles = cycles / divisor;
+        let nanos = nanos / divisor;
+        Self { cycles, nanos }
+    }
+}
+
+impl Ord for Rate {
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        let this = u128::from(self.cycles) * u128::from(other.nanos);
+        let other = u128::from(other.cycles) * u128::from(self.nanos);
+        this.cmp(&other)
+    }
+}
+
+impl PartialOrd for Rate {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
     }
 }
 
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
 impl Duration {
-    pub fn from_nanos(nanos: u32) -> Self {
+    /// A duration of zero time.
+    pub const ZERO: Self = Self(0);
+    /// The longest duration that can be represented.
+    pub const MAX: Self = Self(u64::MAX);
+
+    pub const fn from_nanos(nanos: u64) -> Self {
         Self(nanos)
     }
 
-    pub fn from_millis(millis: u32) -> Self {
-        Self(millis * 1_000)
+    /// Panics if the duration is longer than [`Duration::MAX`].
+    pub fn from_micros(micros: u64) -> Self {
+        Self::from_unit(micros, NANOS_PER_MICRO)
     }
 
-    pub fn from_seconds(seconds: u32) -> Self {
-        Self(seconds * 1_000_000)
+    /// Panics if the duration is longer than [`Duration::MAX`].
+    pub fn from_millis(millis: u64) -> Self {
+        Self::from_unit(millis, NANOS_PER_MILLI)
     }
 
-    pub fn as_nanos(self) -> u32 {
+    /// Panics if the duration is longer than [`Duration::MAX`].
+    pub fn from_seconds(seconds: u64) -> Self {
+        Self::from_unit(seconds, NANOS_PER_SECOND)
+    }
+
+    pub const fn as_nanos(self) -> u64 {
         self.0
     }
+
+    /// Returns the number of whole microseconds in this duration.
+    pub const fn as_micros(self) -> u64 {
+        self.0 / NANOS_PER_MICRO
+    }
+
+    /// Returns the number of whole milliseconds in this duration.
+    pub const fn as_millis(self) -> u64 {
+        self.0 / NANOS_PER_MILLI
+    }
+
+    /// Returns the number of whole seconds in this duration.
+    pub const fn as_seconds(self) -> u64 {
+        self.0 / NANOS_PER_SECOND
+    }
+
+    pub const fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    pub fn checked_mul(self, factor: u32) -> Option<Self> {
+        self.0.checked_mul(u64::from(factor)).map(Self)
+    }
+
+    /// Returns `None` if `divisor` is zero.
+    pub fn checked_div(self, divisor: u32) -> Option<Self> {
+        self.0.checked_div(u64::from(divisor)).map(Self)
+    }
+
+    pub fn saturating_add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+
+    pub fn saturating_sub(self, other: Self) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+
+    pub fn saturating_mul(self, factor: u32) -> Self {
+        Self(self.0.saturating_mul(u64::from(factor)))
+    }
+
+    fn from_unit(count: u64, nanos_per_unit: u64) -> Self {
+        count
+            .checked_mul(nanos_per_unit)
+            .map(Self)
+            .expect("overflow when creating duration")
+    }
 }
 
-pub trait F32Ext {
-    fn hz(self) -> Rate;
+impl ops::Add for Duration {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.checked_add(other)
+            .expect("overflow when adding durations")
+    }
 }
 
-impl F32Ext for f32 {
-    fn hz(self) -> Rate {
-        Rate(self)
+impl ops::AddAssign for Duration {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl ops::Sub for Duration {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.checked_sub(other)
+            .expect("overflow when subtracting durations")
+    }
+}
+
+impl ops::SubAssign for Duration {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl ops::Mul<u32> for Duration {
+    type Output = Self;
+
+    fn mul(self, factor: u32) -> Self {
+        self.checked_mul(factor)
+            .expect("overflow when multiplying duration")
+    }
+}
+
+impl ops::MulAssign<u32> for Duration {
+    fn mul_assign(&mut self, factor: u32) {
+        *self = *self * factor;
+    }
+}
+
+impl ops::Div<u32> for Duration {
+    type Output = Self;
+
+    fn div(self, divisor: u32) -> Self {
+        self.checked_div(divisor)
+            .expect("divide by zero when dividing duration")
+    }
+}
+
+impl ops::DivAssign<u32> for Duration {
+    fn div_assign(&mut self, divisor: u32) {
+        *self = *self / divisor;
+    }
+}
+
+impl From<Duration> for core::time::Duration {
+    fn from(duration: Duration) -> Self {
+        core::time::Duration::from_nanos(duration.0)
+    }
+}
+
+impl convert::TryFrom<core::time::Duration> for Duration {
+    type Error = OutOfRangeError;
+
+    fn try_from(duration: core::time::Duration) -> Result<Self, Self::Error> {
+        convert::TryFrom::try_from(duration.as_nanos())
+            .map(Self)
+            .map_err(|_| OutOfRangeError)
+    }
+}
+
+impl fmt::Display for OutOfRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("duration is out of range")
+    }
+}
+
+impl Instant {
+    pub fn from_nanos(nanos: u64) -> Self {
+        Self(nanos)
+    }
+
+    pub fn as_nanos(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the duration from `earlier` to this instant, or `None` if `earlier` is later than
+    /// this instant.
+    pub fn checked_duration_since(self, earlier: Self) -> Option<Duration> {
+        self.0.checked_sub(earlier.0).map(Duration)
+    }
+
+    /// Returns the duration from `earlier` to this instant, or a zero duration if `earlier` is
+    /// later than this instant.
+    pub fn saturating_duration_since(self, earlier: Self) -> Duration {
+        Duration(self.0.saturating_sub(earlier.0))
+    }
+
+    pub fn checked_add(self, duration: Duration) -> Option<Self> {
+        self.0.checked_add(duration.0).map(Self)
+    }
+
+    pub fn checked_sub(self, duration: Duration) -> Option<Self> {
+        self.0.checked_sub(duration.0).map(Self)
+    }
+}
+
+impl ops::Add<Duration> for Instant {
+    type Output = Self;
+
+    fn add(self, duration: Duration) -> Self {
+        self.checked_add(duration)
+            .expect("overflow when adding duration to instant")
+    }
+}
+
+impl ops::AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+impl ops::Sub<Duration> for Instant {
+    type Output = Self;
+
+    fn sub(self, duration: Duration) -> Self {
+        self.checked_sub(duration)
+            .expect("overflow when subtracting duration from instant")
+    }
+}
+
+impl ops::SubAssign<Duration> for Instant {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+impl ops::Sub for Instant {
+    type Output = Duration;
+
+    /// Returns the duration between two instants.
+    ///
+    /// Panics if `earlier` is later than this instant.
+    fn sub(self, earlier: Self) -> Duration {
+        self.checked_duration_since(earlier)
+            .expect("duration between instants is negative")
     }
 }
 
 pub trait U32Ext {
     fn hz(self) -> Rate;
+
+    fn khz(self) -> Rate;
+
+    fn mhz(self) -> Rate;
 }
 
 impl U32Ext for u32 {
     fn hz(self) -> Rate {
-        Rate(self as f32)
+        Rate::from_hz(self)
+    }
+
+    fn khz(self) -> Rate {
+        Rate::from_khz(self)
+    }
+
+    fn mhz(self) -> Rate {
+        Rate::from_mhz(self)
     }
 }
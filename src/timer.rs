@@ -1,11 +1,18 @@
+use crate::clock;
 use crate::time;
 use core::fmt;
+use core::future;
 use core::pin;
 use core::task;
 
+pub mod cancel;
+pub mod elapsed;
+pub mod restart;
+pub mod service;
 pub mod start;
 pub mod tick;
 pub mod ticks;
+pub mod timeout;
 
 pub trait Timer: fmt::Debug {
     type Error;
@@ -19,6 +26,19 @@ pub trait Timer: fmt::Debug {
         self: pin::Pin<&mut Self>,
         cx: &mut task::Context<'_>,
     ) -> task::Poll<Result<(), Self::Error>>;
+
+    /// Polls for the next tick, like [`Timer::poll_tick`], but observes all ticks that have
+    /// happened so far at once, and returns how many there were.
+    ///
+    /// The count is always at least one.  The default implementation observes a single tick;
+    /// timers that keep count of elapsed periods should override it, so that
+    /// [`Ticks`](ticks::Ticks) streams can detect missed ticks.
+    fn poll_ticks(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<u32, Self::Error>> {
+        self.poll_tick(cx).map(|result| result.map(|()| 1))
+    }
 }
 
 pub trait TimerExt: Timer {
@@ -36,16 +56,141 @@ pub trait TimerExt: Timer {
         tick::tick(self)
     }
 
+    /// Returns a stream of the ticks of this timer, that catches up with missed ticks in a burst.
+    ///
+    /// Each item is the number of periods that were missed by the time that the tick was
+    /// observed.
     fn ticks(&mut self) -> ticks::Ticks<Self>
     where
         Self: Unpin,
     {
-        ticks::ticks(self)
+        ticks::ticks(self, ticks::MissedTickBehavior::Burst)
+    }
+
+    /// Returns a stream of the ticks of this timer, that handles missed ticks as specified.
+    fn ticks_with(&mut self, behavior: ticks::MissedTickBehavior) -> ticks::Ticks<Self>
+    where
+        Self: Unpin,
+    {
+        ticks::ticks(self, behavior)
+    }
+
+    /// Runs the future, but fails with [`timeout::Elapsed`] if it doesn't complete within the
+    /// specified duration.
+    ///
+    /// The timer is configured as a oneshot timer right away, and only started once the future
+    /// has been polled and turned out not to be ready.  The timeout hands the timer back along
+    /// with the result, so that it can be used for the next timeout.
+    fn timeout<F>(
+        self,
+        duration: time::Duration,
+        future: F,
+    ) -> Result<timeout::Timeout<Self::OneshotTimer, F>, Self::Error>
+    where
+        Self: IntoOneshotTimer + Sized,
+        F: future::Future,
+    {
+        timeout::timeout(self, duration, future)
+    }
+
+    /// Runs the future, but fails with [`timeout::Elapsed`] if it doesn't complete before the
+    /// specified instant of the specified clock.
+    ///
+    /// The time remaining until the deadline is computed when this method is called.
+    fn deadline<C, F>(
+        self,
+        clock: &C,
+        deadline: time::Instant,
+        future: F,
+    ) -> Result<timeout::Timeout<Self::OneshotTimer, F>, Self::Error>
+    where
+        Self: IntoOneshotTimer + Sized,
+        C: clock::Clock + ?Sized,
+        F: future::Future,
+    {
+        timeout::deadline(self, clock, deadline, future)
     }
 }
 
 impl<T> TimerExt for T where T: Timer {}
 
+/// A timer that can be stopped before it ticks.
+pub trait CancellableTimer: Timer {
+    /// Stops the timer, and discards any ticks that haven't been observed yet.
+    ///
+    /// The timer can be started again afterwards.  Cancelling a timer that isn't running has no
+    /// effect.
+    fn poll_cancel(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>>;
+}
+
+/// Extension functions for instances of [`CancellableTimer`].
+pub trait CancellableTimerExt: CancellableTimer {
+    /// Stops the timer.
+    fn cancel(&mut self) -> cancel::Cancel<Self>
+    where
+        Self: Unpin,
+    {
+        cancel::cancel(self)
+    }
+}
+
+impl<T> CancellableTimerExt for T where T: CancellableTimer {}
+
+/// A timer that can be re-armed with a different delay, e.g. to implement a watchdog that is
+/// kicked periodically.
+pub trait RestartableTimer: CancellableTimer {
+    /// Cancels the timer and starts it again, so that it next ticks after the specified delay.
+    ///
+    /// For periodic timers, the delay also becomes the new period.
+    fn poll_restart(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+        delay: time::Duration,
+    ) -> task::Poll<Result<(), Self::Error>>;
+}
+
+/// Extension functions for instances of [`RestartableTimer`].
+pub trait RestartableTimerExt: RestartableTimer {
+    /// Restarts the timer with the specified delay.
+    fn restart(&mut self, delay: time::Duration) -> restart::Restart<Self>
+    where
+        Self: Unpin,
+    {
+        restart::restart(self, delay)
+    }
+}
+
+impl<T> RestartableTimerExt for T where T: RestartableTimer {}
+
+/// A timer whose progress can be read, e.g. to measure pulse widths.
+pub trait CountingTimer: Timer {
+    /// Polls the time that has passed since the timer was last started or last ticked.
+    ///
+    /// Once a timer has stopped, either because a oneshot timer expired or because it was
+    /// cancelled, this is the time that it counted before stopping.  A timer that has never been
+    /// started has counted zero time.
+    fn poll_elapsed(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<time::Duration, Self::Error>>;
+}
+
+/// Extension functions for instances of [`CountingTimer`].
+pub trait CountingTimerExt: CountingTimer {
+    /// Reads how far the timer has counted.
+    fn elapsed(&mut self) -> elapsed::Elapsed<Self>
+    where
+        Self: Unpin,
+    {
+        elapsed::elapsed(self)
+    }
+}
+
+impl<T> CountingTimerExt for T where T: CountingTimer {}
+
 pub trait IntoPeriodicTimer: Timer {
     type PeriodicTimer: Timer<Error = Self::Error> + Unpin;
 
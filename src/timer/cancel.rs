@@ -0,0 +1,31 @@
+use core::future;
+use core::pin;
+use core::task;
+
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Cancel<'a, A>
+where
+    A: super::CancellableTimer + Unpin + ?Sized,
+{
+    timer: &'a mut A,
+}
+
+pub fn cancel<A>(timer: &mut A) -> Cancel<A>
+where
+    A: super::CancellableTimer + Unpin + ?Sized,
+{
+    Cancel { timer }
+}
+
+impl<A> future::Future for Cancel<'_, A>
+where
+    A: super::CancellableTimer + Unpin + ?Sized,
+{
+    type Output = Result<(), A::Error>;
+
+    fn poll(mut self: pin::Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let this = &mut *self;
+        pin::Pin::new(&mut *this.timer).poll_cancel(cx)
+    }
+}
@@ -0,0 +1,32 @@
+use crate::time;
+use core::future;
+use core::pin;
+use core::task;
+
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Elapsed<'a, A>
+where
+    A: super::CountingTimer + Unpin + ?Sized,
+{
+    timer: &'a mut A,
+}
+
+pub fn elapsed<A>(timer: &mut A) -> Elapsed<A>
+where
+    A: super::CountingTimer + Unpin + ?Sized,
+{
+    Elapsed { timer }
+}
+
+impl<A> future::Future for Elapsed<'_, A>
+where
+    A: super::CountingTimer + Unpin + ?Sized,
+{
+    type Output = Result<time::Duration, A::Error>;
+
+    fn poll(mut self: pin::Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let this = &mut *self;
+        pin::Pin::new(&mut *this.timer).poll_elapsed(cx)
+    }
+}
@@ -0,0 +1,33 @@
+use crate::time;
+use core::future;
+use core::pin;
+use core::task;
+
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Restart<'a, A>
+where
+    A: super::RestartableTimer + Unpin + ?Sized,
+{
+    timer: &'a mut A,
+    delay: time::Duration,
+}
+
+pub fn restart<A>(timer: &mut A, delay: time::Duration) -> Restart<A>
+where
+    A: super::RestartableTimer + Unpin + ?Sized,
+{
+    Restart { timer, delay }
+}
+
+impl<A> future::Future for Restart<'_, A>
+where
+    A: super::RestartableTimer + Unpin + ?Sized,
+{
+    type Output = Result<(), A::Error>;
+
+    fn poll(mut self: pin::Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let this = &mut *self;
+        pin::Pin::new(&mut *this.timer).poll_restart(cx, this.delay)
+    }
+}
@@ -0,0 +1,461 @@
+//! Multiplexing many software timers over one hardware timer.
+//!
+//! A [`Service`] takes a single hardware timer, runs it as a periodic tick source, and hands out
+//! [`Handle`]s that each implement [`Timer`](super::Timer), [`IntoOneshotTimer`],
+//! [`IntoPeriodicTimer`] and [`RestartableTimer`].  Deadlines are kept in a fixed number of
+//! slots, so no allocation is needed.
+//!
+//! There is no background task driving the service.  Instead, whichever handle is waiting also
+//! polls the hardware timer on behalf of all the others, and hands that duty over to another
+//! waiting handle when it completes or is dropped.
+//!
+//! Since the service works in whole ticks, a delay lasts at least its duration and less than two
+//! resolutions longer, and the period of an interval is rounded down to a whole number of ticks.
+//!
+//! The hardware timer only runs while some handle has a deadline: it is cancelled as soon as the
+//! last deadline has passed or was cancelled, and started again once a handle waits for a new one.
+//!
+//! [`IntoOneshotTimer`]: super::IntoOneshotTimer
+//! [`IntoPeriodicTimer`]: super::IntoPeriodicTimer
+//! [`RestartableTimer`]: super::RestartableTimer
+use crate::time;
+use core::array;
+use core::cell;
+use core::fmt;
+use core::future;
+use core::mem;
+use core::pin;
+use core::task;
+
+/// A timer service with `N` slots, driven by the periodic hardware timer `T`.
+pub struct Service<T, const N: usize> {
+    resolution: time::Duration,
+    timer: cell::RefCell<T>,
+    state: cell::RefCell<State<N>>,
+}
+
+struct State<const N: usize> {
+    started: bool,
+    now: u64,
+    driver: Option<usize>,
+    slots: [Slot; N],
+}
+
+#[derive(Default)]
+struct Slot {
+    in_use: bool,
+    periodic: bool,
+    period: u64,
+    deadline: Option<u64>,
+    pending: u32,
+    waker: Option<task::Waker>,
+}
+
+/// A software timer that occupies one slot of a [`Service`] for as long as it lives.
+pub struct Handle<'a, T, const N: usize>
+where
+    T: super::CancellableTimer + Unpin,
+{
+    service: &'a Service<T, N>,
+    slot: usize,
+}
+
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Sleep<'a, T, const N: usize>
+where
+    T: super::CancellableTimer + Unpin,
+{
+    handle: Handle<'a, T, N>,
+    started: bool,
+}
+
+impl<T, const N: usize> Service<T, N>
+where
+    T: super::CancellableTimer + Unpin,
+{
+    /// Creates a new service that ticks the specified timer once every `resolution`.
+    ///
+    /// Panics if `resolution` is zero.
+    pub fn new<I>(timer: I, resolution: time::Duration) -> Result<Self, I::Error>
+    where
+        I: super::IntoPeriodicTimer<PeriodicTimer = T>,
+    {
+        assert!(
+            !resolution.is_zero(),
+            "the resolution of a timer service must not be zero"
+        );
+        let timer =
+            cell::RefCell::new(timer.into_periodic_timer(time::Rate::from_period(resolution))?);
+        let state = cell::RefCell::new(State {
+            started: false,
+            now: 0,
+            driver: None,
+            slots: array::from_fn(|_| Slot::default()),
+        });
+        Ok(Self {
+            resolution,
+            timer,
+            state,
+        })
+    }
+
+    /// Creates a new software timer, or returns `None` if all slots are in use.
+    pub fn try_timer(&self) -> Option<Handle<'_, T, N>> {
+        let mut state = self.state.borrow_mut();
+        let slot = state.slots.iter().position(|slot| !slot.in_use)?;
+        state.slots[slot] = Slot {
+            in_use: true,
+            ..Slot::default()
+        };
+        Some(Handle {
+            service: self,
+            slot,
+        })
+    }
+
+    /// Creates a new software timer.
+    ///
+    /// Panics if all slots are in use.
+    pub fn timer(&self) -> Handle<'_, T, N> {
+        self.try_timer()
+            .expect("all timer service slots are in use")
+    }
+
+    /// Creates a oneshot software timer with the specified delay.
+    ///
+    /// Panics if all slots are in use.
+    pub fn delay(&self, duration: time::Duration) -> Handle<'_, T, N> {
+        let handle = self.timer();
+        handle.configure(false, self.delay_ticks(duration));
+        handle
+    }
+
+    /// Creates a periodic software timer with the specified rate.
+    ///
+    /// Panics if all slots are in use.
+    pub fn interval(&self, rate: time::Rate) -> Handle<'_, T, N> {
+        let handle = self.timer();
+        handle.configure(true, self.period_ticks(rate));
+        handle
+    }
+
+    /// Waits for the specified duration.
+    ///
+    /// Panics if all slots are in use.
+    pub fn sleep(&self, duration: time::Duration) -> Sleep<'_, T, N> {
+        let handle = self.delay(duration);
+        let started = false;
+        Sleep { handle, started }
+    }
+
+    fn delay_ticks(&self, duration: time::Duration) -> u64 {
+        let ticks = duration.as_nanos().div_ceil(self.resolution.as_nanos());
+        // Add one tick, since the current tick has already partially passed.
+        ticks.saturating_add(1)
+    }
+
+    fn period_ticks(&self, rate: time::Rate) -> u64 {
+        rate.period_ticks(time::Rate::from_period(self.resolution))
+            .unwrap_or(u64::MAX)
+            .max(1)
+    }
+
+    /// Polls the hardware timer and expires any slots whose deadlines have passed.
+    fn drive(&self, slot: usize, cx: &mut task::Context<'_>) -> Result<(), T::Error> {
+        let mut timer = self.timer.borrow_mut();
+        let mut state = self.state.borrow_mut();
+        state.driver = Some(slot);
+
+        if !state.started && !state.is_idle() {
+            match pin::Pin::new(&mut *timer).poll_start(cx) {
+                task::Poll::Ready(Ok(())) => state.started = true,
+                task::Poll::Ready(Err(err)) => return Err(err),
+                task::Poll::Pending => return Ok(()),
+            }
+        }
+
+        while state.started {
+            match pin::Pin::new(&mut *timer).poll_tick(cx) {
+                task::Poll::Ready(Ok(())) => state.tick(),
+                task::Poll::Ready(Err(err)) => return Err(err),
+                task::Poll::Pending => break,
+            }
+        }
+
+        state.stop_if_idle(&mut *timer, cx)
+    }
+
+    /// Stops the hardware timer if no handle is waiting for a deadline anymore.
+    fn stop_if_idle(&self, cx: &mut task::Context<'_>) -> Result<(), T::Error> {
+        let mut timer = self.timer.borrow_mut();
+        self.state.borrow_mut().stop_if_idle(&mut *timer, cx)
+    }
+}
+
+impl<const N: usize> State<N> {
+    fn is_idle(&self) -> bool {
+        self.slots.iter().all(|slot| slot.deadline.is_none())
+    }
+
+    fn stop_if_idle<T>(&mut self, timer: &mut T, cx: &mut task::Context<'_>) -> Result<(), T::Error>
+    where
+        T: super::CancellableTimer + Unpin,
+    {
+        if !self.started || !self.is_idle() {
+            return Ok(());
+        }
+
+        // If cancelling doesn't complete right away, it is tried again on the next poll.
+        match pin::Pin::new(timer).poll_cancel(cx) {
+            task::Poll::Ready(Ok(())) => self.started = false,
+            task::Poll::Ready(Err(err)) => return Err(err),
+            task::Poll::Pending => {}
+        }
+        Ok(())
+    }
+
+    fn tick(&mut self) {
+        self.now += 1;
+        let now = self.now;
+
+        for slot in self.slots.iter_mut() {
+            if let Some(deadline) = slot.deadline.filter(|&deadline| deadline <= now) {
+                slot.pending += 1;
+                slot.deadline = if slot.periodic {
+                    Some(deadline.saturating_add(slot.period))
+                } else {
+                    None
+                };
+                if let Some(waker) = slot.waker.take() {
+                    waker.wake();
+                }
+            }
+        }
+    }
+
+    /// Makes another waiting slot take over polling the hardware timer from the specified slot.
+    fn hand_over(&mut self, from: usize) {
+        if self.driver != Some(from) {
+            return;
+        }
+        self.driver = None;
+
+        let next = self
+            .slots
+            .iter()
+            .enumerate()
+            .filter(|&(index, slot)| index != from && slot.deadline.is_some())
+            .filter_map(|(_, slot)| slot.waker.as_ref())
+            .next();
+        if let Some(waker) = next {
+            waker.wake_by_ref();
+        }
+    }
+}
+
+impl<T, const N: usize> Handle<'_, T, N>
+where
+    T: super::CancellableTimer + Unpin,
+{
+    fn configure(&self, periodic: bool, period: u64) {
+        let mut state = self.service.state.borrow_mut();
+        let slot = &mut state.slots[self.slot];
+        slot.periodic = periodic;
+        slot.period = period;
+        slot.deadline = None;
+        slot.pending = 0;
+    }
+}
+
+impl<T, const N: usize> super::Timer for Handle<'_, T, N>
+where
+    T: super::CancellableTimer + Unpin,
+{
+    type Error = T::Error;
+
+    fn poll_start(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        let mut state = self.service.state.borrow_mut();
+        let now = state.now;
+        let slot = &mut state.slots[self.slot];
+        slot.deadline = Some(now.saturating_add(slot.period));
+        slot.pending = 0;
+        task::Poll::Ready(Ok(()))
+    }
+
+    fn poll_tick(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        self.service.drive(self.slot, cx)?;
+
+        let mut state = self.service.state.borrow_mut();
+        let slot = &mut state.slots[self.slot];
+        if slot.pending > 0 {
+            slot.pending -= 1;
+            state.hand_over(self.slot);
+            task::Poll::Ready(Ok(()))
+        } else {
+            slot.waker = Some(cx.waker().clone());
+            task::Poll::Pending
+        }
+    }
+
+    fn poll_ticks(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<u32, Self::Error>> {
+        self.service.drive(self.slot, cx)?;
+
+        let mut state = self.service.state.borrow_mut();
+        let slot = &mut state.slots[self.slot];
+        if slot.pending > 0 {
+            let count = mem::take(&mut slot.pending);
+            state.hand_over(self.slot);
+            task::Poll::Ready(Ok(count))
+        } else {
+            slot.waker = Some(cx.waker().clone());
+            task::Poll::Pending
+        }
+    }
+}
+
+impl<T, const N: usize> super::CancellableTimer for Handle<'_, T, N>
+where
+    T: super::CancellableTimer + Unpin,
+{
+    fn poll_cancel(
+        self: pin::Pin<&mut Self>,
+        cx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        {
+            let mut state = self.service.state.borrow_mut();
+            state.hand_over(self.slot);
+            let slot = &mut state.slots[self.slot];
+            slot.deadline = None;
+            slot.pending = 0;
+        }
+        task::Poll::Ready(self.service.stop_if_idle(cx))
+    }
+}
+
+impl<T, const N: usize> super::RestartableTimer for Handle<'_, T, N>
+where
+    T: super::CancellableTimer + Unpin,
+{
+    fn poll_restart(
+        self: pin::Pin<&mut Self>,
+        _cx: &mut task::Context<'_>,
+        delay: time::Duration,
+    ) -> task::Poll<Result<(), Self::Error>> {
+        let mut state = self.service.state.borrow_mut();
+        let now = state.now;
+        let slot = &mut state.slots[self.slot];
+        slot.period = if slot.periodic {
+            (delay.as_nanos() / self.service.resolution.as_nanos()).max(1)
+        } else {
+            self.service.delay_ticks(delay)
+        };
+        slot.deadline = Some(now.saturating_add(slot.period));
+        slot.pending = 0;
+        task::Poll::Ready(Ok(()))
+    }
+}
+
+impl<'a, T, const N: usize> super::IntoOneshotTimer for Handle<'a, T, N>
+where
+    T: super::CancellableTimer + Unpin,
+{
+    type OneshotTimer = Self;
+
+    fn into_oneshot_timer(self, delay: time::Duration) -> Result<Self::OneshotTimer, Self::Error> {
+        self.configure(false, self.service.delay_ticks(delay));
+        Ok(self)
+    }
+}
+
+impl<'a, T, const N: usize> super::IntoPeriodicTimer for Handle<'a, T, N>
+where
+    T: super::CancellableTimer + Unpin,
+{
+    type PeriodicTimer = Self;
+
+    fn into_periodic_timer(self, rate: time::Rate) -> Result<Self::PeriodicTimer, Self::Error> {
+        self.configure(true, self.service.period_ticks(rate));
+        Ok(self)
+    }
+}
+
+impl<T, const N: usize> Drop for Handle<'_, T, N>
+where
+    T: super::CancellableTimer + Unpin,
+{
+    fn drop(&mut self) {
+        {
+            let mut state = self.service.state.borrow_mut();
+            state.hand_over(self.slot);
+            state.slots[self.slot] = Slot::default();
+        }
+        // There is no context to poll with here, but nothing needs to be woken by cancelling
+        // either.  Errors can't be reported from here, so they are ignored.
+        let mut cx = task::Context::from_waker(futures::task::noop_waker_ref());
+        let _ = self.service.stop_if_idle(&mut cx);
+    }
+}
+
+impl<T, const N: usize> future::Future for Sleep<'_, T, N>
+where
+    T: super::CancellableTimer + Unpin,
+{
+    type Output = Result<(), T::Error>;
+
+    fn poll(mut self: pin::Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        use super::Timer;
+
+        let this = &mut *self;
+        if !this.started {
+            match pin::Pin::new(&mut this.handle).poll_start(cx) {
+                task::Poll::Ready(Ok(())) => this.started = true,
+                task::Poll::Ready(Err(err)) => return task::Poll::Ready(Err(err)),
+                task::Poll::Pending => return task::Poll::Pending,
+            }
+        }
+        pin::Pin::new(&mut this.handle).poll_tick(cx)
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for Service<T, N>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Service")
+            .field("resolution", &self.resolution)
+            .field("timer", &self.timer)
+            .field("slots", &N)
+            .finish()
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for Handle<'_, T, N>
+where
+    T: super::CancellableTimer + Unpin,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handle").field("slot", &self.slot).finish()
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for Sleep<'_, T, N>
+where
+    T: super::CancellableTimer + Unpin,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sleep")
+            .field("handle", &self.handle)
+            .field("started", &self.started)
+            .finish()
+    }
+}
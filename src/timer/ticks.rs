@@ -1,33 +1,101 @@
 use core::pin;
 use core::task;
 
+/// What a [`Ticks`] stream should do when it is polled too late to observe every tick of its
+/// timer.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum MissedTickBehavior {
+    /// Produce one item for every missed tick, as fast as the stream is polled, until it has
+    /// caught up with the timer.
+    #[default]
+    Burst,
+    /// Produce a single item for all missed ticks, and keep ticking on the original schedule.
+    Skip,
+    /// Produce a single item for all missed ticks, and restart the timer so that the next tick is
+    /// one whole period later.
+    Delay,
+}
+
+/// A stream of the ticks of a timer.
+///
+/// Each item is the number of periods that were missed by the time that the tick was observed,
+/// i.e. zero when the stream is keeping up with the timer.  In [`MissedTickBehavior::Burst`] mode,
+/// this counts down as the missed ticks are produced.
 #[derive(Debug)]
-#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[must_use = "streams do nothing unless polled"]
 pub struct Ticks<'a, A>
 where
     A: super::Timer + Unpin + ?Sized,
 {
     timer: &'a mut A,
+    behavior: MissedTickBehavior,
+    backlog: u32,
+    restart: bool,
+}
+
+pub fn ticks<A>(timer: &mut A, behavior: MissedTickBehavior) -> Ticks<A>
+where
+    A: super::Timer + Unpin + ?Sized,
+{
+    let backlog = 0;
+    let restart = false;
+    Ticks {
+        timer,
+        behavior,
+        backlog,
+        restart,
+    }
 }
 
-pub fn ticks<A>(timer: &mut A) -> Ticks<A>
+impl<A> Ticks<'_, A>
 where
     A: super::Timer + Unpin + ?Sized,
 {
-    Ticks { timer }
+    fn poll_restart(&mut self, cx: &mut task::Context<'_>) -> task::Poll<Result<(), A::Error>> {
+        if self.restart {
+            futures::ready!(pin::Pin::new(&mut *self.timer).poll_start(cx))?;
+            self.restart = false;
+        }
+        task::Poll::Ready(Ok(()))
+    }
 }
 
 impl<A> futures::stream::Stream for Ticks<'_, A>
 where
     A: super::Timer + Unpin + ?Sized,
 {
-    type Item = Result<(), A::Error>;
+    type Item = Result<u32, A::Error>;
 
     fn poll_next(
         mut self: pin::Pin<&mut Self>,
         cx: &mut task::Context<'_>,
     ) -> task::Poll<Option<Self::Item>> {
         let this = &mut *self;
-        pin::Pin::new(&mut *this.timer).poll_tick(cx).map(Some)
+
+        if let Err(err) = futures::ready!(this.poll_restart(cx)) {
+            return task::Poll::Ready(Some(Err(err)));
+        }
+
+        if this.backlog > 0 {
+            this.backlog -= 1;
+            return task::Poll::Ready(Some(Ok(this.backlog)));
+        }
+
+        let missed = match futures::ready!(pin::Pin::new(&mut *this.timer).poll_ticks(cx)) {
+            Ok(count) => count.saturating_sub(1),
+            Err(err) => return task::Poll::Ready(Some(Err(err))),
+        };
+
+        match this.behavior {
+            MissedTickBehavior::Burst => this.backlog = missed,
+            MissedTickBehavior::Skip => {}
+            MissedTickBehavior::Delay => {
+                this.restart = missed > 0;
+                // Restart right away if possible; otherwise it is retried on the next poll.
+                let _ = this.poll_restart(cx);
+            }
+        }
+
+        task::Poll::Ready(Some(Ok(missed)))
     }
 }
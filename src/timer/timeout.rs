@@ -0,0 +1,160 @@
+use crate::clock;
+use crate::time;
+use core::fmt;
+use core::future;
+use core::pin;
+use core::task;
+
+/// The error produced when a future didn't complete before its timeout elapsed.
+///
+/// Timer error types implement `From<Elapsed>`, so that timeouts can be reported through the same
+/// error type as the peripherals of a platform.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Elapsed;
+
+pin_project_lite::pin_project! {
+    /// A future that runs another future with a time limit, measured by a oneshot timer.
+    ///
+    /// The timer is only started once the future has been polled and turned out not to be ready.
+    /// Either way, it is handed back along with the result when the timeout completes, so that it
+    /// can be used again.  If the future won the race, the timer may still be running; starting
+    /// it again discards its pending tick.
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub struct Timeout<T, F> {
+        #[pin]
+        future: F,
+        timer: Option<T>,
+        started: bool,
+    }
+}
+
+pub fn timeout<T, F>(
+    timer: T,
+    duration: time::Duration,
+    future: F,
+) -> Result<Timeout<T::OneshotTimer, F>, T::Error>
+where
+    T: super::IntoOneshotTimer,
+    F: future::Future,
+{
+    let timer = Some(timer.into_oneshot_timer(duration)?);
+    let started = false;
+    Ok(Timeout {
+        future,
+        timer,
+        started,
+    })
+}
+
+pub fn deadline<T, C, F>(
+    timer: T,
+    clock: &C,
+    deadline: time::Instant,
+    future: F,
+) -> Result<Timeout<T::OneshotTimer, F>, T::Error>
+where
+    T: super::IntoOneshotTimer,
+    C: clock::Clock + ?Sized,
+    F: future::Future,
+{
+    let duration = deadline.saturating_duration_since(clock.now());
+    timeout(timer, duration, future)
+}
+
+/// An extension trait for putting a time limit on any future.
+pub trait WithTimeout: future::Future + Sized {
+    /// Runs this future, but fails with [`Elapsed`] if it doesn't complete within the specified
+    /// duration, as measured by the specified timer.
+    ///
+    /// Fails right away if the timer can't be configured with the duration.
+    fn with_timeout<T>(
+        self,
+        timer: T,
+        duration: time::Duration,
+    ) -> Result<Timeout<T::OneshotTimer, Self>, T::Error>
+    where
+        T: super::IntoOneshotTimer,
+    {
+        timeout(timer, duration, self)
+    }
+
+    /// Runs this future, but fails with [`Elapsed`] if it doesn't complete before the specified
+    /// instant of the specified clock.
+    ///
+    /// The time remaining until the deadline is computed when this method is called.
+    fn with_deadline<T, C>(
+        self,
+        timer: T,
+        clock: &C,
+        instant: time::Instant,
+    ) -> Result<Timeout<T::OneshotTimer, Self>, T::Error>
+    where
+        T: super::IntoOneshotTimer,
+        C: clock::Clock + ?Sized,
+    {
+        deadline(timer, clock, instant, self)
+    }
+}
+
+impl<F> WithTimeout for F where F: future::Future {}
+
+impl<T, F> future::Future for Timeout<T, F>
+where
+    T: super::Timer + Unpin,
+    T::Error: From<Elapsed>,
+    F: future::Future,
+{
+    type Output = (T, Result<F::Output, T::Error>);
+
+    fn poll(self: pin::Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<Self::Output> {
+        let this = self.project();
+        let timer = this
+            .timer
+            .as_mut()
+            .expect("timeout polled after completion");
+
+        let result = if let task::Poll::Ready(output) = this.future.poll(cx) {
+            Ok(output)
+        } else {
+            if !*this.started {
+                match super::Timer::poll_start(pin::Pin::new(&mut *timer), cx) {
+                    task::Poll::Ready(Ok(())) => *this.started = true,
+                    task::Poll::Ready(Err(err)) => {
+                        return task::Poll::Ready(finish(this.timer, Err(err)))
+                    }
+                    task::Poll::Pending => return task::Poll::Pending,
+                }
+            }
+
+            match super::Timer::poll_tick(pin::Pin::new(&mut *timer), cx) {
+                task::Poll::Ready(Ok(())) => Err(Elapsed.into()),
+                task::Poll::Ready(Err(err)) => Err(err),
+                task::Poll::Pending => return task::Poll::Pending,
+            }
+        };
+
+        task::Poll::Ready(finish(this.timer, result))
+    }
+}
+
+/// Hands back the timer of a completed timeout along with its result.
+fn finish<T, R>(timer: &mut Option<T>, result: R) -> (T, R) {
+    (
+        timer.take().expect("timeout polled after completion"),
+        result,
+    )
+}
+
+impl<T, F> fmt::Debug for Timeout<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timeout")
+            .field("armed", &self.started)
+            .finish()
+    }
+}
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("deadline has elapsed")
+    }
+}
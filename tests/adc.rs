@@ -0,0 +1,43 @@
+use embedded_platform::adc::{Buffer, Config, Gain, Reading, BUFFER_LEN};
+
+#[test]
+fn full_scale_depends_on_gain() {
+    let config = Config::default();
+    assert_eq!(config.full_scale(600), 3600);
+
+    let config = Config {
+        gain: Gain::Two,
+        ..config
+    };
+    assert_eq!(config.full_scale(600), 300);
+}
+
+#[test]
+fn readings_are_rounded_down() {
+    assert_eq!(Reading::new(1, 12, 3600).millivolts, 0);
+    assert_eq!(Reading::new(4095, 12, 3600).millivolts, 3599);
+    assert_eq!(Reading::new(-4, 12, 3600).millivolts, -4);
+}
+
+#[test]
+fn buffers_convert_samples_to_readings() {
+    let mut samples = [0; BUFFER_LEN];
+    samples[0] = 2048;
+    samples[1] = -4;
+    let buffer = Buffer::new(samples, 12, 3600, 1);
+
+    let readings: Vec<Reading> = buffer.readings().take(2).collect();
+    let expected = vec![
+        Reading {
+            raw: 2048,
+            millivolts: 1800,
+        },
+        Reading {
+            raw: -4,
+            millivolts: -4,
+        },
+    ];
+    assert_eq!(readings, expected);
+    assert_eq!(buffer.readings().len(), BUFFER_LEN);
+    assert_eq!(buffer.overruns(), 1);
+}
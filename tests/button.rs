@@ -0,0 +1,141 @@
+use embedded_platform::gpio::button::{Active, Config, Event};
+use embedded_platform::prelude::*;
+use embedded_platform::time::Duration;
+use futures::stream;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+/// The levels that a scripted pin reads, one per sample.
+type Script = Rc<RefCell<VecDeque<bool>>>;
+
+/// An input pin that reads the next level of its script every time that it is read.
+#[derive(Debug)]
+struct ScriptedPin(Script);
+
+impl embedded_platform::gpio::Pin for ScriptedPin {
+    type Error = ();
+}
+
+impl embedded_platform::gpio::InputPin for ScriptedPin {
+    fn poll_get(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<bool, ()>> {
+        let high = self
+            .0
+            .borrow_mut()
+            .pop_front()
+            .expect("script is exhausted");
+        Poll::Ready(Ok(high))
+    }
+}
+
+/// A periodic timer that ticks as long as the script of its pin has levels left.
+#[derive(Debug)]
+struct ScriptedTimer(Script);
+
+impl embedded_platform::timer::Timer for ScriptedTimer {
+    type Error = ();
+
+    fn poll_start(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), ()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_tick(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), ()>> {
+        if self.0.borrow().is_empty() {
+            Poll::Pending
+        } else {
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+impl embedded_platform::timer::IntoPeriodicTimer for ScriptedTimer {
+    type PeriodicTimer = Self;
+
+    fn into_periodic_timer(self, _rate: embedded_platform::time::Rate) -> Result<Self, ()> {
+        Ok(self)
+    }
+}
+
+fn button(
+    config: Config,
+) -> (
+    impl stream::Stream<Item = Result<Event, ()>> + Unpin,
+    Script,
+) {
+    let script = Script::default();
+    let pin = ScriptedPin(script.clone());
+    let button = pin.button(ScriptedTimer(script.clone()), config).unwrap();
+    (button, script)
+}
+
+/// Appends a number of samples at the specified level to a script.
+fn hold(script: &Script, high: bool, samples: usize) {
+    script
+        .borrow_mut()
+        .extend(std::iter::repeat_n(high, samples));
+}
+
+fn next<S>(stream: &mut S) -> Poll<Option<S::Item>>
+where
+    S: stream::Stream + Unpin,
+{
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    Pin::new(stream).poll_next(&mut cx)
+}
+
+#[test]
+fn click_is_reported_after_double_click_window() {
+    let (mut button, script) = button(Config::new(Active::Low));
+
+    hold(&script, true, 3);
+    hold(&script, false, 5);
+    hold(&script, true, 20);
+    assert_eq!(next(&mut button), Poll::Pending);
+
+    hold(&script, true, 20);
+    assert_eq!(next(&mut button), Poll::Ready(Some(Ok(Event::Click))));
+    assert_eq!(next(&mut button), Poll::Pending);
+}
+
+#[test]
+fn two_quick_clicks_are_a_double_click() {
+    let (mut button, script) = button(Config::new(Active::Low));
+
+    hold(&script, false, 5);
+    hold(&script, true, 10);
+    hold(&script, false, 5);
+    hold(&script, true, 50);
+    assert_eq!(next(&mut button), Poll::Ready(Some(Ok(Event::DoubleClick))));
+    assert_eq!(next(&mut button), Poll::Pending);
+}
+
+#[test]
+fn long_press_is_reported_while_held() {
+    let config = Config::new(Active::High).long_press(Duration::from_millis(500));
+    let (mut button, script) = button(config);
+
+    hold(&script, true, 40);
+    assert_eq!(next(&mut button), Poll::Pending);
+
+    hold(&script, true, 20);
+    assert_eq!(next(&mut button), Poll::Ready(Some(Ok(Event::LongPress))));
+
+    hold(&script, false, 50);
+    assert_eq!(next(&mut button), Poll::Pending);
+}
+
+#[test]
+fn clicks_are_immediate_without_double_clicks() {
+    let config = Config::new(Active::High).double_click(Duration::ZERO);
+    let (mut button, script) = button(config);
+
+    hold(&script, true, 5);
+    hold(&script, false, 1);
+    assert_eq!(next(&mut button), Poll::Ready(Some(Ok(Event::Click))));
+
+    hold(&script, true, 5);
+    hold(&script, false, 1);
+    assert_eq!(next(&mut button), Poll::Ready(Some(Ok(Event::Click))));
+}
@@ -0,0 +1,87 @@
+//! Checks the drive configurations of the nRF52840 pins, which only depend on the shared traits.
+use embedded_platform::gpio::{DriveStrength, OpenDrainFlavor};
+
+#[path = "../platforms/nrf52840/src/gpio/drive.rs"]
+mod drive;
+
+use drive::Drive;
+
+const STRENGTHS: [DriveStrength; 2] = [DriveStrength::Standard, DriveStrength::High];
+const FLAVORS: [OpenDrainFlavor; 2] = [OpenDrainFlavor::DriveLow, OpenDrainFlavor::DriveHigh];
+
+/// The index of the specified drives in the `DRIVES` table, i.e. the value of the `DRIVE` field.
+fn index(drives: (Drive, Drive)) -> usize {
+    drive::DRIVES
+        .iter()
+        .position(|&entry| entry == drives)
+        .unwrap_or_else(|| panic!("{:?} can't be configured", drives))
+}
+
+/// The name of the specified drives in the reference manual, e.g. `S0D1`.
+fn name((low, high): (Drive, Drive)) -> String {
+    let letter = |drive| match drive {
+        Drive::Standard => 'S',
+        Drive::High => 'H',
+        Drive::Disconnect => 'D',
+    };
+    format!("{}0{}1", letter(low), letter(high))
+}
+
+#[test]
+fn drive_field_values_match_the_reference_manual() {
+    let names: Vec<_> = drive::DRIVES.iter().map(|&drives| name(drives)).collect();
+    assert_eq!(
+        names,
+        vec!["S0S1", "H0S1", "S0H1", "H0H1", "D0S1", "D0H1", "S0D1", "H0D1"]
+    );
+}
+
+#[test]
+fn drive_strength_applies_to_the_driven_levels() {
+    for &drives in drive::DRIVES.iter() {
+        for &strength in STRENGTHS.iter() {
+            let expected = match strength {
+                DriveStrength::Standard => Drive::Standard,
+                DriveStrength::High => Drive::High,
+            };
+            let (low, high) = drive::with_strength(drives, strength);
+            let apply = |before, after| match before {
+                Drive::Disconnect => assert_eq!(after, Drive::Disconnect),
+                Drive::Standard | Drive::High => assert_eq!(after, expected),
+            };
+            apply(drives.0, low);
+            apply(drives.1, high);
+            index((low, high));
+        }
+    }
+}
+
+#[test]
+fn open_drain_flavor_disconnects_the_other_level() {
+    for &strength in STRENGTHS.iter() {
+        for &flavor in FLAVORS.iter() {
+            let expected = match (strength, flavor) {
+                (DriveStrength::Standard, OpenDrainFlavor::DriveLow) => "S0D1",
+                (DriveStrength::Standard, OpenDrainFlavor::DriveHigh) => "D0S1",
+                (DriveStrength::High, OpenDrainFlavor::DriveLow) => "H0D1",
+                (DriveStrength::High, OpenDrainFlavor::DriveHigh) => "D0H1",
+            };
+
+            // Starting out from the default configuration of open drain pins, the strength and
+            // the flavor can be configured in either order.
+            let default = drive::DRIVES[6];
+            assert_eq!(name(default), "S0D1");
+            let strength_first =
+                drive::with_flavor(drive::with_strength(default, strength), flavor);
+            let flavor_first = drive::with_strength(drive::with_flavor(default, flavor), strength);
+            assert_eq!(name(strength_first), expected);
+            assert_eq!(name(flavor_first), expected);
+
+            // Changing the flavor keeps the strength, whichever configuration the pin had.
+            for &drives in drive::DRIVES.iter() {
+                let drives = drive::with_flavor(drive::with_strength(drives, strength), flavor);
+                assert_eq!(name(drives), expected);
+            }
+        }
+    }
+}
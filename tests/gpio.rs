@@ -0,0 +1,123 @@
+use embedded_platform::gpio::{ConfigureOpenDrain, Inverted, NoConnect, OpenDrainFlavor};
+use embedded_platform::prelude::*;
+use futures::future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A push-pull output pin that remembers the level that it is set to.
+#[derive(Debug, Default)]
+struct MockPin(bool);
+
+impl embedded_platform::gpio::Pin for MockPin {
+    type Error = ();
+}
+
+impl embedded_platform::gpio::OutputPin for MockPin {
+    fn poll_set(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        high: bool,
+    ) -> Poll<Result<(), ()>> {
+        self.0 = high;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl embedded_platform::gpio::StatefulOutputPin for MockPin {
+    fn poll_is_set_high(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<bool, ()>> {
+        Poll::Ready(Ok(self.0))
+    }
+}
+
+impl embedded_platform::gpio::IntoPushPullOutputPin for MockPin {
+    type PushPullOutputPin = Self;
+
+    fn into_push_pull_output_pin(self, initial_high: bool) -> Result<Self, ()> {
+        Ok(MockPin(initial_high))
+    }
+}
+
+/// An open drain output pin that remembers the level that it actively drives.
+#[derive(Debug)]
+struct MockOpenDrainPin(OpenDrainFlavor);
+
+impl embedded_platform::gpio::Pin for MockOpenDrainPin {
+    type Error = ();
+}
+
+impl embedded_platform::gpio::OutputPin for MockOpenDrainPin {
+    fn poll_set(self: Pin<&mut Self>, _cx: &mut Context<'_>, _high: bool) -> Poll<Result<(), ()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl ConfigureOpenDrain for MockOpenDrainPin {
+    fn set_open_drain_flavor(&mut self, flavor: OpenDrainFlavor) -> Result<(), ()> {
+        self.0 = flavor;
+        Ok(())
+    }
+}
+
+fn poll<F>(future: &mut F) -> Poll<F::Output>
+where
+    F: future::Future + Unpin,
+{
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    Pin::new(future).poll(&mut cx)
+}
+
+#[test]
+fn inverted_pins_read_the_opposite_level() {
+    let mut pin = Inverted::new(NoConnect::new(false))
+        .into_pull_up_input_pin()
+        .unwrap();
+    assert_eq!(poll(&mut pin.get()), Poll::Ready(Ok(true)));
+}
+
+#[test]
+fn inverted_pins_write_the_opposite_level() {
+    let mut led = Inverted::new(MockPin::default())
+        .into_push_pull_output_pin(false)
+        .unwrap();
+    assert_eq!(poll(&mut led.is_set_high()), Poll::Ready(Ok(false)));
+
+    assert_eq!(poll(&mut led.set(true)), Poll::Ready(Ok(())));
+    assert_eq!(poll(&mut led.is_set_high()), Poll::Ready(Ok(true)));
+    assert!(!led.into_inner().0);
+}
+
+#[test]
+fn toggling_an_inverted_pin_toggles_the_underlying_pin() {
+    let mut led = Inverted::new(MockPin(true));
+    assert_eq!(poll(&mut led.toggle()), Poll::Ready(Ok(())));
+    assert_eq!(poll(&mut led.is_set_high()), Poll::Ready(Ok(true)));
+    assert!(!led.into_inner().0);
+}
+
+#[test]
+fn inverted_pin_drives_the_inverse_open_drain_level() {
+    let mut pin = Inverted::new(MockOpenDrainPin(OpenDrainFlavor::DriveLow));
+
+    pin.set_open_drain_flavor(OpenDrainFlavor::DriveLow)
+        .unwrap();
+    assert_eq!(pin.into_inner().0, OpenDrainFlavor::DriveHigh);
+
+    let mut pin = Inverted::new(MockOpenDrainPin(OpenDrainFlavor::DriveLow));
+    pin.set_open_drain_flavor(OpenDrainFlavor::DriveHigh)
+        .unwrap();
+    assert_eq!(pin.into_inner().0, OpenDrainFlavor::DriveLow);
+}
+
+#[test]
+fn no_connect_pins_read_back_their_fixed_level() {
+    for &level in [false, true].iter() {
+        let mut pin = NoConnect::new(level);
+        assert_eq!(poll(&mut pin.get()), Poll::Ready(Ok(level)));
+        assert_eq!(poll(&mut pin.is_set_high()), Poll::Ready(Ok(level)));
+
+        // Writes are no-ops, so toggling doesn't change either readback.
+        assert_eq!(poll(&mut pin.toggle()), Poll::Ready(Ok(())));
+        assert_eq!(poll(&mut pin.get()), Poll::Ready(Ok(level)));
+        assert_eq!(poll(&mut pin.is_set_high()), Poll::Ready(Ok(level)));
+    }
+}
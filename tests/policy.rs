@@ -0,0 +1,242 @@
+use core::executor::Executor;
+use embedded_platform::platform::policy::{self, Action, Outcome, Policy, Stage};
+use embedded_platform::platform::Platform;
+use embedded_platform::time::{Duration, Instant};
+use futures::future;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+use std::thread::LocalKey;
+
+#[derive(Debug, PartialEq)]
+struct Error;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Event {
+    Initialize,
+    Run,
+    Report(Stage),
+    Delay(Duration),
+}
+
+type Results = RefCell<VecDeque<Result<(), Error>>>;
+
+thread_local! {
+    static LOG: RefCell<Vec<Event>> = const { RefCell::new(Vec::new()) };
+    /// The results of the upcoming initializations; initializing succeeds once they are used up.
+    static INITIALIZE: Results = const { RefCell::new(VecDeque::new()) };
+    /// The results of the upcoming application runs; running succeeds once they are used up.
+    static RUN: Results = const { RefCell::new(VecDeque::new()) };
+}
+
+static EXECUTOR: Executor<1, 64> = Executor::new();
+static EXECUTOR_LOCK: Mutex<()> = Mutex::new(());
+
+fn log(event: Event) {
+    LOG.with(|log| log.borrow_mut().push(event));
+}
+
+fn next(results: &'static LocalKey<Results>) -> Result<(), Error> {
+    results.with(|results| results.borrow_mut().pop_front().unwrap_or(Ok(())))
+}
+
+#[derive(Clone, Debug)]
+struct MockClock;
+
+impl embedded_platform::clock::Clock for MockClock {
+    fn now(&self) -> Instant {
+        Instant::from_nanos(0)
+    }
+}
+
+#[derive(Debug)]
+struct MockPlatform;
+
+impl Platform for MockPlatform {
+    type Error = Error;
+    type Clock = MockClock;
+
+    fn main<I, F>(_policy: Policy<Error>, _run: I) -> !
+    where
+        I: FnMut(Self, core::executor::Spawner) -> F,
+        F: future::Future<Output = Result<(), Error>>,
+    {
+        unreachable!("the tests drive `policy::supervise` directly")
+    }
+
+    fn poll_initialize(_cx: &mut Context<'_>) -> Poll<Result<Self, Error>> {
+        log(Event::Initialize);
+        Poll::Ready(next(&INITIALIZE).map(|()| MockPlatform))
+    }
+
+    fn clock(&self) -> MockClock {
+        MockClock
+    }
+}
+
+/// Supervises a mock application with the specified initialization and run results, returning the
+/// outcome along with everything that happened in the meantime.
+fn supervise(
+    policy: Policy<Error>,
+    initialize: Vec<Result<(), Error>>,
+    run: Vec<Result<(), Error>>,
+) -> (Outcome, Vec<Event>) {
+    let _lock = EXECUTOR_LOCK.lock().unwrap();
+    LOG.with(|log| log.borrow_mut().clear());
+    INITIALIZE.with(|results| *results.borrow_mut() = initialize.into());
+    RUN.with(|results| *results.borrow_mut() = run.into());
+
+    let policy = policy.report_to(|stage, _error| log(Event::Report(stage)));
+    let run = |_platform: MockPlatform, _spawner| {
+        log(Event::Run);
+        future::ready(next(&RUN))
+    };
+    let delay = |backoff| {
+        log(Event::Delay(backoff));
+        future::ready(())
+    };
+    let outcome = EXECUTOR.block_on(
+        |spawner| policy::supervise::<MockPlatform, _, _, _, _>(policy, spawner, run, delay),
+        || panic!("the mock platform never waits"),
+    );
+
+    (outcome, LOG.with(|log| log.borrow_mut().split_off(0)))
+}
+
+#[test]
+fn completes_without_errors() {
+    let (outcome, events) = supervise(Policy::new(), vec![], vec![]);
+
+    assert_eq!(outcome, Outcome::Completed);
+    assert_eq!(events, vec![Event::Initialize, Event::Run]);
+}
+
+#[test]
+fn halts_by_default() {
+    let (outcome, events) = supervise(Policy::new(), vec![Err(Error)], vec![]);
+    assert_eq!(outcome, Outcome::Halt(Stage::Initialize));
+    assert_eq!(
+        events,
+        vec![Event::Initialize, Event::Report(Stage::Initialize)]
+    );
+
+    let (outcome, events) = supervise(Policy::new(), vec![], vec![Err(Error)]);
+    assert_eq!(outcome, Outcome::Halt(Stage::Run));
+    assert_eq!(
+        events,
+        vec![Event::Initialize, Event::Run, Event::Report(Stage::Run)]
+    );
+}
+
+#[test]
+fn resets_when_configured() {
+    let policy = Policy::new().on_run_error(Action::Reset);
+    let (outcome, events) = supervise(policy, vec![], vec![Err(Error)]);
+
+    assert_eq!(outcome, Outcome::Reset);
+    assert_eq!(
+        events,
+        vec![Event::Initialize, Event::Run, Event::Report(Stage::Run)]
+    );
+}
+
+#[test]
+fn retries_with_exponential_backoff_until_the_application_succeeds() {
+    let policy = Policy::new()
+        .on_run_error(Action::Retry(3))
+        .backoff(Duration::from_millis(10));
+    let (outcome, events) = supervise(policy, vec![], vec![Err(Error), Err(Error)]);
+
+    assert_eq!(outcome, Outcome::Completed);
+    assert_eq!(
+        events,
+        vec![
+            Event::Initialize,
+            Event::Run,
+            Event::Report(Stage::Run),
+            Event::Delay(Duration::from_millis(10)),
+            Event::Initialize,
+            Event::Run,
+            Event::Report(Stage::Run),
+            Event::Delay(Duration::from_millis(20)),
+            Event::Initialize,
+            Event::Run,
+        ]
+    );
+}
+
+#[test]
+fn halts_once_retries_are_used_up() {
+    let policy = Policy::new()
+        .on_run_error(Action::Retry(2))
+        .backoff(Duration::from_millis(10));
+    let (outcome, events) = supervise(policy, vec![], vec![Err(Error), Err(Error), Err(Error)]);
+
+    assert_eq!(outcome, Outcome::Halt(Stage::Run));
+    let runs = events.iter().filter(|&&event| event == Event::Run).count();
+    assert_eq!(runs, 3);
+    let delays: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Delay(delay) => Some(*delay),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        delays,
+        vec![Duration::from_millis(10), Duration::from_millis(20)]
+    );
+}
+
+#[test]
+fn retries_without_delay_by_default() {
+    let policy = Policy::new().on_initialize_error(Action::Retry(1));
+    let (outcome, events) = supervise(policy, vec![Err(Error)], vec![]);
+
+    assert_eq!(outcome, Outcome::Completed);
+    assert_eq!(
+        events,
+        vec![
+            Event::Initialize,
+            Event::Report(Stage::Initialize),
+            Event::Initialize,
+            Event::Run,
+        ]
+    );
+}
+
+#[test]
+fn counts_initialization_failures_consecutively() {
+    // Every successful initialization starts counting initialization failures from scratch, while
+    // application failures keep adding up.
+    let policy = Policy::new()
+        .on_initialize_error(Action::Retry(1))
+        .on_run_error(Action::Retry(1))
+        .backoff(Duration::from_millis(1));
+    let (outcome, events) = supervise(
+        policy,
+        vec![Err(Error), Ok(()), Err(Error)],
+        vec![Err(Error), Err(Error)],
+    );
+
+    assert_eq!(outcome, Outcome::Halt(Stage::Run));
+    assert_eq!(
+        events,
+        vec![
+            Event::Initialize,
+            Event::Report(Stage::Initialize),
+            Event::Delay(Duration::from_millis(1)),
+            Event::Initialize,
+            Event::Run,
+            Event::Report(Stage::Run),
+            Event::Delay(Duration::from_millis(1)),
+            Event::Initialize,
+            Event::Report(Stage::Initialize),
+            Event::Delay(Duration::from_millis(1)),
+            Event::Initialize,
+            Event::Run,
+            Event::Report(Stage::Run),
+        ]
+    );
+}
@@ -0,0 +1,115 @@
+use embedded_platform::gpio::sequential::Sequential;
+use embedded_platform::prelude::*;
+use futures::future;
+use std::cell::{Cell, RefCell};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+/// A log of the writes made to mock pins, as pairs of pin names and levels.
+type Log = Rc<RefCell<Vec<(&'static str, bool)>>>;
+
+/// An output pin that logs its writes, and that can be made busy.
+#[derive(Debug)]
+struct MockOutput {
+    name: &'static str,
+    log: Log,
+    busy: Rc<Cell<bool>>,
+}
+
+impl embedded_platform::gpio::Pin for MockOutput {
+    type Error = ();
+}
+
+impl embedded_platform::gpio::OutputPin for MockOutput {
+    fn poll_set(self: Pin<&mut Self>, _cx: &mut Context<'_>, high: bool) -> Poll<Result<(), ()>> {
+        if self.busy.get() {
+            return Poll::Pending;
+        }
+        self.log.borrow_mut().push((self.name, high));
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// An input pin that reads a fixed level.
+#[derive(Debug)]
+struct MockInput(bool);
+
+impl embedded_platform::gpio::Pin for MockInput {
+    type Error = ();
+}
+
+impl embedded_platform::gpio::InputPin for MockInput {
+    fn poll_get(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<bool, ()>> {
+        Poll::Ready(Ok(self.0))
+    }
+}
+
+fn output(name: &'static str, log: &Log) -> (MockOutput, Rc<Cell<bool>>) {
+    let busy = Rc::new(Cell::new(false));
+    let pin = MockOutput {
+        name,
+        log: log.clone(),
+        busy: busy.clone(),
+    };
+    (pin, busy)
+}
+
+fn poll<F>(future: &mut F) -> Poll<F::Output>
+where
+    F: future::Future + Unpin,
+{
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    Pin::new(future).poll(&mut cx)
+}
+
+#[test]
+fn masked_writes_only_touch_selected_pins() {
+    let log = Log::default();
+    let pins = [
+        output("d0", &log).0,
+        output("d1", &log).0,
+        output("d2", &log).0,
+    ];
+    let mut bus = Sequential::new(pins);
+
+    assert_eq!(poll(&mut bus.write_word(0b101)), Poll::Ready(Ok(())));
+    assert_eq!(
+        *log.borrow(),
+        vec![("d0", true), ("d1", false), ("d2", true)]
+    );
+
+    log.borrow_mut().clear();
+    assert_eq!(
+        poll(&mut bus.write_masked(0b110, 0b011)),
+        Poll::Ready(Ok(()))
+    );
+    assert_eq!(*log.borrow(), vec![("d1", true), ("d2", false)]);
+}
+
+#[test]
+fn interrupted_writes_resume_at_the_busy_pin() {
+    let log = Log::default();
+    let (d0, _) = output("d0", &log);
+    let (d1, busy) = output("d1", &log);
+    let (d2, _) = output("d2", &log);
+    let mut bus = Sequential::new((d0, d1, d2));
+    busy.set(true);
+
+    let mut write = bus.write_word(0b11);
+    assert_eq!(poll(&mut write), Poll::Pending);
+    assert_eq!(*log.borrow(), vec![("d0", true)]);
+
+    busy.set(false);
+    assert_eq!(poll(&mut write), Poll::Ready(Ok(())));
+    assert_eq!(
+        *log.borrow(),
+        vec![("d0", true), ("d1", true), ("d2", false)]
+    );
+}
+
+#[test]
+fn reads_pins_into_a_word() {
+    let mut bus = Sequential::new([MockInput(true), MockInput(false), MockInput(true)]);
+    assert_eq!(poll(&mut bus.read_word()), Poll::Ready(Ok(0b101)));
+}
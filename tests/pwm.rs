@@ -0,0 +1,22 @@
+use embedded_platform::pwm::Duty;
+
+#[test]
+fn duty_scales_to_the_nearest_count() {
+    assert_eq!(Duty::from_percent(50).scale(1000), 500);
+    assert_eq!(Duty::from_ratio(1, 3).scale(300), 100);
+    assert_eq!(Duty::ZERO.scale(u64::MAX), 0);
+    assert_eq!(Duty::FULL.scale(u64::MAX), u64::MAX);
+}
+
+#[test]
+fn duty_cycles_are_ordered() {
+    assert!(Duty::ZERO < Duty::from_percent(1));
+    assert!(Duty::from_percent(99) < Duty::FULL);
+    assert_eq!(Duty::from_percent(100), Duty::FULL);
+}
+
+#[test]
+#[should_panic]
+fn duty_cannot_exceed_one() {
+    Duty::from_ratio(3, 2);
+}
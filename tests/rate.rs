@@ -0,0 +1,78 @@
+use embedded_platform::time::Duration;
+use embedded_platform::time::Rate;
+use embedded_platform::time::U32Ext;
+use proptest::prelude::*;
+
+#[test]
+fn unit_constructors() {
+    assert_eq!(1.khz(), 1_000.hz());
+    assert_eq!(1.mhz(), 1_000.khz());
+    assert_eq!(4_000.mhz().as_hz(), 4_000_000_000);
+    assert_eq!(Rate::from_hz(7).as_hz(), 7);
+}
+
+#[test]
+fn sub_hertz_rates() {
+    let rate = Rate::from_period(Duration::from_seconds(2));
+    assert_eq!(rate.as_hz(), 0);
+    assert!(rate < 1.hz());
+    assert_eq!(rate.period(), Duration::from_seconds(2));
+}
+
+#[test]
+fn high_rates_are_exact() {
+    assert_eq!(64.mhz().period_ticks(64.mhz()), Some(1));
+    assert_eq!(1.hz().period_ticks(64.mhz()), Some(64_000_000));
+    assert_eq!(3.mhz().period_ticks(16.mhz()), Some(5));
+    assert_eq!(1.mhz().period(), Duration::from_nanos(1_000));
+}
+
+#[test]
+fn period_ticks_overflow() {
+    let slow = Rate::from_period(Duration::MAX);
+    assert_eq!(slow.period_ticks(4_000.mhz()), None);
+}
+
+#[test]
+#[should_panic]
+fn zero_rate_panics() {
+    Rate::from_hz(0);
+}
+
+proptest! {
+    #[test]
+    fn period_round_trips(nanos in 1..=u64::MAX) {
+        let period = Duration::from_nanos(nanos);
+        prop_assert_eq!(Rate::from_period(period).period(), period);
+    }
+
+    #[test]
+    fn hz_round_trips(hz in 1..=u32::MAX) {
+        prop_assert_eq!(hz.hz().as_hz(), u64::from(hz));
+    }
+
+    #[test]
+    fn period_of_hz_is_rounded_down(hz in 1..=u32::MAX) {
+        let nanos = hz.hz().period().as_nanos();
+        prop_assert_eq!(nanos, 1_000_000_000 / u64::from(hz));
+    }
+
+    #[test]
+    fn equal_rates_compare_equal(hz in 1..=4_000_000u32) {
+        prop_assert_eq!((hz * 1_000).hz(), hz.khz());
+        prop_assert_eq!(
+            Rate::from_period(hz.hz().period()) == hz.hz(),
+            1_000_000_000 % u64::from(hz) == 0
+        );
+    }
+
+    #[test]
+    fn ordering_matches_hz(a in 1..=u32::MAX, b in 1..=u32::MAX) {
+        prop_assert_eq!(a.hz().cmp(&b.hz()), a.cmp(&b));
+    }
+
+    #[test]
+    fn period_ticks_of_own_rate_is_one(hz in 1..=u32::MAX) {
+        prop_assert_eq!(hz.hz().period_ticks(hz.hz()), Some(1));
+    }
+}
@@ -0,0 +1,212 @@
+use embedded_platform::prelude::*;
+use embedded_platform::time::Duration;
+use embedded_platform::timer::service::Service;
+use embedded_platform::timer::timeout::Elapsed;
+use futures::future;
+use futures::task::ArcWake;
+use std::cell::Cell;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+#[derive(Debug, PartialEq)]
+enum Error {
+    Elapsed,
+}
+
+impl From<Elapsed> for Error {
+    fn from(_: Elapsed) -> Self {
+        Error::Elapsed
+    }
+}
+
+/// A periodic hardware timer whose ticks are fed by the test, and that records whether it runs.
+#[derive(Debug)]
+struct MockTimer(Rc<Cell<u32>>, Rc<Cell<bool>>);
+
+impl embedded_platform::timer::Timer for MockTimer {
+    type Error = Error;
+
+    fn poll_start(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.1.set(true);
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_tick(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        match self.0.get() {
+            0 => Poll::Pending,
+            ticks => {
+                self.0.set(ticks - 1);
+                Poll::Ready(Ok(()))
+            }
+        }
+    }
+}
+
+impl embedded_platform::timer::CancellableTimer for MockTimer {
+    fn poll_cancel(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.0.set(0);
+        self.1.set(false);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl embedded_platform::timer::IntoPeriodicTimer for MockTimer {
+    type PeriodicTimer = Self;
+
+    fn into_periodic_timer(self, _rate: embedded_platform::time::Rate) -> Result<Self, Error> {
+        Ok(self)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Wakes(AtomicUsize);
+
+impl ArcWake for Wakes {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn service<const N: usize>() -> (Service<MockTimer, N>, Rc<Cell<u32>>) {
+    let (service, ticks, _) = tracked_service();
+    (service, ticks)
+}
+
+fn tracked_service<const N: usize>() -> (Service<MockTimer, N>, Rc<Cell<u32>>, Rc<Cell<bool>>) {
+    let ticks = Rc::new(Cell::new(0));
+    let running = Rc::new(Cell::new(false));
+    let timer = MockTimer(ticks.clone(), running.clone());
+    let service = Service::new(timer, Duration::from_millis(1)).unwrap();
+    (service, ticks, running)
+}
+
+fn poll<F>(future: &mut F, wakes: &Arc<Wakes>) -> Poll<F::Output>
+where
+    F: future::Future + Unpin,
+{
+    let waker = futures::task::waker(wakes.clone());
+    let mut cx = Context::from_waker(&waker);
+    Pin::new(future).poll(&mut cx)
+}
+
+#[test]
+fn sleep_lasts_at_least_its_duration() {
+    let (service, ticks) = service::<2>();
+    let wakes = Arc::new(Wakes::default());
+    let mut sleep = service.sleep(Duration::from_micros(2_500));
+
+    assert_eq!(poll(&mut sleep, &wakes), Poll::Pending);
+    ticks.set(3);
+    assert_eq!(poll(&mut sleep, &wakes), Poll::Pending);
+    ticks.set(1);
+    assert_eq!(poll(&mut sleep, &wakes), Poll::Ready(Ok(())));
+}
+
+#[test]
+fn delays_share_one_timer() {
+    let (service, ticks) = service::<2>();
+    let short_wakes = Arc::new(Wakes::default());
+    let long_wakes = Arc::new(Wakes::default());
+    let mut short = service.sleep(Duration::from_millis(1));
+    let mut long = service.sleep(Duration::from_millis(3));
+
+    assert_eq!(poll(&mut long, &long_wakes), Poll::Pending);
+    assert_eq!(poll(&mut short, &short_wakes), Poll::Pending);
+
+    ticks.set(2);
+    assert_eq!(poll(&mut short, &short_wakes), Poll::Ready(Ok(())));
+    // The short sleep was driving the hardware timer, so the long one is asked to take over.
+    assert_eq!(long_wakes.0.load(Ordering::SeqCst), 1);
+
+    ticks.set(2);
+    assert_eq!(poll(&mut long, &long_wakes), Poll::Ready(Ok(())));
+}
+
+#[test]
+fn interval_ticks_periodically() {
+    let (service, ticks) = service::<1>();
+    let wakes = Arc::new(Wakes::default());
+    let mut interval = service.interval(500u32.hz());
+
+    futures::executor::block_on(interval.start()).unwrap();
+    let mut tick = interval.tick();
+    assert_eq!(poll(&mut tick, &wakes), Poll::Pending);
+
+    ticks.set(4);
+    assert_eq!(poll(&mut tick, &wakes), Poll::Ready(Ok(())));
+    let mut tick = interval.tick();
+    assert_eq!(poll(&mut tick, &wakes), Poll::Ready(Ok(())));
+    let mut tick = interval.tick();
+    assert_eq!(poll(&mut tick, &wakes), Poll::Pending);
+}
+
+#[test]
+fn slots_are_released_on_drop() {
+    let (service, _) = service::<1>();
+    let timer = service.try_timer();
+
+    assert!(timer.is_some());
+    assert!(service.try_timer().is_none());
+    drop(timer);
+    assert!(service.try_timer().is_some());
+}
+
+#[test]
+fn handles_can_time_out_futures() {
+    let (service, ticks) = service::<1>();
+    let wakes = Arc::new(Wakes::default());
+    let timer = service.timer();
+    let mut timeout = timer
+        .timeout(Duration::from_millis(1), future::pending::<()>())
+        .unwrap();
+
+    assert_eq!(
+        poll(&mut timeout, &wakes).map(|(_, result)| result),
+        Poll::Pending
+    );
+    ticks.set(2);
+    assert_eq!(
+        poll(&mut timeout, &wakes).map(|(_, result)| result),
+        Poll::Ready(Err(Error::Elapsed))
+    );
+}
+
+#[test]
+fn hardware_timer_only_runs_while_deadlines_are_pending() {
+    let (service, ticks, running) = tracked_service::<3>();
+    let wakes = Arc::new(Wakes::default());
+    let mut timer = service.timer();
+
+    assert_eq!(poll(&mut timer.tick(), &wakes), Poll::Pending);
+    assert!(!running.get());
+
+    let mut sleep = service.sleep(Duration::from_millis(1));
+    assert_eq!(poll(&mut sleep, &wakes), Poll::Pending);
+    assert!(running.get());
+    ticks.set(2);
+    assert_eq!(poll(&mut sleep, &wakes), Poll::Ready(Ok(())));
+    assert!(!running.get());
+
+    let mut interval = service.interval(1000u32.hz());
+    futures::executor::block_on(interval.start()).unwrap();
+    assert_eq!(poll(&mut interval.tick(), &wakes), Poll::Pending);
+    assert!(running.get());
+    drop(interval);
+    assert!(!running.get());
+
+    futures::executor::block_on(timer.restart(Duration::from_millis(1))).unwrap();
+    assert_eq!(poll(&mut timer.tick(), &wakes), Poll::Pending);
+    assert!(running.get());
+    futures::executor::block_on(timer.cancel()).unwrap();
+    assert!(!running.get());
+}
+
+#[test]
+#[should_panic(expected = "resolution of a timer service must not be zero")]
+fn zero_resolution_is_rejected() {
+    let timer = MockTimer(Rc::new(Cell::new(0)), Rc::new(Cell::new(false)));
+    let _ = Service::<_, 1>::new(timer, Duration::ZERO);
+}
@@ -0,0 +1,117 @@
+use embedded_platform::time::Duration;
+use embedded_platform::time::Instant;
+use embedded_platform::time::OutOfRangeError;
+use std::convert::TryFrom;
+
+#[test]
+fn constructors_use_correct_units() {
+    assert_eq!(Duration::from_nanos(1).as_nanos(), 1);
+    assert_eq!(Duration::from_micros(1).as_nanos(), 1_000);
+    assert_eq!(Duration::from_millis(1).as_nanos(), 1_000_000);
+    assert_eq!(Duration::from_seconds(1).as_nanos(), 1_000_000_000);
+}
+
+#[test]
+fn accessors_truncate() {
+    let duration = Duration::from_nanos(1_999_999_999);
+    assert_eq!(duration.as_micros(), 1_999_999);
+    assert_eq!(duration.as_millis(), 1_999);
+    assert_eq!(duration.as_seconds(), 1);
+}
+
+#[test]
+fn long_durations_do_not_overflow() {
+    let duration = Duration::from_seconds(5000);
+    assert_eq!(duration.as_seconds(), 5000);
+    assert_eq!(duration.as_millis(), 5_000_000);
+
+    let max_seconds = u64::MAX / 1_000_000_000;
+    assert_eq!(
+        Duration::from_seconds(max_seconds).as_seconds(),
+        max_seconds
+    );
+}
+
+#[test]
+#[should_panic]
+fn from_seconds_panics_on_overflow() {
+    Duration::from_seconds(u64::MAX / 1_000_000_000 + 1);
+}
+
+#[test]
+fn arithmetic() {
+    let second = Duration::from_seconds(1);
+    let milli = Duration::from_millis(1);
+
+    assert_eq!(second + milli, Duration::from_millis(1001));
+    assert_eq!(second - milli, Duration::from_millis(999));
+    assert_eq!(milli * 1000, second);
+    assert_eq!(second / 1000, milli);
+
+    let mut duration = second;
+    duration += milli;
+    duration -= second;
+    duration *= 4;
+    duration /= 2;
+    assert_eq!(duration, Duration::from_millis(2));
+}
+
+#[test]
+#[should_panic]
+fn sub_panics_on_underflow() {
+    let _ = Duration::from_millis(1) - Duration::from_seconds(1);
+}
+
+#[test]
+fn checked_arithmetic() {
+    let one = Duration::from_nanos(1);
+
+    assert_eq!(Duration::MAX.checked_add(one), None);
+    assert_eq!(Duration::ZERO.checked_sub(one), None);
+    assert_eq!(Duration::MAX.checked_mul(2), None);
+    assert_eq!(one.checked_div(0), None);
+    assert_eq!(
+        Duration::MAX.checked_sub(one),
+        Some(Duration::from_nanos(u64::MAX - 1))
+    );
+}
+
+#[test]
+fn saturating_arithmetic() {
+    let one = Duration::from_nanos(1);
+
+    assert_eq!(Duration::MAX.saturating_add(one), Duration::MAX);
+    assert_eq!(Duration::ZERO.saturating_sub(one), Duration::ZERO);
+    assert_eq!(Duration::MAX.saturating_mul(2), Duration::MAX);
+    assert!(Duration::ZERO.is_zero());
+}
+
+#[test]
+fn core_duration_conversions() {
+    let duration = Duration::from_nanos(1_500_000_001);
+    let core_duration = core::time::Duration::from(duration);
+    assert_eq!(core_duration, core::time::Duration::new(1, 500_000_001));
+    assert_eq!(Duration::try_from(core_duration), Ok(duration));
+
+    let max = core::time::Duration::from_nanos(u64::MAX);
+    assert_eq!(Duration::try_from(max), Ok(Duration::MAX));
+    assert_eq!(
+        Duration::try_from(max + core::time::Duration::from_nanos(1)),
+        Err(OutOfRangeError)
+    );
+}
+
+#[test]
+fn instant_arithmetic() {
+    let start = Instant::from_nanos(1_000);
+    let later = start + Duration::from_micros(5);
+
+    assert_eq!(later - start, Duration::from_micros(5));
+    assert_eq!(later - Duration::from_micros(5), start);
+    assert_eq!(start.checked_duration_since(later), None);
+    assert_eq!(start.saturating_duration_since(later), Duration::ZERO);
+    assert_eq!(
+        Instant::from_nanos(u64::MAX).checked_add(Duration::from_nanos(1)),
+        None
+    );
+}
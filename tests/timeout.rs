@@ -0,0 +1,209 @@
+use embedded_platform::clock::Clock;
+use embedded_platform::prelude::*;
+use embedded_platform::time::Duration;
+use embedded_platform::time::Instant;
+use embedded_platform::timer::timeout::Elapsed;
+use futures::future;
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+#[derive(Debug, PartialEq)]
+enum Error {
+    Elapsed,
+    OutOfRange,
+    Peripheral,
+}
+
+impl From<Elapsed> for Error {
+    fn from(_: Elapsed) -> Self {
+        Error::Elapsed
+    }
+}
+
+/// Records how the mock timer was used, and lets the test decide when it expires.
+#[derive(Debug, Default)]
+struct Shared {
+    configured: Cell<Option<Duration>>,
+    started: Cell<bool>,
+    expired: Cell<bool>,
+}
+
+#[derive(Debug)]
+struct MockTimer(Rc<Shared>);
+
+impl embedded_platform::timer::Timer for MockTimer {
+    type Error = Error;
+
+    fn poll_start(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.0.started.set(true);
+        self.0.expired.set(false);
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_tick(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        if self.0.expired.get() {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl embedded_platform::timer::IntoOneshotTimer for MockTimer {
+    type OneshotTimer = Self;
+
+    fn into_oneshot_timer(self, delay: Duration) -> Result<Self, Error> {
+        if delay == Duration::MAX {
+            return Err(Error::OutOfRange);
+        }
+        self.0.configured.set(Some(delay));
+        self.0.started.set(false);
+        self.0.expired.set(false);
+        Ok(self)
+    }
+}
+
+#[derive(Debug)]
+struct MockClock(Instant);
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.0
+    }
+}
+
+fn poll<F>(future: &mut F) -> Poll<F::Output>
+where
+    F: future::Future + Unpin,
+{
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    Pin::new(future).poll(&mut cx)
+}
+
+fn mock() -> (MockTimer, Rc<Shared>) {
+    let shared = Rc::new(Shared::default());
+    (MockTimer(shared.clone()), shared)
+}
+
+#[test]
+fn ready_future_does_not_start_timer() {
+    let (timer, shared) = mock();
+    let timeout = timer
+        .timeout(Duration::from_millis(5), future::ready(7))
+        .unwrap();
+    let (_timer, result) = futures::executor::block_on(timeout);
+
+    assert_eq!(result, Ok(7));
+    assert_eq!(shared.configured.get(), Some(Duration::from_millis(5)));
+    assert!(!shared.started.get());
+}
+
+#[test]
+fn pending_future_times_out() {
+    let (timer, shared) = mock();
+    let mut timeout = future::pending::<()>()
+        .with_timeout(timer, Duration::from_millis(5))
+        .unwrap();
+
+    assert_eq!(poll(&mut timeout).map(|(_, result)| result), Poll::Pending);
+    assert_eq!(shared.configured.get(), Some(Duration::from_millis(5)));
+    assert!(shared.started.get());
+
+    shared.expired.set(true);
+    assert_eq!(
+        poll(&mut timeout).map(|(_, result)| result),
+        Poll::Ready(Err(Error::Elapsed))
+    );
+}
+
+#[test]
+fn future_wins_race_against_timer() {
+    let (timer, shared) = mock();
+    let mut ready = false;
+    let inner = future::poll_fn(move |_| {
+        if ready {
+            Poll::Ready(Err::<(), _>(Error::Peripheral))
+        } else {
+            ready = true;
+            Poll::Pending
+        }
+    });
+    let mut timeout = timer.timeout(Duration::from_millis(5), inner).unwrap();
+
+    assert_eq!(poll(&mut timeout).map(|(_, result)| result), Poll::Pending);
+    shared.expired.set(true);
+    assert_eq!(
+        poll(&mut timeout).map(|(_, result)| result),
+        Poll::Ready(Ok(Err(Error::Peripheral)))
+    );
+}
+
+#[test]
+fn timer_is_handed_back_for_the_next_timeout() {
+    let (timer, shared) = mock();
+
+    let mut timeout = timer
+        .timeout(Duration::from_millis(5), future::pending::<()>())
+        .unwrap();
+    assert_eq!(poll(&mut timeout).map(|(_, result)| result), Poll::Pending);
+    shared.expired.set(true);
+    let timer = match poll(&mut timeout) {
+        Poll::Ready((timer, result)) => {
+            assert_eq!(result, Err(Error::Elapsed));
+            timer
+        }
+        Poll::Pending => panic!("the timer has expired"),
+    };
+
+    let mut timeout = timer
+        .timeout(Duration::from_millis(7), future::pending::<()>())
+        .unwrap();
+    assert_eq!(shared.configured.get(), Some(Duration::from_millis(7)));
+    assert_eq!(poll(&mut timeout).map(|(_, result)| result), Poll::Pending);
+    assert!(shared.started.get());
+}
+
+#[test]
+fn async_blocks_can_be_timed_out() {
+    let (timer, shared) = mock();
+    let inner = async {
+        future::pending::<()>().await;
+    };
+    let timeout = inner.with_timeout(timer, Duration::from_millis(5)).unwrap();
+    futures::pin_mut!(timeout);
+
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    assert!(timeout.as_mut().poll(&mut cx).is_pending());
+    shared.expired.set(true);
+    match timeout.as_mut().poll(&mut cx) {
+        Poll::Ready((_, result)) => assert_eq!(result, Err(Error::Elapsed)),
+        Poll::Pending => panic!("the timer has expired"),
+    }
+}
+
+#[test]
+fn timer_errors_are_reported() {
+    let (timer, _) = mock();
+    let result = timer.timeout(Duration::MAX, future::pending::<()>());
+
+    assert_eq!(result.err(), Some(Error::OutOfRange));
+}
+
+#[test]
+fn deadline_is_relative_to_clock() {
+    let (timer, shared) = mock();
+    let clock = MockClock(Instant::from_nanos(1_000));
+    let _timeout = timer
+        .deadline(&clock, Instant::from_nanos(3_000), future::pending::<()>())
+        .unwrap();
+    assert_eq!(shared.configured.get(), Some(Duration::from_nanos(2_000)));
+
+    let (timer, shared) = mock();
+    let _timeout = future::pending::<()>()
+        .with_deadline(timer, &clock, Instant::from_nanos(0))
+        .unwrap();
+    assert_eq!(shared.configured.get(), Some(Duration::ZERO));
+}
@@ -0,0 +1,198 @@
+use embedded_platform::prelude::*;
+use embedded_platform::time::Duration;
+use embedded_platform::timer::service::Service;
+use futures::future;
+use std::cell::Cell;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+/// A oneshot timer that counts the nanoseconds of a shared, manually advanced clock.
+#[derive(Debug)]
+struct MockTimer {
+    now: Rc<Cell<u64>>,
+    delay: u64,
+    started: Option<u64>,
+    stopped: Option<u64>,
+}
+
+impl MockTimer {
+    fn new(delay: Duration) -> (Self, Rc<Cell<u64>>) {
+        let now = Rc::new(Cell::new(0));
+        let timer = MockTimer {
+            now: now.clone(),
+            delay: delay.as_nanos(),
+            started: None,
+            stopped: None,
+        };
+        (timer, now)
+    }
+}
+
+impl embedded_platform::timer::Timer for MockTimer {
+    type Error = ();
+
+    fn poll_start(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), ()>> {
+        self.started = Some(self.now.get());
+        self.stopped = None;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_tick(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), ()>> {
+        match self.started {
+            Some(started) if self.stopped.is_none() && self.now.get() >= started + self.delay => {
+                self.stopped = Some(started + self.delay);
+                Poll::Ready(Ok(()))
+            }
+            _ => Poll::Pending,
+        }
+    }
+}
+
+impl embedded_platform::timer::CancellableTimer for MockTimer {
+    fn poll_cancel(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), ()>> {
+        if self.started.is_some() && self.stopped.is_none() {
+            self.stopped = Some(self.now.get());
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl embedded_platform::timer::RestartableTimer for MockTimer {
+    fn poll_restart(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        delay: Duration,
+    ) -> Poll<Result<(), ()>> {
+        self.delay = delay.as_nanos();
+        self.started = Some(self.now.get());
+        self.stopped = None;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl embedded_platform::timer::CountingTimer for MockTimer {
+    fn poll_elapsed(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<Duration, ()>> {
+        let elapsed = self.started.map_or(0, |started| {
+            self.stopped.unwrap_or_else(|| self.now.get()) - started
+        });
+        Poll::Ready(Ok(Duration::from_nanos(elapsed)))
+    }
+}
+
+/// A periodic hardware timer whose ticks are fed by the test.
+#[derive(Debug)]
+struct MockTickSource(Rc<Cell<u32>>);
+
+impl embedded_platform::timer::Timer for MockTickSource {
+    type Error = ();
+
+    fn poll_start(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), ()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_tick(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), ()>> {
+        match self.0.get() {
+            0 => Poll::Pending,
+            ticks => {
+                self.0.set(ticks - 1);
+                Poll::Ready(Ok(()))
+            }
+        }
+    }
+}
+
+impl embedded_platform::timer::CancellableTimer for MockTickSource {
+    fn poll_cancel(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), ()>> {
+        self.0.set(0);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl embedded_platform::timer::IntoPeriodicTimer for MockTickSource {
+    type PeriodicTimer = Self;
+
+    fn into_periodic_timer(self, _rate: embedded_platform::time::Rate) -> Result<Self, ()> {
+        Ok(self)
+    }
+}
+
+fn poll<F>(future: &mut F) -> Poll<F::Output>
+where
+    F: future::Future + Unpin,
+{
+    let mut cx = Context::from_waker(futures::task::noop_waker_ref());
+    Pin::new(future).poll(&mut cx)
+}
+
+fn ms(millis: u64) -> u64 {
+    Duration::from_millis(millis).as_nanos()
+}
+
+#[test]
+fn restart_postpones_expiry() {
+    let (mut timer, now) = MockTimer::new(Duration::from_millis(10));
+    futures::executor::block_on(timer.start()).unwrap();
+
+    now.set(ms(8));
+    futures::executor::block_on(timer.restart(Duration::from_millis(10))).unwrap();
+    now.set(ms(16));
+    assert_eq!(poll(&mut timer.tick()), Poll::Pending);
+
+    now.set(ms(18));
+    assert_eq!(poll(&mut timer.tick()), Poll::Ready(Ok(())));
+}
+
+#[test]
+fn cancel_discards_tick() {
+    let (mut timer, now) = MockTimer::new(Duration::from_millis(10));
+    futures::executor::block_on(timer.start()).unwrap();
+
+    now.set(ms(5));
+    futures::executor::block_on(timer.cancel()).unwrap();
+    now.set(ms(20));
+    assert_eq!(poll(&mut timer.tick()), Poll::Pending);
+}
+
+#[test]
+fn elapsed_measures_pulse_width() {
+    let (mut timer, now) = MockTimer::new(Duration::from_millis(100));
+    assert_eq!(
+        futures::executor::block_on(timer.elapsed()),
+        Ok(Duration::ZERO)
+    );
+
+    now.set(ms(2));
+    futures::executor::block_on(timer.start()).unwrap();
+    now.set(ms(5));
+    assert_eq!(
+        futures::executor::block_on(timer.elapsed()),
+        Ok(Duration::from_millis(3))
+    );
+
+    futures::executor::block_on(timer.cancel()).unwrap();
+    now.set(ms(9));
+    assert_eq!(
+        futures::executor::block_on(timer.elapsed()),
+        Ok(Duration::from_millis(3))
+    );
+}
+
+#[test]
+fn service_handles_can_be_kicked() {
+    let ticks = Rc::new(Cell::new(0));
+    let service: Service<_, 1> =
+        Service::new(MockTickSource(ticks.clone()), Duration::from_millis(1)).unwrap();
+    let mut watchdog = service.delay(Duration::from_millis(2));
+    futures::executor::block_on(watchdog.start()).unwrap();
+
+    ticks.set(2);
+    assert_eq!(poll(&mut watchdog.tick()), Poll::Pending);
+    futures::executor::block_on(watchdog.restart(Duration::from_millis(2))).unwrap();
+    ticks.set(2);
+    assert_eq!(poll(&mut watchdog.tick()), Poll::Pending);
+
+    futures::executor::block_on(watchdog.cancel()).unwrap();
+    ticks.set(5);
+    assert_eq!(poll(&mut watchdog.tick()), Poll::Pending);
+}